@@ -0,0 +1,175 @@
+//! Reading the abstract of a record in the terminal.
+
+use crate::error::{Error, Result};
+use crate::output;
+use adsabs::{search::Document, Ads};
+use console::{style, Term};
+use std::io::{self, Write};
+
+// The widest that the text is wrapped, even in wide terminals
+const MAX_WIDTH: usize = 80;
+
+#[derive(clap::Args)]
+pub struct AbstractArgs {
+    /// A bibcode, or a search query
+    #[arg(required = true)]
+    target: Vec<String>,
+
+    /// The number of matching records to show
+    #[arg(short = 'n', long, default_value_t = 1)]
+    limit: u64,
+}
+
+pub fn run(client: &Ads, args: &AbstractArgs) -> Result<()> {
+    let docs = client
+        .search(&query(&args.target))
+        .fl("bibcode,title,author,pub,year,abstract")
+        .iter_docs()
+        .limit(args.limit)
+        .collect::<adsabs::Result<Vec<_>>>()?;
+    if docs.is_empty() {
+        return Err(Error::NoResults);
+    }
+
+    let width = match Term::stdout().size_checked() {
+        Some((_, columns)) => (columns as usize).min(MAX_WIDTH),
+        None => MAX_WIDTH,
+    };
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for (n, mut doc) in docs.into_iter().enumerate() {
+        if n > 0 {
+            writeln!(out)?;
+        }
+        doc.decode_html();
+        write_abstract(&mut out, &doc, width)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// A single bibcode is looked up directly, and anything else is searched for.
+fn query(target: &[String]) -> String {
+    match target {
+        [bibcode] if output::is_bibcode(bibcode) => format!("bibcode:\"{}\"", bibcode),
+        _ => target.join(" "),
+    }
+}
+
+fn write_abstract(out: &mut impl Write, doc: &Document, width: usize) -> Result<()> {
+    for line in wrap(&output::title(doc), width) {
+        writeln!(out, "{}", style(line).bold())?;
+    }
+    if let Some(authors) = &doc.author {
+        for line in wrap(&authors.join("; "), width) {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    let source = [doc.publication.as_deref(), doc.year.as_deref()]
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !source.is_empty() {
+        writeln!(out, "{}", style(source).dim())?;
+    }
+    if let Some(bibcode) = &doc.bibcode {
+        writeln!(out, "{}", style(output::abs_url(bibcode)).dim())?;
+    }
+    writeln!(out)?;
+    match doc.abs.as_deref() {
+        Some(text) if !text.trim().is_empty() => {
+            for line in wrap(text, width) {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        _ => writeln!(out, "{}", style("No abstract available.").italic())?,
+    }
+    Ok(())
+}
+
+/// Wrap text into lines of at most `width` characters, breaking at
+/// whitespace; words longer than a line are left on a line of their own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut length = 0;
+    for word in text.split_whitespace() {
+        let count = word.chars().count();
+        if length > 0 && length + 1 + count > width {
+            lines.push(std::mem::take(&mut line));
+            length = 0;
+        }
+        if length > 0 {
+            line.push(' ');
+            length += 1;
+        }
+        line.push_str(word);
+        length += count;
+    }
+    if length > 0 {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets() {
+        assert_eq!(
+            query(&["2013PASP..125..306F".to_owned()]),
+            "bibcode:\"2013PASP..125..306F\""
+        );
+        assert_eq!(
+            query(&["emcee".to_owned(), "year:2013".to_owned()]),
+            "emcee year:2013"
+        );
+    }
+
+    #[test]
+    fn wrapping() {
+        assert_eq!(
+            wrap(
+                "We introduce  a stable,\nwell tested Python implementation",
+                20
+            ),
+            [
+                "We introduce a",
+                "stable, well tested",
+                "Python",
+                "implementation"
+            ]
+        );
+        assert_eq!(
+            wrap("an extraordinarily long word", 5),
+            ["an", "extraordinarily", "long", "word"]
+        );
+        assert!(wrap("  ", 10).is_empty());
+    }
+
+    #[test]
+    fn rendering() {
+        console::set_colors_enabled(false);
+        let doc = Document {
+            bibcode: Some("2013PASP..125..306F".to_owned()),
+            title: Some(vec!["emcee: The MCMC Hammer".to_owned()]),
+            author: Some(vec!["Foreman-Mackey, D".to_owned(), "Hogg, D".to_owned()]),
+            publication: Some("PASP".to_owned()),
+            year: Some("2013".to_owned()),
+            abs: Some("We introduce a stable implementation.".to_owned()),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_abstract(&mut out, &doc, 20).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "emcee: The MCMC\nHammer\nForeman-Mackey, D;\nHogg, D\nPASP, 2013\n\
+             https://ui.adsabs.harvard.edu/abs/2013PASP..125..306F/abstract\n\n\
+             We introduce a\nstable\nimplementation.\n"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Running saved queries from the configuration file.
+//!
+//! Queries are saved in the `[queries]` table of the configuration file, and
+//! they can include placeholders like `{year}` that are filled in using the
+//! `--param` option. The values are escaped as described for
+//! [`adsabs::search::QueryTemplate`], so they can't change the structure of
+//! the query:
+//!
+//! ```toml
+//! [queries]
+//! mypapers = 'author:"^Foreman-Mackey, D" year:{year}'
+//! ```
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::search;
+use adsabs::{search::QueryTemplate, Ads};
+use std::io::{self, Write};
+
+#[derive(clap::Args)]
+pub struct RunArgs {
+    /// The name of the saved query; all saved queries are listed if this is
+    /// omitted
+    name: Option<String>,
+
+    /// A value for a placeholder in the query, e.g. year=2020; this can be
+    /// repeated
+    #[arg(short, long = "param", value_parser = parse_param)]
+    params: Vec<(String, String)>,
+
+    #[command(flatten)]
+    results: search::ResultArgs,
+}
+
+pub fn run(client: impl FnOnce() -> Result<Ads>, config: &Config, args: &RunArgs) -> Result<()> {
+    let name = match &args.name {
+        Some(name) => name,
+        None => {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            for (name, query) in &config.queries {
+                writeln!(out, "{}\t{}", name, query)?;
+            }
+            return Ok(());
+        }
+    };
+    let template = config
+        .queries
+        .get(name)
+        .ok_or_else(|| Error::UnknownQuery(name.clone()))?;
+    let query = render(template, &args.params)?;
+    search::run(&client()?, &query, &args.results)
+}
+
+/// Fill in the placeholders of a saved query with escaped values.
+fn render(template: &str, params: &[(String, String)]) -> Result<String> {
+    let template: QueryTemplate = template.parse()?;
+    Ok(template.render(params)?)
+}
+
+fn parse_param(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.trim().to_owned(), value.to_owned()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendering() {
+        let params = vec![
+            ("year".to_owned(), "2020".to_owned()),
+            ("author".to_owned(), "Smith\" OR x".to_owned()),
+        ];
+        assert_eq!(
+            render("author:\"^{author}\" year:{year}", &params).unwrap(),
+            "author:\"^Smith\\\" OR x\" year:2020"
+        );
+        assert!(matches!(
+            render("year:{year} {bibstem}", &params),
+            Err(Error::Ads(adsabs::AdsError::MissingParameter(name))) if name == "bibstem"
+        ));
+        assert!(matches!(
+            render("year:{year", &params),
+            Err(Error::Ads(adsabs::AdsError::InvalidTemplate(_)))
+        ));
+    }
+
+    #[test]
+    fn params() {
+        assert_eq!(
+            parse_param("year=2020-2021"),
+            Ok(("year".to_owned(), "2020-2021".to_owned()))
+        );
+        assert!(parse_param("year").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Sending arbitrary, authenticated requests to the API.
+//!
+//! This makes it possible to script endpoints that aren't wrapped by any of
+//! the other commands, e.g. `ads api GET search/query -p q=star -p rows=1`.
+
+use crate::error::Result;
+use crate::output;
+use adsabs::Ads;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct ApiArgs {
+    /// The HTTP method, e.g. GET or POST
+    method: String,
+
+    /// The path of the endpoint, relative to the base URL of the API, e.g.
+    /// search/query
+    path: String,
+
+    /// Add a query parameter, as key=value; this can be repeated
+    #[arg(short, long = "param", value_name = "KEY=VALUE", value_parser = parse_pair)]
+    params: Vec<(String, String)>,
+
+    /// Add a field to the JSON body, as key=value, where values that are valid
+    /// JSON are included as is, and anything else as a string; this can be
+    /// repeated
+    #[arg(short, long = "field", value_name = "KEY=VALUE", value_parser = parse_pair)]
+    fields: Vec<(String, String)>,
+
+    /// Read the JSON body from a file, or from stdin if this is "-"
+    #[arg(long, value_name = "PATH", conflicts_with = "fields")]
+    input: Option<PathBuf>,
+}
+
+pub fn run(client: &Ads, args: &ApiArgs) -> Result<()> {
+    let body = match &args.input {
+        Some(path) => {
+            let text = if path.as_os_str() == "-" {
+                io::read_to_string(io::stdin())?
+            } else {
+                fs::read_to_string(path)?
+            };
+            Some(serde_json::from_str(&text)?)
+        }
+        None if !args.fields.is_empty() => Some(fields_body(&args.fields)),
+        None => None,
+    };
+    let response = client.request_raw(
+        &args.method,
+        args.path.trim_start_matches('/'),
+        &args.params,
+        body.as_ref(),
+    )?;
+
+    let stdout = io::stdout();
+    output::write_raw(&mut stdout.lock(), &response)
+}
+
+/// Build a JSON object from the `--field` options.
+fn fields_body(fields: &[(String, String)]) -> Value {
+    let object = fields
+        .iter()
+        .map(|(key, value)| {
+            let value = serde_json::from_str(value).unwrap_or_else(|_| value.clone().into());
+            (key.clone(), value)
+        })
+        .collect();
+    Value::Object(object)
+}
+
+/// Parse a `key=value` pair.
+fn parse_pair(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+        _ => Err(format!("'{}' is not of the form key=value", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs() {
+        assert_eq!(
+            parse_pair("q=author:\"Hogg, D\""),
+            Ok(("q".to_owned(), "author:\"Hogg, D\"".to_owned()))
+        );
+        assert_eq!(parse_pair("fq="), Ok(("fq".to_owned(), String::new())));
+        assert!(parse_pair("rows").is_err());
+        assert!(parse_pair("=1").is_err());
+    }
+
+    #[test]
+    fn fields() {
+        let fields = [
+            ("action".to_owned(), "add".to_owned()),
+            ("bibcode".to_owned(), "[\"a\", \"b\"]".to_owned()),
+            ("rows".to_owned(), "10".to_owned()),
+        ];
+        assert_eq!(
+            fields_body(&fields),
+            serde_json::json!({"action": "add", "bibcode": ["a", "b"], "rows": 10})
+        );
+    }
+}
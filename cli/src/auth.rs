@@ -0,0 +1,178 @@
+//! Loading and storing the API token.
+//!
+//! The token is loaded from the first of the following locations where it can
+//! be found:
+//!
+//! 1. The `ADS_API_TOKEN` or `ADS_DEV_KEY` environment variables,
+//! 2. The system keyring, as stored by `ads auth login`,
+//! 3. The `token` entry in the configuration file, and
+//! 4. The token files checked by [`adsabs::AdsBuilder::from_env`], like
+//!    `$XDG_CONFIG_HOME/adsabs/token` or `~/.ads/token`.
+//!
+//! When a named profile is selected, only the keyring and the configuration
+//! file are checked, since the other locations can't distinguish between
+//! profiles.
+
+use crate::config::{Config, Profile};
+use crate::error::{Error, Result};
+use adsabs::AdsBuilder;
+use std::fmt;
+
+const KEYRING_SERVICE: &str = "adsabs";
+const DEFAULT_PROFILE: &str = "default";
+
+/// Where the API token was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Env(&'static str),
+    Keyring,
+    Config,
+    TokenFile,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Env(name) => write!(f, "the {} environment variable", name),
+            Source::Keyring => write!(f, "the system keyring"),
+            Source::Config => write!(f, "the configuration file"),
+            Source::TokenFile => write!(f, "a token file"),
+        }
+    }
+}
+
+#[derive(clap::Subcommand)]
+pub enum AuthCommand {
+    /// Store an API token in the system keyring
+    Login,
+    /// Report where the API token is loaded from
+    Status,
+    /// Remove the stored API token
+    Logout,
+}
+
+#[derive(clap::Subcommand)]
+pub enum TokenCommand {
+    /// Check that the API token authenticates and report the account it
+    /// belongs to
+    Verify,
+}
+
+/// Build a client builder for a profile, using the first token that can be
+/// found.
+pub fn builder(config: &Config, profile: Option<&str>) -> Result<(AdsBuilder, Source)> {
+    let settings = config.profile(profile)?;
+    let (mut builder, source) = find_token(settings, profile)?;
+    if let Some(base_url) = &settings.base_url {
+        builder = builder.base_url(base_url);
+    }
+    Ok((builder, source))
+}
+
+fn find_token(settings: &Profile, profile: Option<&str>) -> Result<(AdsBuilder, Source)> {
+    if profile.is_none() {
+        for name in &["ADS_API_TOKEN", "ADS_DEV_KEY"] {
+            if let Ok(token) = std::env::var(name) {
+                return Ok((AdsBuilder::new(&token), Source::Env(name)));
+            }
+        }
+    }
+    if let Some(token) = keyring_entry(profile).and_then(|entry| entry.get_password().ok()) {
+        return Ok((AdsBuilder::new(&token), Source::Keyring));
+    }
+    if let Some(token) = &settings.token {
+        return Ok((AdsBuilder::new(token), Source::Config));
+    }
+    if profile.is_none() {
+        Ok((AdsBuilder::from_env()?, Source::TokenFile))
+    } else {
+        Err(adsabs::AdsError::Token.into())
+    }
+}
+
+pub fn run(command: &AuthCommand, mut config: Config, profile: Option<&str>) -> Result<()> {
+    match command {
+        AuthCommand::Login => {
+            eprintln!("Generate a token at https://ui.adsabs.harvard.edu/user/settings/token");
+            let token = rpassword::prompt_password("ADS API token: ")?;
+            let token = token.trim();
+            match keyring_entry(profile).map(|entry| entry.set_password(token)) {
+                Some(Ok(())) => {
+                    // Make sure that the profile exists so that it can be selected
+                    if config.profile(profile).is_err() {
+                        config.profile_mut(profile);
+                        config.save()?;
+                    }
+                    eprintln!("token saved to the system keyring");
+                }
+                _ => {
+                    config.profile_mut(profile).token = Some(token.to_owned());
+                    config.save()?;
+                    eprintln!(
+                        "the system keyring is unavailable; token saved to {}",
+                        Config::path()?.display()
+                    );
+                }
+            }
+        }
+        AuthCommand::Status => match builder(&config, profile) {
+            Ok((_, source)) => println!("token loaded from {}", source),
+            Err(_) => println!("no token found; run `ads auth login` to add one"),
+        },
+        AuthCommand::Logout => {
+            if let Some(entry) = keyring_entry(profile) {
+                if entry.delete_credential().is_ok() {
+                    eprintln!("token removed from the system keyring");
+                }
+            }
+            if config.profile_mut(profile).token.take().is_some() {
+                config.save()?;
+                eprintln!("token removed from {}", Config::path()?.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run_token(command: &TokenCommand, config: &Config, profile: Option<&str>) -> Result<()> {
+    match command {
+        TokenCommand::Verify => {
+            let (builder, source) = builder(config, profile)?;
+            println!("token:     loaded from {}", source);
+            let client = builder.build()?;
+            let response = client.get("accounts/bootstrap", None::<&()>)?;
+            let status = response.status();
+            match status.as_u16() {
+                401 | 403 => {
+                    println!("status:    rejected by the API ({})", status);
+                    return Err(Error::Authentication(status.as_u16()));
+                }
+                // The token might still be valid, so these aren't reported
+                // as authentication failures
+                429 => {
+                    let reset = client.rate_limit().map(|rate_limit| rate_limit.reset);
+                    return Err(adsabs::AdsError::RateLimited { reset }.into());
+                }
+                _ if !status.is_success() => return Err(Error::HttpStatus(status.as_u16())),
+                _ => {}
+            }
+            println!("status:    authenticated");
+            let info: serde_json::Value = response.json().map_err(adsabs::AdsError::from)?;
+            match info.get("username").and_then(|name| name.as_str()) {
+                Some(name) => println!("account:   {}", name),
+                None => println!("account:   unknown"),
+            }
+            if let Some(rate_limit) = client.rate_limit() {
+                println!(
+                    "requests:  {} of {} remaining until {}",
+                    rate_limit.remaining, rate_limit.limit, rate_limit.reset
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn keyring_entry(profile: Option<&str>) -> Option<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, profile.unwrap_or(DEFAULT_PROFILE)).ok()
+}
@@ -0,0 +1,113 @@
+//! Summarizing the publications of an author.
+
+use crate::error::{Error, Result};
+use crate::output::{self, Format};
+use crate::search;
+use adsabs::{metrics::Metrics, search::Document, Ads};
+use console::style;
+use std::io::{self, Write};
+
+#[derive(clap::Args)]
+pub struct AuthorArgs {
+    /// The author, using the ADS author syntax, e.g. "Foreman-Mackey, D", or
+    /// "^Foreman-Mackey, D" for first author publications only
+    author: String,
+
+    /// The number of recent papers to list
+    #[arg(short = 'n', long, default_value_t = 5)]
+    recent: u64,
+}
+
+pub fn run(client: &Ads, args: &AuthorArgs) -> Result<()> {
+    let query = format!("author:{}", search::quote(&args.author));
+    let metrics = client.metrics_for_query(client.search(&query))?;
+    if metrics.all.papers == 0 {
+        return Err(Error::NoResults);
+    }
+    let recent = client
+        .search(&query)
+        .sort("date")
+        .iter_docs()
+        .limit(args.recent)
+        .collect::<adsabs::Result<Vec<_>>>()?;
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    write_summary(&mut out, &args.author, &metrics, &recent)?;
+    out.flush()?;
+    Ok(())
+}
+
+fn write_summary(
+    out: &mut impl Write,
+    author: &str,
+    metrics: &Metrics,
+    recent: &[Document],
+) -> Result<()> {
+    let (all, refereed) = (&metrics.all, &metrics.refereed);
+    writeln!(out, "{}", style(author).bold())?;
+    writeln!(
+        out,
+        "  papers     {} ({} refereed)",
+        all.papers, refereed.papers
+    )?;
+    writeln!(
+        out,
+        "  citations  {} ({} to refereed papers)",
+        all.citations, refereed.citations
+    )?;
+    writeln!(
+        out,
+        "  h-index    {} ({} for refereed papers)",
+        all.h_index, refereed.h_index
+    )?;
+    if !recent.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "{}", style("Recent papers").bold())?;
+        output::write_docs(out, Format::Text, recent.iter().cloned().map(Ok))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adsabs::metrics::MetricsTotals;
+
+    #[test]
+    fn summary() {
+        console::set_colors_enabled(false);
+        let metrics = Metrics {
+            all: MetricsTotals {
+                papers: 12,
+                citations: 340,
+                h_index: 7,
+                ..Default::default()
+            },
+            refereed: MetricsTotals {
+                papers: 9,
+                citations: 310,
+                h_index: 6,
+                ..Default::default()
+            },
+        };
+        let recent = [Document {
+            bibcode: Some("2013PASP..125..306F".to_owned()),
+            author: Some(vec!["Foreman-Mackey, D".to_owned()]),
+            year: Some("2013".to_owned()),
+            title: Some(vec!["emcee: The MCMC Hammer".to_owned()]),
+            ..Default::default()
+        }];
+        let mut out = Vec::new();
+        write_summary(&mut out, "Foreman-Mackey, D", &metrics, &recent).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Foreman-Mackey, D\n  \
+             papers     12 (9 refereed)\n  \
+             citations  340 (310 to refereed papers)\n  \
+             h-index    7 (6 for refereed papers)\n\n\
+             Recent papers\n\
+             2013PASP..125..306F  Foreman-Mackey, D (2013): emcee: The MCMC Hammer\n"
+        );
+    }
+}
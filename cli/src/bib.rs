@@ -0,0 +1,32 @@
+//! Keeping a local BibTeX file up to date with the results of a query.
+//!
+//! New records are added to the end of the file, and the publication details
+//! of existing entries are refreshed (e.g. when a preprint is published), but
+//! any other edits made to the file by hand are kept.
+
+use crate::error::Result;
+use crate::search::QueryArgs;
+use adsabs::Ads;
+use std::path::PathBuf;
+
+#[derive(clap::Args)]
+pub struct BibArgs {
+    /// The BibTeX file to update, which is created if it doesn't exist
+    file: PathBuf,
+
+    #[command(flatten)]
+    query: QueryArgs,
+}
+
+pub fn run(client: &Ads, args: &BibArgs) -> Result<()> {
+    let query = args.query.build()?;
+    let report = adsabs::export::update_bib_file(client.search(&query), &args.file)?;
+    eprintln!(
+        "{}: {} added, {} updated, {} unchanged",
+        args.file.display(),
+        report.added.len(),
+        report.updated.len(),
+        report.unchanged.len()
+    );
+    Ok(())
+}
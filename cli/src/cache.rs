@@ -0,0 +1,123 @@
+//! An on-disk cache of search results.
+//!
+//! Results are stored in the user's cache directory, keyed by the query and
+//! all the parameters that affect the results, so that repeating the same
+//! search within a short time doesn't use up any of the API rate limit.
+
+use crate::error::{Error, Result};
+use adsabs::search::Document;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+// How long cached results remain valid
+const MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    docs: Vec<Document>,
+}
+
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Open the cache in the default location for the current platform.
+    pub fn open() -> Result<Self> {
+        let mut dir = dirs::cache_dir().ok_or(Error::CacheDir)?;
+        dir.push("adsabs");
+        Ok(Self::new(dir))
+    }
+
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Load the cached results for `key`, if they exist and haven't expired.
+    pub fn get(&self, key: &str) -> Option<Vec<Document>> {
+        let path = self.path(key);
+        let age = fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if age > MAX_AGE {
+            return None;
+        }
+        let entry: Entry = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+        if entry.key == key {
+            Some(entry.docs)
+        } else {
+            None
+        }
+    }
+
+    /// Store the results for `key`.
+    pub fn put(&self, key: &str, docs: Vec<Document>) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let entry = Entry {
+            key: key.to_owned(),
+            docs,
+        };
+        fs::write(self.path(key), serde_json::to_vec(&entry)?)?;
+        Ok(())
+    }
+
+    /// Remove all cached results, returning the number of entries removed.
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+        let mut count = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                fs::remove_file(path)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(key.as_bytes())))
+    }
+}
+
+// A simple hash that is stable across platforms and compiler versions
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let dir = std::env::temp_dir().join(format!("adsabs-cache-test-{}", std::process::id()));
+        let cache = Cache::new(dir.clone());
+        assert!(cache.get("q=star").is_none());
+
+        let doc = Document {
+            bibcode: Some("2013PASP..125..306F".to_owned()),
+            ..Document::default()
+        };
+        cache.put("q=star", vec![doc]).unwrap();
+        let docs = cache.get("q=star").unwrap();
+        assert_eq!(docs[0].bibcode.as_deref(), Some("2013PASP..125..306F"));
+        assert!(cache.get("q=galaxy").is_none());
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.get("q=star").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+//! The configuration file for the command line interface.
+//!
+//! This is a TOML file stored at `adsabs/config.toml` in the platform specific
+//! configuration directory (e.g. `~/.config` on Linux). The top level settings
+//! are used by default, and other named profiles can be selected using the
+//! `--profile` option:
+//!
+//! ```toml
+//! token = "..."
+//!
+//! [profiles.dev]
+//! token = "..."
+//! base_url = "https://devapi.adsabs.harvard.edu/v1/"
+//! ```
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Config {
+    /// The default profile.
+    #[serde(flatten)]
+    pub default: Profile,
+
+    /// Other named profiles.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// Saved queries that can be run using `ads run`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub queries: BTreeMap<String, String>,
+}
+
+/// The settings for a single account or environment.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Profile {
+    /// The API token, if it isn't stored in the system keyring.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// The base URL for the API, e.g. for a mirror or development server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+impl Config {
+    /// Get the profile called `name`, or the default profile for `None`.
+    pub fn profile(&self, name: Option<&str>) -> Result<&Profile> {
+        match name {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .ok_or_else(|| Error::UnknownProfile(name.to_owned())),
+            None => Ok(&self.default),
+        }
+    }
+
+    /// Get a mutable reference to the profile called `name`, creating it if
+    /// it doesn't already exist.
+    pub fn profile_mut(&mut self, name: Option<&str>) -> &mut Profile {
+        match name {
+            Some(name) => self.profiles.entry(name.to_owned()).or_default(),
+            None => &mut self.default,
+        }
+    }
+
+    /// The location of the configuration file.
+    pub fn path() -> Result<PathBuf> {
+        let mut path = dirs::config_dir().ok_or(Error::ConfigDir)?;
+        path.push("adsabs");
+        path.push("config.toml");
+        Ok(path)
+    }
+
+    /// Load the configuration file, or the default configuration if the file
+    /// doesn't exist.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        match fs::read_to_string(&path) {
+            Ok(data) => toml::from_str(&data).map_err(|err| Error::Config(path, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Save the configuration file, which is only readable by the current
+    /// user since it may contain the API token.
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = toml::to_string_pretty(self)?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // Create the file with restricted permissions, so that the token is
+        // never readable by other users
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)?;
+        // The mode only applies to new files, so fix up existing ones before
+        // writing the token
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(data.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles() {
+        let config: Config = toml::from_str(
+            r#"
+            token = "default-token"
+
+            [profiles.dev]
+            token = "dev-token"
+            base_url = "https://devapi.adsabs.harvard.edu/v1/"
+
+            [queries]
+            mine = "author:\"^Foreman-Mackey\" year:{year}"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.profile(None).unwrap().token.as_deref(),
+            Some("default-token")
+        );
+        let dev = config.profile(Some("dev")).unwrap();
+        assert_eq!(dev.token.as_deref(), Some("dev-token"));
+        assert!(dev.base_url.is_some());
+        assert_eq!(config.queries.len(), 1);
+        assert!(matches!(
+            config.profile(Some("work")),
+            Err(Error::UnknownProfile(_))
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+//! Diagnosing common problems with the configuration, token, and network.
+//!
+//! The output of `ads doctor` is meant to be included in bug reports, so it
+//! never includes the token itself.
+
+use crate::auth;
+use crate::config::Config;
+use crate::error::{Error, Result};
+use console::style;
+use std::io::{self, Write};
+
+/// The outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning,
+    Failed,
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    message: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, message: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn failed(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Failed,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run all the checks, stopping early when a check fails that the later checks
+/// depend on.
+pub fn run(profile: Option<&str>) -> Result<()> {
+    let mut checks = vec![Check::ok("version", env!("CARGO_PKG_VERSION"))];
+    let result = diagnose(profile, &mut checks);
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    write_report(&mut out, &checks)?;
+    out.flush()?;
+    result?;
+
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == Status::Failed)
+        .count();
+    if failed > 0 {
+        return Err(Error::ChecksFailed(failed));
+    }
+    Ok(())
+}
+
+fn diagnose(profile: Option<&str>, checks: &mut Vec<Check>) -> Result<()> {
+    let path = Config::path()?;
+    let config = match Config::load() {
+        Ok(config) => {
+            let message = if path.exists() {
+                format!("{} is valid", path.display())
+            } else {
+                format!("{} doesn't exist; using the defaults", path.display())
+            };
+            checks.push(Check::ok("config", message));
+            config
+        }
+        Err(err) => {
+            checks.push(Check::failed(
+                "config",
+                describe(&err),
+                format!("fix or remove {}", path.display()),
+            ));
+            return Ok(());
+        }
+    };
+    if let Err(err) = config.profile(profile) {
+        checks.push(Check::failed(
+            "profile",
+            err.to_string(),
+            "add the profile to the configuration file, or log in using \
+             `ads --profile NAME auth login`",
+        ));
+        return Ok(());
+    }
+    if let Some(name) = profile {
+        checks.push(Check::ok("profile", name));
+    }
+
+    let (builder, source) = match auth::builder(&config, profile) {
+        Ok(found) => found,
+        Err(_) => {
+            checks.push(Check::failed(
+                "token",
+                "no API token was found",
+                "run `ads auth login`, or set the ADS_API_TOKEN environment variable",
+            ));
+            return Ok(());
+        }
+    };
+    checks.push(Check::ok("token", format!("loaded from {}", source)));
+
+    let client = builder.build()?;
+    let status = match client.get("accounts/bootstrap", None::<&()>) {
+        Ok(response) => response.status(),
+        Err(err) => {
+            checks.push(Check::failed(
+                "network",
+                format!("unable to reach the API: {}", describe(&err)),
+                "check your internet connection and proxy settings, and the base_url in the \
+                 configuration file, if any",
+            ));
+            return Ok(());
+        }
+    };
+    checks.push(Check::ok("network", "the API is reachable"));
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        // A rate limited token is still a valid token
+        let message = match client.rate_limit() {
+            Some(limit) => format!(
+                "the daily rate limit was reached; it resets at {}",
+                limit.reset
+            ),
+            None => "the daily rate limit was reached".to_owned(),
+        };
+        checks.push(Check::ok("auth", "the token is valid"));
+        checks.push(Check::failed(
+            "quota",
+            message,
+            "wait for the limit to reset, or ask ADS for a higher limit",
+        ));
+        return Ok(());
+    }
+    if !status.is_success() {
+        checks.push(Check::failed(
+            "auth",
+            format!("the token was rejected ({})", status),
+            "generate a new token at https://ui.adsabs.harvard.edu/user/settings/token and \
+             run `ads auth login`",
+        ));
+        return Ok(());
+    }
+    checks.push(Check::ok("auth", "the token is valid"));
+
+    checks.push(match client.rate_limit() {
+        Some(limit) if limit.remaining == 0 => Check::failed(
+            "quota",
+            format!("no requests remaining until {}", limit.reset),
+            "wait for the limit to reset, or ask ADS for a higher limit",
+        ),
+        Some(limit) if limit.remaining * 10 < limit.limit => Check::warning(
+            "quota",
+            format!(
+                "{} of {} requests remaining until {}",
+                limit.remaining, limit.limit, limit.reset
+            ),
+            "use --limit, or the local cache, to reduce the number of requests",
+        ),
+        Some(limit) => Check::ok(
+            "quota",
+            format!(
+                "{} of {} requests remaining until {}",
+                limit.remaining, limit.limit, limit.reset
+            ),
+        ),
+        None => Check::ok("quota", "unknown; the API didn't report a rate limit"),
+    });
+    Ok(())
+}
+
+/// An error message, including its sources, skipping any that are already
+/// part of the message.
+fn describe(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        let text = err.to_string();
+        if !message.contains(&text) {
+            message = format!("{}: {}", message, text);
+        }
+        source = err.source();
+    }
+    message
+}
+
+fn write_report(out: &mut impl Write, checks: &[Check]) -> Result<()> {
+    let width = checks.iter().map(|check| check.name.len()).max();
+    for check in checks {
+        let status = match check.status {
+            Status::Ok => style("ok  ").green(),
+            Status::Warning => style("warn").yellow(),
+            Status::Failed => style("fail").red(),
+        };
+        writeln!(
+            out,
+            "{}  {:<width$}  {}",
+            status,
+            check.name,
+            check.message,
+            width = width.unwrap_or_default()
+        )?;
+        if let Some(fix) = &check.fix {
+            writeln!(out, "      {}", style(format!("fix: {}", fix)).dim())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        console::set_colors_enabled(false);
+        let checks = [
+            Check::ok("version", "0.1.0"),
+            Check::ok("config", "config.toml is valid"),
+            Check::failed("token", "no API token was found", "run `ads auth login`"),
+        ];
+        let mut out = Vec::new();
+        write_report(&mut out, &checks).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ok    version  0.1.0\n\
+             ok    config   config.toml is valid\n\
+             fail  token    no API token was found\n      \
+             fix: run `ads auth login`\n"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::io;
+use std::path::PathBuf;
+use std::result;
+
+pub type Result<T, E = Error> = result::Result<T, E>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Ads(#[from] adsabs::AdsError),
+
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+
+    #[error("JSON error")]
+    Json(#[from] serde_json::Error),
+
+    #[error("YAML error")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("unable to locate the local data directory")]
+    DataDir,
+
+    #[error("unable to locate the local cache directory")]
+    CacheDir,
+
+    #[error("no search query was provided")]
+    EmptyQuery,
+
+    #[error("unable to read the query file {0}")]
+    QueryFile(PathBuf, #[source] io::Error),
+
+    #[error("unable to locate the configuration directory")]
+    ConfigDir,
+
+    #[error("invalid configuration file {0}")]
+    Config(PathBuf, #[source] toml::de::Error),
+
+    #[error("no saved query named '{0}' in the configuration file")]
+    UnknownQuery(String),
+
+    #[error("no journals matching '{0}' were found")]
+    UnknownJournal(String),
+
+    #[error("no library named '{0}' was found")]
+    UnknownLibrary(String),
+
+    #[error("there is no entry {0} in the search history")]
+    HistoryEntry(usize),
+
+    #[error("no profile named '{0}' in the configuration file")]
+    UnknownProfile(String),
+
+    #[error("the API token was rejected with HTTP status {0}")]
+    Authentication(u16),
+
+    #[error("unable to write the configuration file")]
+    ConfigWrite(#[from] toml::ser::Error),
+
+    #[error("no records matched the query")]
+    NoResults,
+
+    #[error("the API returned HTTP status {0}")]
+    HttpStatus(u16),
+
+    #[error("unable to download the latest release")]
+    Download(#[source] reqwest::Error),
+
+    #[error("release {0} doesn't include the binary {1}")]
+    NoReleaseBinary(String, String),
+
+    #[error("release {0} doesn't include a checksum for the binary {1}")]
+    NoReleaseChecksum(String, String),
+
+    #[error("the downloaded binary {0} doesn't match the release checksum")]
+    ChecksumMismatch(String),
+
+    #[error("{0} check(s) failed")]
+    ChecksFailed(usize),
+
+    #[error("unable to show the prompt")]
+    Prompt(#[source] inquire::InquireError),
+
+    #[error("unable to access the clipboard")]
+    Clipboard(#[source] arboard::Error),
+}
+
+/// The broad category of an error, which determines the exit status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Any error not covered by the other categories.
+    Failure,
+    /// Invalid command line arguments or configuration.
+    Usage,
+    /// The API token is missing or was rejected.
+    Auth,
+    /// The API rate limit has been exceeded.
+    RateLimited,
+    /// The query didn't match any records.
+    NoResults,
+    /// The API couldn't be reached.
+    Network,
+}
+
+impl ErrorKind {
+    /// The exit status used for this kind of error.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Failure => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::Auth => 3,
+            ErrorKind::RateLimited => 4,
+            ErrorKind::NoResults => 5,
+            ErrorKind::Network => 6,
+        }
+    }
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Ads(err) => ads_error_kind(err),
+            Error::Authentication(_) | Error::HttpStatus(401) | Error::HttpStatus(403) => {
+                ErrorKind::Auth
+            }
+            Error::HttpStatus(429) => ErrorKind::RateLimited,
+            Error::NoResults => ErrorKind::NoResults,
+            Error::EmptyQuery
+            | Error::QueryFile(..)
+            | Error::Config(..)
+            | Error::UnknownQuery(_)
+            | Error::HistoryEntry(_)
+            | Error::UnknownLibrary(_)
+            | Error::UnknownProfile(_) => ErrorKind::Usage,
+            _ => ErrorKind::Failure,
+        }
+    }
+
+    /// The error and its chain of causes as a JSON object, for `--json-errors`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut causes = Vec::new();
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            causes.push(err.to_string());
+            source = err.source();
+        }
+        let kind = self.kind();
+        let mut value = serde_json::json!({
+            "kind": kind,
+            "exit_code": kind.exit_code(),
+            "message": self.to_string(),
+            "causes": causes,
+        });
+        if let Error::Ads(adsabs::AdsError::RateLimited { reset: Some(reset) }) = self {
+            value["reset"] = reset.to_rfc3339().into();
+        }
+        serde_json::json!({ "error": value })
+    }
+}
+
+fn ads_error_kind(err: &adsabs::AdsError) -> ErrorKind {
+    match err {
+        adsabs::AdsError::Token => ErrorKind::Auth,
+        adsabs::AdsError::InvalidTemplate(_) | adsabs::AdsError::MissingParameter(_) => {
+            ErrorKind::Usage
+        }
+        adsabs::AdsError::RateLimited { .. } => ErrorKind::RateLimited,
+        adsabs::AdsError::Api { status, .. } => match status {
+            401 | 403 => ErrorKind::Auth,
+            429 => ErrorKind::RateLimited,
+            _ => ErrorKind::Failure,
+        },
+        adsabs::AdsError::Reqwest(err) => match err.status().map(|status| status.as_u16()) {
+            Some(401) | Some(403) => ErrorKind::Auth,
+            Some(429) => ErrorKind::RateLimited,
+            Some(_) => ErrorKind::Failure,
+            None if err.is_decode() => ErrorKind::Failure,
+            None => ErrorKind::Network,
+        },
+        _ => ErrorKind::Failure,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds() {
+        assert_eq!(Error::NoResults.kind().exit_code(), 5);
+        assert_eq!(Error::EmptyQuery.kind(), ErrorKind::Usage);
+        assert_eq!(Error::from(adsabs::AdsError::Token).kind(), ErrorKind::Auth);
+        let err = Error::from(adsabs::AdsError::RateLimited { reset: None });
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert_eq!(err.to_json()["error"]["kind"], "rate_limited");
+        assert_eq!(err.to_json()["error"]["exit_code"], 4);
+        let err = Error::from(adsabs::AdsError::Api {
+            status: 401,
+            message: "Unauthorized".to_owned(),
+        });
+        assert_eq!(err.kind(), ErrorKind::Auth);
+        let err = Error::from(adsabs::AdsError::Api {
+            status: 500,
+            message: "Internal error".to_owned(),
+        });
+        assert_eq!(err.kind(), ErrorKind::Failure);
+    }
+
+    #[test]
+    fn json_causes() {
+        let err = Error::QueryFile(
+            PathBuf::from("query.txt"),
+            io::Error::new(io::ErrorKind::NotFound, "not found"),
+        );
+        let json = err.to_json();
+        assert_eq!(json["error"]["kind"], "usage");
+        assert_eq!(
+            json["error"]["message"],
+            "unable to read the query file query.txt"
+        );
+        assert_eq!(json["error"]["causes"], serde_json::json!(["not found"]));
+    }
+}
@@ -0,0 +1,145 @@
+//! A local history of search queries.
+//!
+//! Each query run using the search commands is appended to `history.jsonl` in
+//! the local data directory, along with the full command line, so that it can
+//! be listed and re-run later using `ads history`.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// When the query was run.
+    pub time: DateTime<Utc>,
+    /// The command line arguments, excluding the program name.
+    pub args: Vec<String>,
+    /// The query that was sent to the API.
+    pub query: String,
+    /// The total number of matching records, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_found: Option<u64>,
+}
+
+#[derive(clap::Args)]
+pub struct HistoryArgs {
+    #[command(subcommand)]
+    command: Option<HistoryCommand>,
+}
+
+#[derive(clap::Subcommand)]
+enum HistoryCommand {
+    /// List recent queries (the default)
+    List {
+        /// The number of entries to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Run a query from the history again
+    Run {
+        /// The number of the entry, as shown by `ads history list`
+        number: usize,
+    },
+    /// Remove all entries from the history
+    Clear,
+}
+
+/// What to do after handling a history command.
+pub enum Action {
+    Done,
+    /// Run the command with the given arguments again.
+    Rerun(Vec<String>),
+}
+
+pub fn run(args: &HistoryArgs) -> Result<Action> {
+    let path = path()?;
+    match args.command {
+        None => list(&path, 20)?,
+        Some(HistoryCommand::List { limit }) => list(&path, limit)?,
+        Some(HistoryCommand::Run { number }) => {
+            let entries = load(&path)?;
+            let entry = number
+                .checked_sub(1)
+                .and_then(|n| entries.get(n))
+                .ok_or(Error::HistoryEntry(number))?;
+            let _ = RERUN_ARGS.set(entry.args.clone());
+            return Ok(Action::Rerun(entry.args.clone()));
+        }
+        Some(HistoryCommand::Clear) => match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        },
+    }
+    Ok(Action::Done)
+}
+
+// The arguments of a command being re-run from the history
+static RERUN_ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Add a query run using the current command line to the history.
+pub fn record(query: &str, num_found: Option<u64>) -> Result<()> {
+    let args = match RERUN_ARGS.get() {
+        Some(args) => args.clone(),
+        None => std::env::args().skip(1).collect(),
+    };
+    let entry = Entry {
+        time: Utc::now(),
+        args,
+        query: query.to_owned(),
+        num_found,
+    };
+    let path = path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+fn list(path: &PathBuf, limit: usize) -> Result<()> {
+    let entries = load(path)?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for (n, entry) in entries
+        .iter()
+        .enumerate()
+        .skip(entries.len().saturating_sub(limit))
+    {
+        writeln!(
+            out,
+            "{:>5}  {}  {:>8}  {}",
+            n + 1,
+            entry.time.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            entry
+                .num_found
+                .map_or_else(|| "-".to_owned(), |n| n.to_string()),
+            entry.query
+        )?;
+    }
+    Ok(())
+}
+
+fn load(path: &PathBuf) -> Result<Vec<Entry>> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    // Skip any malformed lines, e.g. from interrupted writes
+    Ok(data
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn path() -> Result<PathBuf> {
+    let mut path = dirs::data_dir().ok_or(Error::DataDir)?;
+    path.push("adsabs");
+    path.push("history.jsonl");
+    Ok(path)
+}
@@ -0,0 +1,150 @@
+//! Looking up journals in the ADS journals database.
+//!
+//! A bibstem is looked up directly, and its full name and publication history
+//! are shown. Otherwise the argument is treated as (part of) a journal name,
+//! and the bibstems of all matching journals are listed.
+
+use crate::error::{Error, Result};
+use adsabs::Ads;
+use serde_json::Value;
+use std::io::{self, Write};
+
+#[derive(clap::Args)]
+pub struct JournalArgs {
+    /// A bibstem (e.g. "ApJ") or a journal name (e.g. "Astrophysical Journal")
+    name: String,
+}
+
+pub fn run(client: &Ads, args: &JournalArgs) -> Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let term = encode_segment(args.name.trim());
+    if let Some(summary) = fetch(client, &format!("journals/summary/{}", term))? {
+        if let Some(master) = summary.get("summary").and_then(|s| s.get("master")) {
+            return write_summary(&mut out, &summary["summary"], master);
+        }
+    }
+    let matches = fetch(client, &format!("journals/journal/{}", term))?
+        .and_then(|data| data.get("journal").and_then(Value::as_array).cloned())
+        .unwrap_or_default();
+    if matches.is_empty() {
+        return Err(Error::UnknownJournal(args.name.clone()));
+    }
+    for journal in &matches {
+        writeln!(
+            out,
+            "{}\t{}",
+            string(journal, "bibstem").unwrap_or_default(),
+            string(journal, "name").unwrap_or_default()
+        )?;
+    }
+    Ok(())
+}
+
+/// Get a JSON response from the API, returning `None` if nothing was found.
+fn fetch(client: &Ads, path: &str) -> Result<Option<Value>> {
+    let response = client.get(path, None::<&()>)?;
+    if response.status().as_u16() == 404 {
+        return Ok(None);
+    }
+    let response = response
+        .error_for_status()
+        .map_err(adsabs::AdsError::from)?;
+    Ok(Some(response.json().map_err(adsabs::AdsError::from)?))
+}
+
+fn write_summary(out: &mut impl Write, summary: &Value, master: &Value) -> Result<()> {
+    for (label, key) in &[
+        ("bibstem", "bibstem"),
+        ("name", "journal_name"),
+        ("type", "pubtype"),
+        ("refereed", "refereed"),
+    ] {
+        if let Some(value) = string(master, key) {
+            writeln!(out, "{:<10}{}", format!("{}:", label), value)?;
+        }
+    }
+    let history = summary
+        .get("pubhist")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for entry in history {
+        if let Some(publisher) = string(entry, "publisher") {
+            let years = match (string(entry, "start_year"), string(entry, "end_year")) {
+                (Some(start), Some(end)) => format!(" ({}-{})", start, end),
+                (Some(start), None) => format!(" ({}-)", start),
+                _ => String::new(),
+            };
+            writeln!(out, "publisher: {}{}", publisher, years)?;
+        }
+    }
+    Ok(())
+}
+
+/// Get a field as a string, also accepting numbers and booleans.
+fn string(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(value) if !value.is_empty() => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(if *value { "yes" } else { "no" }.to_owned()),
+        _ => None,
+    }
+}
+
+/// Percent-encode a value for use as a single URL path segment.
+fn encode_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'&' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn encode() {
+        assert_eq!(encode_segment("ApJ"), "ApJ");
+        assert_eq!(encode_segment("A&A"), "A&A");
+        assert_eq!(
+            encode_segment("Astrophysical Journal/Letters?"),
+            "Astrophysical%20Journal%2FLetters%3F"
+        );
+    }
+
+    #[test]
+    fn summary() {
+        let summary = json!({
+            "master": {
+                "bibstem": "ApJ",
+                "journal_name": "The Astrophysical Journal",
+                "pubtype": "Journal",
+                "refereed": "yes",
+            },
+            "pubhist": [
+                {"publisher": "University of Chicago Press", "start_year": 1895, "end_year": 2008},
+                {"publisher": "IOP Publishing", "start_year": 2009},
+            ],
+        });
+        let mut out = Vec::new();
+        write_summary(&mut out, &summary, &summary["master"]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "bibstem:  ApJ\n\
+             name:     The Astrophysical Journal\n\
+             type:     Journal\n\
+             refereed: yes\n\
+             publisher: University of Chicago Press (1895-2008)\n\
+             publisher: IOP Publishing (2009-)\n"
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Working with ADS libraries.
+
+use crate::error::{Error, Result};
+use crate::progress::Progress;
+use adsabs::export::FormatType;
+use adsabs::Ads;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+// The number of records exported by each request, which is the API's limit
+const EXPORT_CHUNK_SIZE: usize = 2000;
+
+#[derive(clap::Subcommand)]
+pub enum LibraryCommand {
+    /// Export all the records in a library, e.g. to a BibTeX file
+    Export(ExportArgs),
+}
+
+#[derive(clap::Args)]
+pub struct ExportArgs {
+    /// The name or ID of the library
+    name: String,
+
+    /// The file to write, instead of stdout
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// The export format
+    #[arg(short, long, value_enum, default_value_t = ExportFormat::Bibtex)]
+    format: ExportFormat,
+
+    /// The maximum number of export requests made at once
+    #[arg(short, long, default_value_t = 4)]
+    jobs: usize,
+
+    /// Don't display a progress bar while exporting
+    #[arg(long)]
+    no_progress: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// BibTeX
+    Bibtex,
+    /// BibTeX, including abstracts
+    BibtexAbs,
+    /// RIS
+    Ris,
+    /// EndNote
+    Endnote,
+    /// The ADS tagged format
+    Ads,
+    /// An AASTeX reference list
+    Aastex,
+    /// An MNRAS reference list
+    Mnras,
+    /// An Icarus reference list
+    Icarus,
+    /// A Solar Physics reference list
+    Soph,
+    /// An IEEE reference list
+    Ieee,
+    /// VOTable
+    Votable,
+}
+
+impl ExportFormat {
+    fn format_type(self) -> FormatType {
+        match self {
+            ExportFormat::Bibtex => FormatType::Bibtex,
+            ExportFormat::BibtexAbs => FormatType::BibtexAbs,
+            ExportFormat::Ris => FormatType::Ris,
+            ExportFormat::Endnote => FormatType::Endnote,
+            ExportFormat::Ads => FormatType::Ads,
+            ExportFormat::Aastex => FormatType::Aastex,
+            ExportFormat::Mnras => FormatType::Mnras,
+            ExportFormat::Icarus => FormatType::Icarus,
+            ExportFormat::Soph => FormatType::Soph,
+            ExportFormat::Ieee => FormatType::Ieee,
+            ExportFormat::Votable => FormatType::Votable,
+        }
+    }
+}
+
+pub fn run(client: &Ads, command: &LibraryCommand) -> Result<()> {
+    match command {
+        LibraryCommand::Export(args) => export(client, args),
+    }
+}
+
+fn export(client: &Ads, args: &ExportArgs) -> Result<()> {
+    let id = match adsabs::sync::find_library(client, &args.name)? {
+        Some(id) => id,
+        None if is_library_id(&args.name) => args.name.clone(),
+        None => return Err(Error::UnknownLibrary(args.name.clone())),
+    };
+    let bibcodes = adsabs::sync::library_bibcodes(client, &id)?;
+    if bibcodes.is_empty() {
+        return Err(Error::NoResults);
+    }
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout()),
+    };
+    // The bar is only drawn when stdout is a terminal, so with `--output` it's
+    // shown only if stdout hasn't also been redirected
+    let progress = Progress::new(!args.no_progress);
+    let total = bibcodes.len() as u64;
+    progress.update(0, Some(total), None);
+    let chunks = bibcodes.chunks(EXPORT_CHUNK_SIZE).collect::<Vec<_>>();
+    let format = args.format.format_type();
+    let mut exported = 0;
+    export_chunks(
+        &chunks,
+        args.jobs,
+        |&chunk| client.export(format, chunk).send(),
+        |n, text| {
+            progress.suspend(|| write_chunk(&mut out, text))?;
+            exported += chunks[n].len() as u64;
+            progress.update(exported, Some(total), client.rate_limit());
+            Ok(())
+        },
+    )?;
+    out.flush()?;
+    progress.finish();
+    if let Some(path) = &args.output {
+        eprintln!("{}: exported {} records", path.display(), total);
+    }
+    Ok(())
+}
+
+/// Export the chunks using up to `jobs` concurrent requests, passing the
+/// results to `write` in their original order as soon as they are available.
+///
+/// No new requests are started after the first error.
+fn export_chunks<T, E, W>(chunks: &[T], jobs: usize, export: E, mut write: W) -> Result<()>
+where
+    T: Sync,
+    E: Fn(&T) -> adsabs::Result<String> + Sync,
+    W: FnMut(usize, &str) -> Result<()>,
+{
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, chunks.len().max(1)) {
+            let sender = sender.clone();
+            let (next, failed, export) = (&next, &failed, &export);
+            scope.spawn(move || loop {
+                let n = next.fetch_add(1, Ordering::SeqCst);
+                if n >= chunks.len() || failed.load(Ordering::SeqCst) {
+                    break;
+                }
+                let result = export(&chunks[n]);
+                if result.is_err() {
+                    failed.store(true, Ordering::SeqCst);
+                }
+                if sender.send((n, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Hold on to results that arrive early until it is their turn
+        let mut pending = BTreeMap::new();
+        let mut written = 0;
+        for (n, result) in receiver {
+            pending.insert(n, result);
+            while let Some(result) = pending.remove(&written) {
+                let result = result
+                    .map_err(Error::from)
+                    .and_then(|text| write(written, &text));
+                if let Err(err) = result {
+                    failed.store(true, Ordering::SeqCst);
+                    return Err(err);
+                }
+                written += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Library IDs are 22 character, URL safe, base64 strings.
+fn is_library_id(value: &str) -> bool {
+    value.len() == 22
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Write one chunk of exported text, making sure that it ends with a newline
+/// so that the chunks don't run together.
+fn write_chunk(out: &mut impl Write, text: &str) -> io::Result<()> {
+    out.write_all(text.as_bytes())?;
+    if !text.is_empty() && !text.ends_with('\n') {
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn library_ids() {
+        assert!(is_library_id("hHGU1Ef-TpacAhicI3J8kQ"));
+        assert!(!is_library_id("My Thesis"));
+    }
+
+    #[test]
+    fn ordered_export() {
+        let chunks = (0..20).collect::<Vec<u64>>();
+        let mut written = Vec::new();
+        export_chunks(
+            &chunks,
+            4,
+            |&n| {
+                // Finish the chunks out of order
+                thread::sleep(std::time::Duration::from_millis((20 - n) % 7));
+                Ok(n.to_string())
+            },
+            |n, text| {
+                written.push((n, text.to_owned()));
+                Ok(())
+            },
+        )
+        .unwrap();
+        let expected = (0..20).map(|n| (n, n.to_string())).collect::<Vec<_>>();
+        assert_eq!(written, expected);
+
+        let result = export_chunks(
+            &chunks,
+            2,
+            |&n| match n {
+                3 => Err(adsabs::AdsError::Ads("failed".to_owned())),
+                n => Ok(n.to_string()),
+            },
+            |n, _| {
+                assert!(n < 3);
+                Ok(())
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn chunks() {
+        let mut out = Vec::new();
+        write_chunk(&mut out, "@article{a}").unwrap();
+        write_chunk(&mut out, "@article{b}\n").unwrap();
+        write_chunk(&mut out, "").unwrap();
+        assert_eq!(out, b"@article{a}\n@article{b}\n");
+    }
+}
@@ -0,0 +1,302 @@
+//! # ads
+//!
+//! A command line interface for the SAO/NASA Astrophysics Data System API.
+//!
+//! The API token can be stored in the system keyring using `ads auth login`,
+//! or provided using any of the locations listed in the [`auth`] module.
+
+mod abs;
+mod alias;
+mod api;
+mod auth;
+mod author;
+mod bib;
+mod cache;
+mod config;
+mod doctor;
+mod error;
+mod history;
+mod journal;
+mod library;
+mod network;
+mod output;
+mod pick;
+mod progress;
+mod pubs;
+mod search;
+mod stats;
+mod update;
+mod watch;
+
+use adsabs::prelude::*;
+use clap::{Parser, Subcommand};
+use error::Result;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "ads", version, about)]
+struct Cli {
+    /// When to use colors in the output
+    #[arg(long, value_enum, global = true, default_value_t = output::Color::Auto)]
+    color: output::Color,
+
+    /// Print errors to stderr as JSON objects
+    #[arg(long, global = true)]
+    json_errors: bool,
+
+    /// The named profile from the configuration file to use
+    #[arg(long, global = true, env = "ADS_PROFILE")]
+    profile: Option<String>,
+
+    /// The timeout for each request to the API, in seconds
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = parse_seconds)]
+    timeout: Option<Duration>,
+
+    /// The number of times to retry requests that time out or fail with a
+    /// temporary server error
+    #[arg(long, global = true, default_value_t = 0)]
+    retries: u32,
+
+    /// The delay before the first retry, in seconds, which is doubled for each
+    /// further retry
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = parse_seconds)]
+    retry_delay: Option<Duration>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Search for records using the usual ADS search syntax
+    Search {
+        #[command(flatten)]
+        query: search::QueryArgs,
+
+        #[command(flatten)]
+        results: search::ResultArgs,
+    },
+
+    /// List the records that cite a given record
+    Cites {
+        /// The bibcode of the cited record
+        bibcode: String,
+
+        #[command(flatten)]
+        results: search::ResultArgs,
+    },
+
+    /// List the records referenced by a given record
+    Refs {
+        /// The bibcode of the citing record
+        bibcode: String,
+
+        #[command(flatten)]
+        results: search::ResultArgs,
+    },
+
+    /// List records with similar content to a given record
+    Similar {
+        /// The bibcode of the record
+        bibcode: String,
+
+        #[command(flatten)]
+        results: search::ResultArgs,
+    },
+
+    /// Show the title, authors, and abstract of a record
+    Abstract(abs::AbstractArgs),
+
+    /// Summarize the papers, citations, and h-index of an author
+    Author(author::AuthorArgs),
+
+    /// Generate a publication list for an author
+    Pubs(pubs::PubsArgs),
+
+    /// Count the records matching a query by year, journal, or type
+    Stats(stats::StatsArgs),
+
+    /// Write the citation or co-author network of a set of records
+    Network(network::NetworkArgs),
+
+    /// Add the records matching a query to a BibTeX file, or update them
+    Bib(bib::BibArgs),
+
+    /// Translate between journal names and bibstems
+    Journal(journal::JournalArgs),
+
+    /// Work with ADS libraries
+    #[command(subcommand)]
+    Library(library::LibraryCommand),
+
+    /// Report records matching a query that were added since the last check
+    Watch(watch::WatchArgs),
+
+    /// Run a query saved in the configuration file
+    Run(alias::RunArgs),
+
+    /// List and re-run previous searches
+    History(history::HistoryArgs),
+
+    /// Manage the API token
+    #[command(subcommand)]
+    Auth(auth::AuthCommand),
+
+    /// Check the API token
+    #[command(subcommand)]
+    Token(auth::TokenCommand),
+
+    /// Manage the local cache of search results
+    #[command(subcommand)]
+    Cache(CacheCommand),
+
+    /// Send an authenticated request to any API endpoint and print the
+    /// response
+    Api(api::ApiArgs),
+
+    /// Check the configuration, API token, and connection to the API, and
+    /// suggest fixes for any problems
+    Doctor,
+
+    /// Update ads to the latest release
+    SelfUpdate(update::SelfUpdateArgs),
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Remove all cached search results
+    Clear,
+}
+
+fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) if err.use_stderr() && std::env::args().any(|arg| arg == "--json-errors") => {
+            let message = err.render().to_string();
+            let message = message.lines().next().unwrap_or_default();
+            let json = serde_json::json!({
+                "error": {
+                    "kind": error::ErrorKind::Usage,
+                    "exit_code": error::ErrorKind::Usage.exit_code(),
+                    "message": message.trim_start_matches("error: "),
+                    "causes": [],
+                }
+            });
+            eprintln!("{}", json);
+            std::process::exit(error::ErrorKind::Usage.exit_code());
+        }
+        Err(err) => err.exit(),
+    };
+    let json_errors = cli.json_errors;
+    if let Err(err) = run(cli) {
+        if json_errors {
+            eprintln!("{}", err.to_json());
+        } else {
+            eprint!("error: {}", err);
+            let mut source = std::error::Error::source(&err);
+            while let Some(err) = source {
+                eprint!(": {}", err);
+                source = err.source();
+            }
+            eprintln!();
+        }
+        std::process::exit(err.kind().exit_code());
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    cli.color.apply();
+    // This needs to run even if the configuration file is invalid
+    if let Command::Doctor = cli.command {
+        return doctor::run(cli.profile.as_deref());
+    }
+    let config = config::Config::load()?;
+    let profile = cli.profile.as_deref();
+    let (timeout, retries, retry_delay) = (cli.timeout, cli.retries, cli.retry_delay);
+    let client = || -> Result<Ads> {
+        let mut builder = auth::builder(&config, profile)?.0.retries(retries);
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(delay) = retry_delay {
+            builder = builder.retry_delay(delay);
+        }
+        Ok(builder.build()?)
+    };
+    match cli.command {
+        Command::Search { query, results } => search::run(&client()?, &query.build()?, &results),
+        Command::Cites { bibcode, results } => search::run(
+            &client()?,
+            &operator_query("citations", &bibcode)?,
+            &results,
+        ),
+        Command::Refs { bibcode, results } => search::run(
+            &client()?,
+            &operator_query("references", &bibcode)?,
+            &results,
+        ),
+        Command::Similar { bibcode, results } => {
+            search::run(&client()?, &operator_query("similar", &bibcode)?, &results)
+        }
+        Command::Abstract(args) => abs::run(&client()?, &args),
+        Command::Author(args) => author::run(&client()?, &args),
+        Command::Pubs(args) => pubs::run(&client()?, &args),
+        Command::Stats(args) => stats::run(&client()?, &args),
+        Command::Network(args) => network::run(&client()?, &args),
+        Command::Bib(args) => bib::run(&client()?, &args),
+        Command::Journal(args) => journal::run(&client()?, &args),
+        Command::Library(command) => library::run(&client()?, &command),
+        Command::Watch(args) => watch::run(&client()?, &args),
+        Command::Run(args) => alias::run(client, &config, &args),
+        Command::History(args) => match history::run(&args)? {
+            history::Action::Done => Ok(()),
+            history::Action::Rerun(args) => run(Cli::parse_from(
+                std::iter::once("ads".to_owned()).chain(args),
+            )),
+        },
+        Command::Auth(command) => auth::run(&command, config, profile),
+        Command::Token(command) => auth::run_token(&command, &config, profile),
+        Command::Cache(CacheCommand::Clear) => {
+            let count = cache::Cache::open()?.clear()?;
+            eprintln!("removed {} cached result(s)", count);
+            Ok(())
+        }
+        Command::Api(args) => api::run(&client()?, &args),
+        Command::Doctor => unreachable!("handled before loading the configuration"),
+        Command::SelfUpdate(args) => update::run(&args),
+    }
+}
+
+/// A query using a second order operator, like `citations`, on a single
+/// record, with the bibcode escaped in the same way as a query template.
+fn operator_query(operator: &str, bibcode: &str) -> Result<String> {
+    let template: adsabs::search::QueryTemplate =
+        format!("{}(bibcode:\"{{bibcode}}\")", operator).parse()?;
+    Ok(template.render(&[("bibcode", bibcode)])?)
+}
+
+/// Parse a (possibly fractional) number of seconds.
+fn parse_seconds(value: &str) -> std::result::Result<Duration, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("'{}' is not a valid number of seconds", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operator_queries() {
+        assert_eq!(
+            operator_query("citations", "2013PASP..125..306F").unwrap(),
+            "citations(bibcode:\"2013PASP..125..306F\")"
+        );
+        assert_eq!(
+            operator_query("similar", "x\") OR (y").unwrap(),
+            "similar(bibcode:\"x\\\") OR (y\")"
+        );
+    }
+}
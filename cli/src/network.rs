@@ -0,0 +1,308 @@
+//! Building citation and co-author networks for a set of records.
+//!
+//! The paper network links the matching records that cite each other, and the
+//! author network links authors who wrote one of the matching records
+//! together, weighted by the number of shared records. Both are written in a
+//! format that can be opened by tools like Graphviz and Gephi.
+
+use crate::error::{Error, Result};
+use crate::output;
+use adsabs::graph::{dot_id, xml_escape, CitationGraph};
+use adsabs::{search::Document, Ads};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+#[derive(clap::Args)]
+pub struct NetworkArgs {
+    /// A search query, or a list of bibcodes
+    #[arg(required = true)]
+    targets: Vec<String>,
+
+    /// The kind of network to build
+    #[arg(long, value_enum, default_value_t = Kind::Paper)]
+    kind: Kind,
+
+    /// The output format
+    #[arg(short, long, value_enum, default_value_t = NetworkFormat::Dot)]
+    format: NetworkFormat,
+
+    /// The maximum number of records to include
+    #[arg(short = 'n', long, default_value_t = 200)]
+    limit: u64,
+
+    /// Skip records with more authors than this in the author network, since
+    /// large collaborations link every pair of their members
+    #[arg(long, default_value_t = 50)]
+    max_authors: usize,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Kind {
+    /// The citations between the records
+    Paper,
+    /// The co-authors of the records
+    Author,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum NetworkFormat {
+    /// The Graphviz DOT language
+    Dot,
+    /// GraphML, e.g. for Gephi or Cytoscape
+    Graphml,
+    /// A JSON object with lists of nodes and edges
+    Json,
+}
+
+pub fn run(client: &Ads, args: &NetworkArgs) -> Result<()> {
+    let fields = match args.kind {
+        Kind::Paper => "bibcode,reference",
+        Kind::Author => "bibcode,author_norm",
+    };
+    let docs = client
+        .search(&query(&args.targets))
+        .fl(fields)
+        .iter_docs()
+        .limit(args.limit)
+        .collect::<adsabs::Result<Vec<_>>>()?;
+    if docs.is_empty() {
+        return Err(Error::NoResults);
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match args.kind {
+        Kind::Paper => {
+            let graph = paper_network(&docs);
+            match args.format {
+                NetworkFormat::Dot => out.write_all(graph.to_dot().as_bytes())?,
+                NetworkFormat::Graphml => out.write_all(graph.to_graphml().as_bytes())?,
+                NetworkFormat::Json => {
+                    let edges = graph
+                        .edges()
+                        .map(|(a, b)| serde_json::json!({"source": a, "target": b}))
+                        .collect::<Vec<_>>();
+                    let json = serde_json::json!({
+                        "nodes": graph.nodes().collect::<Vec<_>>(),
+                        "edges": edges,
+                    });
+                    writeln!(out, "{}", serde_json::to_string_pretty(&json)?)?;
+                }
+            }
+        }
+        Kind::Author => {
+            let network = AuthorNetwork::new(&docs, args.max_authors);
+            match args.format {
+                NetworkFormat::Dot => network.write_dot(&mut out)?,
+                NetworkFormat::Graphml => network.write_graphml(&mut out)?,
+                NetworkFormat::Json => network.write_json(&mut out)?,
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Build the query for the targets, which are either all bibcodes or a search
+/// query.
+fn query(targets: &[String]) -> String {
+    if targets.iter().all(|target| output::is_bibcode(target)) {
+        let bibcodes = targets
+            .iter()
+            .map(|bibcode| format!("\"{}\"", bibcode))
+            .collect::<Vec<_>>();
+        format!("bibcode:({})", bibcodes.join(" OR "))
+    } else {
+        targets.join(" ")
+    }
+}
+
+/// The citations between the records, ignoring references to other records.
+fn paper_network(docs: &[Document]) -> CitationGraph {
+    let mut graph = CitationGraph::default();
+    for bibcode in docs.iter().filter_map(|doc| doc.bibcode.as_deref()) {
+        graph.add_node(bibcode);
+    }
+    for doc in docs {
+        let citing = match &doc.bibcode {
+            Some(bibcode) => bibcode,
+            None => continue,
+        };
+        for cited in doc.reference.iter().flatten() {
+            if graph.contains(cited) {
+                graph.add_edge(citing, cited);
+            }
+        }
+    }
+    graph
+}
+
+/// An undirected network of authors, linked by the records they wrote
+/// together.
+#[derive(Default)]
+struct AuthorNetwork {
+    papers: BTreeMap<String, u64>,
+    links: BTreeMap<(String, String), u64>,
+}
+
+impl AuthorNetwork {
+    fn new(docs: &[Document], max_authors: usize) -> Self {
+        let mut network = Self::default();
+        for doc in docs {
+            let mut authors = doc.author_norm.clone().unwrap_or_default();
+            if authors.len() > max_authors {
+                continue;
+            }
+            authors.sort();
+            authors.dedup();
+            for (n, author) in authors.iter().enumerate() {
+                *network.papers.entry(author.clone()).or_default() += 1;
+                for other in &authors[n + 1..] {
+                    *network
+                        .links
+                        .entry((author.clone(), other.clone()))
+                        .or_default() += 1;
+                }
+            }
+        }
+        network
+    }
+
+    fn write_dot(&self, out: &mut impl Write) -> Result<()> {
+        writeln!(out, "graph coauthors {{")?;
+        for (author, papers) in &self.papers {
+            writeln!(out, "  {} [papers={}];", dot_id(author), papers)?;
+        }
+        for ((a, b), weight) in &self.links {
+            writeln!(out, "  {} -- {} [weight={}];", dot_id(a), dot_id(b), weight)?;
+        }
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+
+    fn write_graphml(&self, out: &mut impl Write) -> Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            out,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        )?;
+        writeln!(
+            out,
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>"
+        )?;
+        writeln!(
+            out,
+            "  <key id=\"papers\" for=\"node\" attr.name=\"papers\" attr.type=\"long\"/>"
+        )?;
+        writeln!(
+            out,
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"long\"/>"
+        )?;
+        writeln!(out, "  <graph id=\"coauthors\" edgedefault=\"undirected\">")?;
+        for (author, papers) in &self.papers {
+            writeln!(
+                out,
+                "    <node id=\"{0}\"><data key=\"label\">{0}</data><data key=\"papers\">{1}</data></node>",
+                xml_escape(author),
+                papers
+            )?;
+        }
+        for ((a, b), weight) in &self.links {
+            writeln!(
+                out,
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"weight\">{}</data></edge>",
+                xml_escape(a),
+                xml_escape(b),
+                weight
+            )?;
+        }
+        writeln!(out, "  </graph>\n</graphml>")?;
+        Ok(())
+    }
+
+    fn write_json(&self, out: &mut impl Write) -> Result<()> {
+        let json = serde_json::json!({
+            "nodes": self
+                .papers
+                .iter()
+                .map(|(author, papers)| serde_json::json!({"id": author, "papers": papers}))
+                .collect::<Vec<_>>(),
+            "edges": self
+                .links
+                .iter()
+                .map(|((a, b), weight)| serde_json::json!({"source": a, "target": b, "weight": weight}))
+                .collect::<Vec<_>>(),
+        });
+        writeln!(out, "{}", serde_json::to_string_pretty(&json)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(bibcode: &str, authors: &[&str], references: &[&str]) -> Document {
+        let strings = |values: &[&str]| Some(values.iter().map(|s| s.to_string()).collect());
+        Document {
+            bibcode: Some(bibcode.to_owned()),
+            author_norm: strings(authors),
+            reference: strings(references),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn targets() {
+        assert_eq!(
+            query(&[
+                "2013PASP..125..306F".to_owned(),
+                "2019JOSS....4.1864F".to_owned()
+            ]),
+            "bibcode:(\"2013PASP..125..306F\" OR \"2019JOSS....4.1864F\")"
+        );
+        assert_eq!(
+            query(&["author:\"Hogg, D\"".to_owned(), "year:2020".to_owned()]),
+            "author:\"Hogg, D\" year:2020"
+        );
+    }
+
+    #[test]
+    fn papers() {
+        let docs = [
+            doc("a", &[], &["b", "z"]),
+            doc("b", &[], &[]),
+            doc("c", &[], &["a"]),
+        ];
+        let graph = paper_network(&docs);
+        assert_eq!(graph.nodes().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(graph.edges().collect::<Vec<_>>(), [("a", "b"), ("c", "a")]);
+    }
+
+    #[test]
+    fn authors() {
+        let docs = [
+            doc("a", &["Hogg, D", "Foreman-Mackey, D"], &[]),
+            doc("b", &["Foreman-Mackey, D", "Hogg, D", "Lang, D"], &[]),
+            doc("c", &["Lang, D", "Hogg, D", "Bovy, J"], &[]),
+        ];
+        let network = AuthorNetwork::new(&docs, 2);
+        let mut out = Vec::new();
+        network.write_dot(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "graph coauthors {\n  \
+             \"Foreman-Mackey, D\" [papers=1];\n  \
+             \"Hogg, D\" [papers=1];\n  \
+             \"Foreman-Mackey, D\" -- \"Hogg, D\" [weight=1];\n}\n"
+        );
+
+        let network = AuthorNetwork::new(&docs, 50);
+        assert_eq!(network.papers["Hogg, D"], 3);
+        assert_eq!(
+            network.links[&("Foreman-Mackey, D".to_owned(), "Hogg, D".to_owned())],
+            2
+        );
+        assert_eq!(network.links.len(), 5);
+    }
+}
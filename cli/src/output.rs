@@ -0,0 +1,452 @@
+//! Formatting of search results for display or for use by other tools.
+
+use crate::error::Result;
+use adsabs::search::Document;
+use console::style;
+use std::io::{self, IsTerminal, Write};
+
+const ABS_BASE_URL: &str = "https://ui.adsabs.harvard.edu/abs/";
+
+/// The fields returned by the API when `fl` isn't specified.
+pub const DEFAULT_FIELDS: &str = "author,first_author,bibcode,id,year,title";
+
+/// The supported output formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// One line per record, for reading in the terminal
+    Text,
+    /// A JSON array of records
+    Json,
+    /// One JSON record per line, written as the results arrive
+    Ndjson,
+    /// A Markdown list with linked titles
+    Markdown,
+    /// A Markdown table with linked titles
+    MarkdownTable,
+    /// A YAML list of records
+    Yaml,
+}
+
+/// When to use colors in the human readable output formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Color {
+    /// Use colors when writing to a terminal, unless `NO_COLOR` is set
+    Auto,
+    /// Always use colors
+    Always,
+    /// Never use colors
+    Never,
+}
+
+impl Color {
+    /// Enable or disable colored output globally.
+    pub fn apply(self) {
+        let enabled = match self {
+            Color::Always => true,
+            Color::Never => false,
+            Color::Auto => {
+                let no_color =
+                    matches!(std::env::var_os("NO_COLOR"), Some(value) if !value.is_empty());
+                !no_color && io::stdout().is_terminal()
+            }
+        };
+        console::set_colors_enabled(enabled);
+    }
+}
+
+/// Write a sequence of documents to `out` in the requested format.
+///
+/// The documents are written as they are pulled from the iterator, so results
+/// will start appearing before all the pages have been fetched.
+pub fn write_docs<W, I>(out: &mut W, format: Format, docs: I) -> Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = adsabs::Result<Document>>,
+{
+    if format == Format::MarkdownTable {
+        writeln!(out, "| Title | Authors | Year |")?;
+        writeln!(out, "| --- | --- | --- |")?;
+    } else if format == Format::Json {
+        write!(out, "[")?;
+    }
+
+    let mut count = 0;
+    for (n, doc) in docs.into_iter().enumerate() {
+        count += 1;
+        let doc = doc?;
+        match format {
+            Format::Text => writeln!(
+                out,
+                "{}  {} ({}): {}",
+                style(doc.bibcode.as_deref().unwrap_or_default()).dim(),
+                style(authors(&doc)).bold(),
+                style(doc.year.as_deref().unwrap_or_default()).yellow(),
+                style(title(&doc)).cyan()
+            )?,
+            Format::Json => {
+                if n > 0 {
+                    write!(out, ",")?;
+                }
+                writeln!(out)?;
+                serde_json::to_writer_pretty(&mut *out, &doc)?;
+            }
+            Format::Ndjson => {
+                serde_json::to_writer(&mut *out, &doc)?;
+                writeln!(out)?;
+            }
+            Format::Markdown => writeln!(
+                out,
+                "- {} — {} ({})",
+                markdown_link(&doc, false),
+                escape_markdown(&authors(&doc), false),
+                doc.year.as_deref().unwrap_or_default(),
+            )?,
+            Format::MarkdownTable => writeln!(
+                out,
+                "| {} | {} | {} |",
+                markdown_link(&doc, true),
+                escape_markdown(&authors(&doc), true),
+                doc.year.as_deref().unwrap_or_default(),
+            )?,
+            Format::Yaml => write_yaml_item(out, &doc)?,
+        }
+    }
+
+    if format == Format::Json {
+        writeln!(out, "\n]")?;
+    } else if format == Format::Yaml && count == 0 {
+        writeln!(out, "[]")?;
+    }
+    Ok(())
+}
+
+/// Write only the values of the requested fields, with one record per line
+/// and the fields separated by tabs.
+///
+/// Fields with multiple values (like `author`) are joined using `; `, and
+/// missing fields are left empty.
+pub fn write_fields<W, I>(out: &mut W, fields: &[String], docs: I) -> Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = adsabs::Result<Document>>,
+{
+    for doc in docs {
+        let doc = serde_json::to_value(doc?)?;
+        let values = fields
+            .iter()
+            .map(|field| field_to_string(&doc[field.as_str()]))
+            .collect::<Vec<_>>();
+        writeln!(out, "{}", values.join("\t"))?;
+    }
+    Ok(())
+}
+
+/// A one line format for records, with `{field}` placeholders that are
+/// replaced by the values of the fields, e.g. "{first_author} ({year}) {title}".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field(String),
+}
+
+impl Template {
+    /// The fields used by the template, in order and without duplicates.
+    pub fn fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        for part in &self.parts {
+            if let Part::Field(field) = part {
+                if !fields.contains(field) {
+                    fields.push(field.clone());
+                }
+            }
+        }
+        fields
+    }
+
+    fn render(&self, doc: &serde_json::Value) -> String {
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => line.push_str(text),
+                Part::Field(field) => line.push_str(&field_to_string(&doc[field.as_str()])),
+            }
+        }
+        line
+    }
+}
+
+impl std::str::FromStr for Template {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unmatched '{{' in the template '{}'", template))?;
+            let field = rest[start + 1..start + end].trim();
+            if field.is_empty() {
+                return Err(format!("empty field name in the template '{}'", template));
+            }
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_owned()));
+            }
+            parts.push(Part::Field(field.to_owned()));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_owned()));
+        }
+        Ok(Template { parts })
+    }
+}
+
+/// Write one line for each record, formatted using a template.
+///
+/// As for [`write_fields`], multiple values are joined using `; ` and missing
+/// fields are left empty.
+pub fn write_template<W, I>(out: &mut W, template: &Template, docs: I) -> Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = adsabs::Result<Document>>,
+{
+    for doc in docs {
+        writeln!(out, "{}", template.render(&serde_json::to_value(doc?)?))?;
+    }
+    Ok(())
+}
+
+fn field_to_string(value: &serde_json::Value) -> String {
+    use serde_json::Value;
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.split_whitespace().collect::<Vec<_>>().join(" "),
+        Value::Array(values) => values
+            .iter()
+            .map(field_to_string)
+            .collect::<Vec<_>>()
+            .join("; "),
+        value => value.to_string(),
+    }
+}
+
+/// Write the body of an unparsed API response, pretty printing it if it is
+/// JSON, and returning an error if the request failed.
+pub fn write_raw<W: Write>(out: &mut W, response: &adsabs::RawResponse) -> Result<()> {
+    match serde_json::from_str::<serde_json::Value>(&response.body) {
+        Ok(json) => writeln!(out, "{}", serde_json::to_string_pretty(&json)?)?,
+        Err(_) if response.body.is_empty() => {}
+        Err(_) => writeln!(out, "{}", response.body.trim_end())?,
+    }
+    out.flush()?;
+    if !response.is_success() {
+        return Err(crate::error::Error::HttpStatus(response.status));
+    }
+    Ok(())
+}
+
+/// Write a serializable value as a single item in a YAML block sequence, so
+/// that records can be written one at a time.
+pub fn write_yaml_item<W: Write, T: serde::Serialize>(out: &mut W, value: &T) -> Result<()> {
+    let yaml = serde_yaml::to_string(value)?;
+    for (n, line) in yaml.lines().enumerate() {
+        if n == 0 {
+            writeln!(out, "- {}", line)?;
+        } else if line.is_empty() {
+            writeln!(out)?;
+        } else {
+            writeln!(out, "  {}", line)?;
+        }
+    }
+    Ok(())
+}
+
+/// The full title of a document on a single line.
+pub fn title(doc: &Document) -> String {
+    doc.title
+        .as_ref()
+        .map(|t| t.join(" ").split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default()
+}
+
+/// A short author list: the first author, followed by "et al." if needed.
+pub fn authors(doc: &Document) -> String {
+    let first = doc
+        .first_author
+        .clone()
+        .or_else(|| doc.author.as_ref().and_then(|a| a.first().cloned()))
+        .unwrap_or_default();
+    let count = doc
+        .author
+        .as_ref()
+        .map_or(0, Vec::len)
+        .max(doc.author_count.unwrap_or(0) as usize);
+    if count > 1 {
+        format!("{} et al.", first)
+    } else {
+        first
+    }
+}
+
+/// The URL for the abstract page of a record in the ADS user interface.
+pub fn abs_url(bibcode: &str) -> String {
+    format!("{}{}/abstract", ABS_BASE_URL, bibcode)
+}
+
+/// Bibcodes are 19 characters long and start with the year.
+pub fn is_bibcode(value: &str) -> bool {
+    value.len() == 19
+        && value.is_char_boundary(4)
+        && value[..4].chars().all(|c| c.is_ascii_digit())
+        && !value.contains(char::is_whitespace)
+}
+
+fn markdown_link(doc: &Document, table: bool) -> String {
+    let text = escape_markdown(&title(doc), table);
+    match &doc.bibcode {
+        Some(bibcode) => format!("[{}]({})", text, abs_url(bibcode)),
+        None => text,
+    }
+}
+
+pub fn escape_markdown(text: &str, table: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']' | '*' | '_' | '`') || (table && c == '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bibcodes() {
+        assert!(is_bibcode("2013PASP..125..306F"));
+        assert!(!is_bibcode("emcee mcmc hammer"));
+        assert!(!is_bibcode("2013 PASP..125..306F"));
+        assert!(!is_bibcode("abcdPASP..125..306F"));
+    }
+
+    fn emcee() -> Document {
+        Document {
+            bibcode: Some("2013PASP..125..306F".to_owned()),
+            first_author: Some("Foreman-Mackey, Daniel".to_owned()),
+            author_count: Some(4),
+            year: Some("2013".to_owned()),
+            title: Some(vec!["emcee: The MCMC Hammer".to_owned()]),
+            ..Document::default()
+        }
+    }
+
+    fn render(format: Format) -> String {
+        let mut out = Vec::new();
+        write_docs(&mut out, format, vec![Ok(emcee())]).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn plain_text() {
+        Color::Never.apply();
+        assert_eq!(
+            render(Format::Text),
+            "2013PASP..125..306F  Foreman-Mackey, Daniel et al. (2013): emcee: The MCMC Hammer\n"
+        );
+    }
+
+    #[test]
+    fn markdown_list() {
+        assert_eq!(
+            render(Format::Markdown),
+            "- [emcee: The MCMC Hammer](https://ui.adsabs.harvard.edu/abs/2013PASP..125..306F/abstract) — Foreman-Mackey, Daniel et al. (2013)\n"
+        );
+    }
+
+    #[test]
+    fn markdown_table() {
+        let out = render(Format::MarkdownTable);
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "| Title | Authors | Year |");
+        assert!(lines[2].starts_with("| [emcee: The MCMC Hammer](https://"));
+        assert!(lines[2].ends_with("| Foreman-Mackey, Daniel et al. | 2013 |"));
+    }
+
+    #[test]
+    fn markdown_escaping() {
+        assert_eq!(escape_markdown("a [b] | c_d", false), "a \\[b\\] | c\\_d");
+        assert_eq!(escape_markdown("a | b", true), "a \\| b");
+    }
+
+    #[test]
+    fn ndjson_lines() {
+        let mut out = Vec::new();
+        write_docs(&mut out, Format::Ndjson, vec![Ok(emcee()), Ok(emcee())]).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["year"], "2013");
+        }
+    }
+
+    #[test]
+    fn yaml_list() {
+        let mut out = Vec::new();
+        write_docs(&mut out, Format::Yaml, vec![Ok(emcee()), Ok(emcee())]).unwrap();
+        let value: Vec<Document> = serde_yaml::from_slice(&out).unwrap();
+        assert_eq!(value.len(), 2);
+        assert_eq!(value[1].bibcode.as_deref(), Some("2013PASP..125..306F"));
+
+        let mut out = Vec::new();
+        write_docs(&mut out, Format::Yaml, vec![]).unwrap();
+        assert_eq!(out, b"[]\n");
+    }
+
+    #[test]
+    fn fields() {
+        let mut out = Vec::new();
+        let fields = ["bibcode", "author_count", "title", "doi"].map(str::to_owned);
+        write_fields(&mut out, &fields, vec![Ok(emcee())]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "2013PASP..125..306F\t4\temcee: The MCMC Hammer\t\n"
+        );
+    }
+
+    #[test]
+    fn templates() {
+        let template = "{first_author} ({year}) {title} [{citation_count}]"
+            .parse::<Template>()
+            .unwrap();
+        assert_eq!(
+            template.fields(),
+            ["first_author", "year", "title", "citation_count"]
+        );
+        let mut out = Vec::new();
+        write_template(&mut out, &template, vec![Ok(emcee())]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Foreman-Mackey, Daniel (2013) emcee: The MCMC Hammer []\n"
+        );
+        assert!("{year".parse::<Template>().is_err());
+        assert!("{} {year}".parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn json_array() {
+        let value: serde_json::Value = serde_json::from_str(&render(Format::Json)).unwrap();
+        assert_eq!(value[0]["bibcode"], "2013PASP..125..306F");
+    }
+}
@@ -0,0 +1,135 @@
+//! Interactively choosing search results and acting on them.
+
+use crate::error::{Error, Result};
+use crate::output;
+use adsabs::{export::FormatType, search::Document, Ads};
+use inquire::{InquireError, MultiSelect, Select};
+use std::fmt;
+use std::io::{self, Write};
+
+/// What to do with the chosen records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Print,
+    Copy,
+    Bibtex,
+    Open,
+}
+
+impl Action {
+    const ALL: [Action; 4] = [Action::Print, Action::Copy, Action::Bibtex, Action::Open];
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::Print => "Print the bibcodes",
+            Action::Copy => "Copy the bibcodes to the clipboard",
+            Action::Bibtex => "Print the BibTeX entries",
+            Action::Open => "Open the abstract pages in the browser",
+        })
+    }
+}
+
+/// A record in the list of choices.
+struct Choice(Document);
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let doc = &self.0;
+        write!(
+            f,
+            "{}  {} ({}): {}",
+            doc.bibcode.as_deref().unwrap_or_default(),
+            output::authors(doc),
+            doc.year.as_deref().unwrap_or_default(),
+            output::title(doc)
+        )
+    }
+}
+
+/// Let the user choose some of the records, and then what to do with them.
+///
+/// Cancelling either prompt, e.g. using escape, exits without doing anything.
+pub fn pick(client: &Ads, docs: Vec<Document>) -> Result<()> {
+    let choices = docs
+        .into_iter()
+        .filter(|doc| doc.bibcode.is_some())
+        .map(Choice)
+        .collect::<Vec<_>>();
+    if choices.is_empty() {
+        return Err(Error::NoResults);
+    }
+    let chosen = match cancellable(
+        MultiSelect::new("Choose records (type to filter, space to select):", choices)
+            .with_page_size(15)
+            .prompt(),
+    )? {
+        Some(chosen) if !chosen.is_empty() => chosen,
+        _ => return Ok(()),
+    };
+    let action = match cancellable(Select::new("Action:", Action::ALL.to_vec()).prompt())? {
+        Some(action) => action,
+        None => return Ok(()),
+    };
+
+    let bibcodes = chosen
+        .iter()
+        .filter_map(|choice| choice.0.bibcode.clone())
+        .collect::<Vec<_>>();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match action {
+        Action::Print => {
+            for bibcode in &bibcodes {
+                writeln!(out, "{}", bibcode)?;
+            }
+        }
+        Action::Copy => {
+            arboard::Clipboard::new()
+                .and_then(|mut clipboard| clipboard.set_text(bibcodes.join("\n")))
+                .map_err(Error::Clipboard)?;
+            eprintln!("copied {} bibcode(s)", bibcodes.len());
+        }
+        Action::Bibtex => {
+            let text = client.export(FormatType::Bibtex, &bibcodes).send()?;
+            out.write_all(text.as_bytes())?;
+        }
+        Action::Open => {
+            for bibcode in &bibcodes {
+                open::that(output::abs_url(bibcode))?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Treat a cancelled prompt as an empty answer, rather than an error.
+fn cancellable<T>(result: Result<T, InquireError>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(InquireError::OperationCanceled) | Err(InquireError::OperationInterrupted) => Ok(None),
+        Err(err) => Err(Error::Prompt(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices() {
+        let doc = Document {
+            bibcode: Some("2013PASP..125..306F".to_owned()),
+            author: Some(vec!["Foreman-Mackey, D".to_owned(), "Hogg, D".to_owned()]),
+            year: Some("2013".to_owned()),
+            title: Some(vec!["emcee: The MCMC Hammer".to_owned()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            Choice(doc).to_string(),
+            "2013PASP..125..306F  Foreman-Mackey, D et al. (2013): emcee: The MCMC Hammer"
+        );
+    }
+}
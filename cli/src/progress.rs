@@ -0,0 +1,86 @@
+//! A progress bar for long running queries.
+//!
+//! The bar is drawn on stderr, and it is hidden when stdout is not a terminal
+//! so that it doesn't get in the way when the output is being piped elsewhere.
+
+use adsabs::RateLimit;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::{self, IsTerminal, Write};
+
+const TEMPLATE: &str = "{spinner} [{elapsed_precise}] {wide_bar} {pos}/{len} {msg}";
+
+/// A progress bar tracking the number of records fetched.
+#[derive(Clone)]
+pub struct Progress {
+    bar: ProgressBar,
+}
+
+impl Progress {
+    /// Build a new progress bar, which will only be displayed if `enabled` is
+    /// `true` and stdout is a terminal.
+    pub fn new(enabled: bool) -> Self {
+        let bar = if enabled && io::stdout().is_terminal() {
+            let bar = ProgressBar::new(0);
+            bar.set_style(
+                ProgressStyle::with_template(TEMPLATE)
+                    .expect("valid progress bar template")
+                    .progress_chars("=> "),
+            );
+            bar
+        } else {
+            ProgressBar::hidden()
+        };
+        Self { bar }
+    }
+
+    /// Update the progress, where `total` is the total number of records that
+    /// are expected, if known.
+    pub fn update(&self, position: u64, total: Option<u64>, rate_limit: Option<RateLimit>) {
+        if let Some(total) = total {
+            self.bar.set_length(total);
+        }
+        self.bar.set_position(position);
+        if let Some(rate_limit) = rate_limit {
+            self.bar
+                .set_message(format!("({} requests remaining)", rate_limit.remaining));
+        }
+    }
+
+    /// Hide the progress bar while running `f`.
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.bar.suspend(f)
+    }
+
+    /// Remove the progress bar from the terminal.
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+
+    /// Wrap a writer so that the progress bar is temporarily cleared whenever
+    /// output is written.
+    pub fn wrap<W: Write>(&self, inner: W) -> Writer<W> {
+        Writer {
+            bar: self.bar.clone(),
+            inner,
+        }
+    }
+}
+
+/// A writer that suspends the progress bar while writing; see
+/// [`Progress::wrap`].
+pub struct Writer<W> {
+    bar: ProgressBar,
+    inner: W,
+}
+
+impl<W: Write> Write for Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        self.bar.suspend(|| inner.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.bar.suspend(|| inner.flush())
+    }
+}
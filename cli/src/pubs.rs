@@ -0,0 +1,196 @@
+//! Generating publication lists, e.g. for a CV.
+
+use crate::error::Result;
+use crate::{output, search};
+use adsabs::{
+    search::{merge_duplicates, Document, DUPLICATE_FIELDS},
+    Ads,
+};
+use std::io::{self, Write};
+
+const FIELDS: &str = "id,title,author,year,pubdate,pub,volume,page,citation_count";
+
+// The number of authors listed before truncating with "et al."
+const MAX_AUTHORS: usize = 5;
+
+#[derive(clap::Args)]
+pub struct PubsArgs {
+    /// The author, using the ADS author syntax, e.g. "^Foreman-Mackey, D" for
+    /// first author publications only
+    #[arg(long)]
+    author: String,
+
+    /// Only include refereed publications
+    #[arg(long)]
+    refereed: bool,
+
+    /// The output format
+    #[arg(short, long, value_enum, default_value_t = PubsFormat::Markdown)]
+    format: PubsFormat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum PubsFormat {
+    /// A LaTeX enumerate environment
+    Latex,
+    /// A numbered Markdown list
+    Markdown,
+    /// A JSON array of records
+    Json,
+}
+
+pub fn run(client: &Ads, args: &PubsArgs) -> Result<()> {
+    let mut query = format!(
+        "author:{} AND (doctype:\"article\" OR doctype:\"eprint\")",
+        search::quote(&args.author)
+    );
+    if args.refereed {
+        query.push_str(" AND property:refereed");
+    }
+    let docs = client
+        .search(&query)
+        .fl(FIELDS)
+        .fl(DUPLICATE_FIELDS)
+        .sort("date")
+        .iter_docs()
+        .collect::<adsabs::Result<Vec<_>>>()?;
+    let docs = merge_duplicates(docs);
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match args.format {
+        PubsFormat::Latex => write_latex(&mut out, &docs)?,
+        PubsFormat::Markdown => write_markdown(&mut out, &docs)?,
+        PubsFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&docs)?)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn author_list(doc: &Document) -> Vec<String> {
+    let authors = doc.author.clone().unwrap_or_default();
+    if authors.len() > MAX_AUTHORS {
+        let mut authors = authors[..MAX_AUTHORS].to_vec();
+        authors.push("et al.".to_owned());
+        authors
+    } else {
+        authors
+    }
+}
+
+/// The publication, volume, and page, e.g. "PASP, 125, 306".
+fn citation(doc: &Document) -> String {
+    let page = doc.page.as_ref().and_then(|page| page.first());
+    [doc.publication.as_ref(), doc.volume.as_ref(), page]
+        .iter()
+        .flatten()
+        .map(|x| x.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn write_markdown<W: Write>(out: &mut W, docs: &[Document]) -> Result<()> {
+    for (n, doc) in docs.iter().enumerate() {
+        let title = output::escape_markdown(&output::title(doc), false);
+        let title = match &doc.bibcode {
+            Some(bibcode) => format!("[{}]({})", title, output::abs_url(bibcode)),
+            None => title,
+        };
+        writeln!(
+            out,
+            "{}. {} ({}). {}. *{}*. Citations: {}.",
+            n + 1,
+            output::escape_markdown(&author_list(doc).join("; "), false),
+            doc.year.as_deref().unwrap_or_default(),
+            title,
+            output::escape_markdown(&citation(doc), false),
+            doc.citation_count.unwrap_or(0)
+        )?;
+    }
+    Ok(())
+}
+
+fn write_latex<W: Write>(out: &mut W, docs: &[Document]) -> Result<()> {
+    writeln!(out, "\\begin{{enumerate}}")?;
+    for doc in docs {
+        writeln!(
+            out,
+            "\\item {} ({}). \\textit{{{}}}. {}. Citations: {}.",
+            escape_latex(&author_list(doc).join("; ")),
+            doc.year.as_deref().unwrap_or_default(),
+            escape_latex(&output::title(doc)),
+            escape_latex(&citation(doc)),
+            doc.citation_count.unwrap_or(0)
+        )?;
+    }
+    writeln!(out, "\\end{{enumerate}}")?;
+    Ok(())
+}
+
+/// Escape special LaTeX characters, leaving any math (between `$`s) as is,
+/// since ADS titles often include inline LaTeX math.
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut math = false;
+    for c in text.chars() {
+        if c == '$' {
+            math = !math;
+            escaped.push(c);
+            continue;
+        }
+        if math {
+            escaped.push(c);
+            continue;
+        }
+        match c {
+            '&' | '%' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latex_escaping() {
+        assert_eq!(
+            escape_latex("Stars & planets at 50% $\\sim$ 1_000"),
+            "Stars \\& planets at 50\\% $\\sim$ 1\\_000"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let doc = Document {
+            bibcode: Some("2013PASP..125..306F".to_owned()),
+            author: Some(vec![
+                "Foreman-Mackey, D.".to_owned(),
+                "Hogg, D. W.".to_owned(),
+            ]),
+            year: Some("2013".to_owned()),
+            title: Some(vec!["emcee: The MCMC Hammer".to_owned()]),
+            publication: Some("PASP".to_owned()),
+            volume: Some("125".to_owned()),
+            page: Some(vec!["306".to_owned()]),
+            citation_count: Some(100),
+            ..Document::default()
+        };
+        let mut out = Vec::new();
+        write_markdown(&mut out, &[doc]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1. Foreman-Mackey, D.; Hogg, D. W. (2013). \
+             [emcee: The MCMC Hammer](https://ui.adsabs.harvard.edu/abs/2013PASP..125..306F/abstract). \
+             *PASP, 125, 306*. Citations: 100.\n"
+        );
+    }
+}
@@ -0,0 +1,711 @@
+//! Running search queries and writing out the results.
+
+use crate::error::{Error, Result};
+use crate::{cache::Cache, history, output, pick, progress::Progress};
+use adsabs::search::{merge_duplicates, Query, Sort, DUPLICATE_FIELDS};
+use adsabs::{Ads, AdsError};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+// The maximum number of records that the API returns in a single response
+const MAX_ROWS: u64 = 2000;
+
+// The longest that `--all` waits for the rate limit to reset, which happens
+// daily
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The command line options used to build a search query.
+///
+/// All of the provided options are combined using `AND`.
+#[derive(clap::Args, Default)]
+pub struct QueryArgs {
+    /// The search query, e.g. 'author:"^Foreman-Mackey" year:2013'
+    query: Option<String>,
+
+    /// Read the search query from a file, or from stdin if this is "-"; lines
+    /// starting with "#" are ignored
+    #[arg(long, value_name = "PATH", conflicts_with = "query")]
+    query_file: Option<PathBuf>,
+
+    /// Search for records by an author, e.g. "Foreman-Mackey, D"; this can be
+    /// repeated
+    #[arg(long)]
+    author: Vec<String>,
+
+    /// Search for records by a first author
+    #[arg(long)]
+    first_author: Option<String>,
+
+    /// Search for records published in a year, or range of years, e.g. 2013 or
+    /// 2010-2020
+    #[arg(long, value_parser = parse_year)]
+    year: Option<String>,
+
+    /// Search for records published in or after this month or day, given as
+    /// YYYY, YYYY-MM, or YYYY-MM-DD
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    from: Option<String>,
+
+    /// Search for records published in or before this month or day, given as
+    /// YYYY, YYYY-MM, or YYYY-MM-DD
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    to: Option<String>,
+
+    /// Search for records with a title matching this phrase
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Search for records with an abstract matching this phrase
+    #[arg(long = "abs")]
+    abstract_: Option<String>,
+
+    /// Search for records in a publication, using its bibstem, e.g. ApJ
+    #[arg(long)]
+    bibstem: Option<String>,
+
+    /// Search for records associated with an ORCID iD, e.g. 0000-0002-9328-5652
+    #[arg(long, value_parser = parse_orcid)]
+    orcid: Option<String>,
+}
+
+impl QueryArgs {
+    /// Combine all the query options into a single query string.
+    pub fn build(&self) -> Result<String> {
+        let mut terms = Vec::new();
+        if let Some(query) = &self.query {
+            terms.push(query.clone());
+        }
+        if let Some(path) = &self.query_file {
+            let text = if path.as_os_str() == "-" {
+                io::read_to_string(io::stdin())
+            } else {
+                fs::read_to_string(path)
+            }
+            .map_err(|err| Error::QueryFile(path.clone(), err))?;
+            let query = join_lines(&text);
+            if !query.is_empty() {
+                terms.push(query);
+            }
+        }
+        for author in &self.author {
+            terms.push(format!("author:{}", quote(author)));
+        }
+        if let Some(first_author) = &self.first_author {
+            terms.push(format!("first_author:{}", quote(first_author)));
+        }
+        if let Some(year) = &self.year {
+            terms.push(format!("year:{}", year));
+        }
+        if self.from.is_some() || self.to.is_some() {
+            // Publication dates are stored by month, so a year covers all of
+            // its months
+            let from = date_bound(self.from.as_deref(), "01");
+            let to = date_bound(self.to.as_deref(), "12");
+            terms.push(format!("pubdate:[{} TO {}]", from, to));
+        }
+        if let Some(title) = &self.title {
+            terms.push(format!("title:{}", quote(title)));
+        }
+        if let Some(abstract_) = &self.abstract_ {
+            terms.push(format!("abs:{}", quote(abstract_)));
+        }
+        if let Some(bibstem) = &self.bibstem {
+            terms.push(format!("bibstem:{}", quote(bibstem)));
+        }
+        if let Some(orcid) = &self.orcid {
+            terms.push(format!("orcid:{}", orcid));
+        }
+        match terms.len() {
+            0 => Err(Error::EmptyQuery),
+            1 => Ok(terms.remove(0)),
+            _ => Ok(terms
+                .iter()
+                .map(|term| format!("({})", term))
+                .collect::<Vec<_>>()
+                .join(" AND ")),
+        }
+    }
+}
+
+/// One end of a publication date range, where a year is expanded to the given
+/// month and a missing date is left open.
+fn date_bound(date: Option<&str>, month: &str) -> String {
+    match date {
+        Some(year) if year.len() == 4 => format!("{}-{}", year, month),
+        Some(date) => date.to_owned(),
+        None => "*".to_owned(),
+    }
+}
+
+/// Join the lines of a query file into a single query, skipping blank lines
+/// and comments.
+fn join_lines(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quote a value as a phrase, escaping any special characters.
+pub(crate) fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Check that a year, or range of years, is well formed.
+fn parse_year(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let valid = value
+        .splitn(2, '-')
+        .all(|year| year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()));
+    if valid {
+        Ok(value.to_owned())
+    } else {
+        Err(format!(
+            "'{}' is not a valid year (expected YYYY or YYYY-YYYY)",
+            value
+        ))
+    }
+}
+
+/// Check that a date is given as YYYY, YYYY-MM, or YYYY-MM-DD.
+fn parse_date(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let parts = value.split('-').collect::<Vec<_>>();
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    let valid = match parts.as_slice() {
+        [year] => year.len() == 4 && digits(year),
+        [year, month] => {
+            year.len() == 4
+                && digits(year)
+                && month.len() == 2
+                && matches!(month.parse(), Ok(1..=12))
+        }
+        [_, _, _] => {
+            value.len() == 10 && chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+        }
+        _ => false,
+    };
+    if valid {
+        Ok(value.to_owned())
+    } else {
+        Err(format!(
+            "'{}' is not a valid date (expected YYYY, YYYY-MM, or YYYY-MM-DD)",
+            value
+        ))
+    }
+}
+
+/// Parse a sort field and direction, e.g. "date asc".
+fn parse_sort(value: &str) -> Result<Sort, String> {
+    value.parse().map_err(|err: AdsError| err.to_string())
+}
+
+/// Parse and normalize an ORCID iD, which can also be given as a URL.
+fn parse_orcid(value: &str) -> Result<String, String> {
+    let orcid = value
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("orcid.org/")
+        .to_uppercase();
+    let groups = orcid.split('-').collect::<Vec<_>>();
+    let valid = groups.len() == 4
+        && groups.iter().enumerate().all(|(n, group)| {
+            group.len() == 4
+                && group
+                    .chars()
+                    .enumerate()
+                    .all(|(m, c)| c.is_ascii_digit() || (n == 3 && m == 3 && c == 'X'))
+        });
+    if valid {
+        Ok(orcid)
+    } else {
+        Err(format!(
+            "'{}' is not a valid ORCID iD (expected XXXX-XXXX-XXXX-XXXX)",
+            value
+        ))
+    }
+}
+
+/// The command line options shared by all commands that list records.
+#[derive(clap::Args)]
+pub struct ResultArgs {
+    /// The maximum number of records to return
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub limit: u64,
+
+    /// Return all matching records, waiting for the rate limit to reset if
+    /// necessary
+    #[arg(long, conflicts_with = "limit")]
+    pub all: bool,
+
+    /// The field to sort on, optionally followed by "asc" or "desc" (the
+    /// default); this can be repeated to sort on more fields
+    #[arg(short, long, value_name = "FIELD [DIRECTION]", value_parser = parse_sort)]
+    pub sort: Vec<Sort>,
+
+    /// Only include records that also match this filter query, e.g.
+    /// property:refereed; this can be repeated
+    #[arg(long, value_name = "QUERY")]
+    pub fq: Vec<String>,
+
+    /// A comma separated list of fields to return
+    #[arg(long)]
+    pub fl: Option<String>,
+
+    /// Collapse arXiv preprints with their published versions, keeping the
+    /// refereed record
+    #[arg(long)]
+    pub dedupe: bool,
+
+    /// Only print the values of these comma separated fields, with one record
+    /// per line and the values separated by tabs
+    #[arg(long, value_delimiter = ',', conflicts_with = "output")]
+    pub get: Option<Vec<String>>,
+
+    /// Print one line for each record using a template, where placeholders
+    /// like {year} are replaced by the values of the fields, e.g.
+    /// "{first_author} ({year}) {title}"
+    #[arg(long, conflicts_with_all = ["get", "output"])]
+    pub template: Option<output::Template>,
+
+    /// The output format
+    #[arg(short, long, value_enum, default_value_t = output::Format::Text)]
+    pub output: output::Format,
+
+    /// Only print the number of matching records
+    #[arg(long, conflicts_with_all = ["all", "dedupe", "get", "template", "output"])]
+    pub count: bool,
+
+    /// Print the complete JSON response from the API for a single page of
+    /// results, without any processing
+    #[arg(long, conflicts_with_all = ["all", "dedupe", "get", "template", "output", "count"])]
+    pub raw: bool,
+
+    /// Choose from the results interactively, and then print, copy, export,
+    /// or open the chosen records
+    #[arg(long, conflicts_with_all = ["dedupe", "get", "template", "output", "count", "raw"])]
+    pub pick: bool,
+
+    /// Don't display a progress bar while fetching results
+    #[arg(long)]
+    pub no_progress: bool,
+
+    /// Always fetch fresh results, instead of using recently cached results
+    #[arg(long)]
+    pub no_cache: bool,
+}
+
+impl ResultArgs {
+    /// The maximum number of records to return, or `None` for all of them.
+    pub fn limit(&self) -> Option<u64> {
+        if self.all {
+            None
+        } else {
+            Some(self.limit)
+        }
+    }
+
+    /// The fields to request from the API, if they differ from the defaults.
+    pub fn fl(&self) -> Option<String> {
+        let fl = match (&self.fl, &self.get, &self.template) {
+            (Some(fl), _, _) => Some(fl.clone()),
+            (None, Some(fields), _) => Some(fields.join(",")),
+            (None, None, Some(template)) => Some(template.fields().join(",")),
+            (None, None, None) => None,
+        };
+        if self.dedupe {
+            let fl = fl.unwrap_or_else(|| output::DEFAULT_FIELDS.to_owned());
+            Some(format!("{},{}", fl, DUPLICATE_FIELDS))
+        } else {
+            fl
+        }
+    }
+
+    /// The filter query combining all of the `--fq` options, if any.
+    pub fn fq(&self) -> Option<String> {
+        match self.fq.as_slice() {
+            [] => None,
+            [fq] => Some(fq.clone()),
+            fqs => Some(
+                fqs.iter()
+                    .map(|fq| format!("({})", fq))
+                    .collect::<Vec<_>>()
+                    .join(" AND "),
+            ),
+        }
+    }
+
+    /// Build the query for `q` using the sort, filter, and field options.
+    fn query<'a>(&self, client: &'a Ads, q: &str) -> Query<'a> {
+        let mut query = client.search(q);
+        for sort in &self.sort {
+            query = query.sort(sort.clone());
+        }
+        if let Some(fq) = self.fq() {
+            query = query.fq(&fq);
+        }
+        if let Some(fl) = self.fl() {
+            query = query.fl(&fl);
+        }
+        query
+    }
+
+    /// Write `docs` to `out` using the requested output format.
+    fn write<W, I>(&self, out: &mut W, docs: I) -> Result<()>
+    where
+        W: Write,
+        I: IntoIterator<Item = adsabs::Result<adsabs::search::Document>>,
+    {
+        if self.dedupe {
+            // Deduplication needs all the records, so we can't stream them
+            let docs = docs.into_iter().collect::<adsabs::Result<Vec<_>>>()?;
+            self.write_format(out, merge_duplicates(docs).into_iter().map(Ok))
+        } else {
+            self.write_format(out, docs)
+        }
+    }
+
+    fn write_format<W, I>(&self, out: &mut W, docs: I) -> Result<()>
+    where
+        W: Write,
+        I: IntoIterator<Item = adsabs::Result<adsabs::search::Document>>,
+    {
+        match (&self.get, &self.template) {
+            (Some(fields), _) => output::write_fields(out, fields, docs),
+            (None, Some(template)) => output::write_template(out, template, docs),
+            (None, None) => output::write_docs(out, self.output, docs),
+        }
+    }
+
+    /// A key identifying the results of running `q` with these options,
+    /// against the API at `base_url`.
+    fn cache_key(&self, base_url: &str, q: &str) -> String {
+        serde_json::json!({
+            "base_url": base_url,
+            "q": q,
+            "limit": self.limit(),
+            "sort": self.sort,
+            "fq": self.fq,
+            "fl": self.fl(),
+            "dedupe": self.dedupe,
+        })
+        .to_string()
+    }
+}
+
+/// Execute the query `q` and write the results to stdout.
+pub fn run(client: &Ads, q: &str, args: &ResultArgs) -> Result<()> {
+    if args.count {
+        return count(client, q, args);
+    }
+    if args.raw {
+        return raw(client, q, args);
+    }
+    if args.pick {
+        return pick(client, q, args);
+    }
+    let cache = if args.no_cache {
+        None
+    } else {
+        Cache::open().ok()
+    };
+    let key = args.cache_key(client.base_url(), q);
+    if let Some(docs) = cache.as_ref().and_then(|cache| cache.get(&key)) {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let empty = docs.is_empty();
+        args.write(&mut out, docs.into_iter().map(Ok))?;
+        out.flush()?;
+        history::record(q, None)?;
+        return if empty { Err(Error::NoResults) } else { Ok(()) };
+    }
+
+    let progress = Progress::new(!args.no_progress);
+    let mut iter = args.query(client, q).iter_docs();
+    if let Some(limit) = args.limit() {
+        iter = iter.limit(limit);
+    }
+    if args.all {
+        iter = iter.wait_on_rate_limit(MAX_RATE_LIMIT_WAIT);
+    }
+    let mut fetched = Vec::new();
+    let mut error = None;
+    let docs = std::iter::from_fn(|| {
+        let doc = match iter.next()? {
+            Err(err) => {
+                // Finish writing the output before reporting the error
+                error = Some(err);
+                return None;
+            }
+            Ok(doc) => doc,
+        };
+        fetched.push(doc.clone());
+        let total = iter
+            .num_found()
+            .map(|n| args.limit().map_or(n, |limit| n.min(limit)));
+        progress.update(fetched.len() as u64, total, client.rate_limit());
+        Some(Ok(doc))
+    });
+
+    let stdout = io::stdout();
+    let mut out = progress.wrap(stdout.lock());
+    args.write(&mut out, docs)?;
+    out.flush()?;
+    progress.finish();
+    if let Some(err) = error {
+        return Err(err.into());
+    }
+    history::record(q, iter.num_found())?;
+
+    if let (false, Some(num_found)) = (args.all, iter.num_found()) {
+        if num_found > args.limit {
+            eprintln!(
+                "showing {} of {} records; use --limit or --all to see more",
+                args.limit, num_found
+            );
+        }
+    }
+    let empty = fetched.is_empty();
+    if let Some(cache) = cache {
+        cache.put(&key, fetched)?;
+    }
+    if empty {
+        return Err(Error::NoResults);
+    }
+    Ok(())
+}
+
+/// Print the unprocessed response for the first page of results.
+fn raw(client: &Ads, q: &str, args: &ResultArgs) -> Result<()> {
+    let response = args
+        .query(client, q)
+        .rows(args.limit.min(MAX_ROWS))
+        .send_raw()?;
+    let stdout = io::stdout();
+    output::write_raw(&mut stdout.lock(), &response)?;
+    history::record(q, None)?;
+    Ok(())
+}
+
+/// Fetch the results and let the user choose from them.
+fn pick(client: &Ads, q: &str, args: &ResultArgs) -> Result<()> {
+    let mut query = args.query(client, q);
+    if args.fl.is_some() {
+        query = query.fl("bibcode");
+    }
+    let progress = Progress::new(!args.no_progress);
+    let mut iter = query.iter_docs();
+    if let Some(limit) = args.limit() {
+        iter = iter.limit(limit);
+    }
+    let mut docs = Vec::new();
+    while let Some(doc) = iter.next() {
+        docs.push(doc?);
+        let total = iter
+            .num_found()
+            .map(|n| args.limit().map_or(n, |limit| n.min(limit)));
+        progress.update(docs.len() as u64, total, client.rate_limit());
+    }
+    progress.finish();
+    history::record(q, iter.num_found())?;
+    pick::pick(client, docs)
+}
+
+/// Print the number of records matching a query, without fetching any of them.
+fn count(client: &Ads, q: &str, args: &ResultArgs) -> Result<()> {
+    let mut query = client.search(q).rows(0).fl("id");
+    if let Some(fq) = args.fq() {
+        query = query.fq(&fq);
+    }
+    let num_found = query.send()?.num_found;
+    println!("{}", num_found);
+    history::record(q, Some(num_found))?;
+    if num_found == 0 {
+        return Err(Error::NoResults);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_args(query: Option<&str>, orcid: Option<&str>) -> QueryArgs {
+        QueryArgs {
+            query: query.map(str::to_owned),
+            orcid: orcid.map(str::to_owned),
+            ..QueryArgs::default()
+        }
+    }
+
+    #[test]
+    fn quote_escapes() {
+        assert_eq!(quote("Hogg, D"), "\"Hogg, D\"");
+        assert_eq!(quote("a\\\" OR b"), "\"a\\\\\\\" OR b\"");
+    }
+
+    #[test]
+    fn structured_query() {
+        let args = QueryArgs {
+            author: vec!["Hogg, D".to_owned(), "Foreman-Mackey".to_owned()],
+            year: Some("2010-2020".to_owned()),
+            title: Some("the \"MCMC\" hammer".to_owned()),
+            ..QueryArgs::default()
+        };
+        assert_eq!(
+            args.build().unwrap(),
+            "(author:\"Hogg, D\") AND (author:\"Foreman-Mackey\") AND (year:2010-2020) \
+             AND (title:\"the \\\"MCMC\\\" hammer\")"
+        );
+    }
+
+    #[test]
+    fn query_file() {
+        let text = "# exoplanet surveys\n\
+                    abs:exoplanet\n\
+                    \n\
+                    AND (bibstem:ApJ OR\n    bibstem:AJ)\n";
+        assert_eq!(
+            join_lines(text),
+            "abs:exoplanet AND (bibstem:ApJ OR bibstem:AJ)"
+        );
+        assert_eq!(join_lines("# only a comment\n"), "");
+    }
+
+    #[test]
+    fn years() {
+        assert_eq!(parse_year("2013"), Ok("2013".to_owned()));
+        assert_eq!(parse_year("2010-2020"), Ok("2010-2020".to_owned()));
+        assert!(parse_year("13").is_err());
+        assert!(parse_year("2010-").is_err());
+        assert!(parse_year("2010-2020-2030").is_err());
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("2020"), Ok("2020".to_owned()));
+        assert_eq!(parse_date("2020-03"), Ok("2020-03".to_owned()));
+        assert_eq!(parse_date("2020-02-29"), Ok("2020-02-29".to_owned()));
+        assert!(parse_date("2020-13").is_err());
+        assert!(parse_date("2021-02-29").is_err());
+        assert!(parse_date("2020-3").is_err());
+        assert!(parse_date("2020-3-1").is_err());
+        assert!(parse_date("20").is_err());
+
+        let args = QueryArgs {
+            from: Some("2020".to_owned()),
+            to: Some("2021-06".to_owned()),
+            ..QueryArgs::default()
+        };
+        assert_eq!(args.build().unwrap(), "pubdate:[2020-01 TO 2021-06]");
+        let args = QueryArgs {
+            query: Some("exoplanet".to_owned()),
+            to: Some("2019".to_owned()),
+            ..QueryArgs::default()
+        };
+        assert_eq!(
+            args.build().unwrap(),
+            "(exoplanet) AND (pubdate:[* TO 2019-12])"
+        );
+    }
+
+    #[test]
+    fn sorts() {
+        assert_eq!(parse_sort("date"), Ok(Sort::desc("date")));
+        assert_eq!(parse_sort("date asc"), Ok(Sort::asc("date")));
+        assert_eq!(
+            parse_sort("citation_count DESC"),
+            Ok(Sort::desc("citation_count"))
+        );
+        assert!(parse_sort("date up").is_err());
+        assert!(parse_sort("").is_err());
+    }
+
+    #[test]
+    fn filters() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            results: ResultArgs,
+        }
+
+        let cli = Cli::parse_from(["ads"]);
+        assert_eq!(cli.results.fq(), None);
+        let cli = Cli::parse_from(["ads", "--fq", "property:refereed"]);
+        assert_eq!(cli.results.fq(), Some("property:refereed".to_owned()));
+        let cli = Cli::parse_from(["ads", "--fq", "property:refereed", "--fq", "year:2020-2024"]);
+        assert_eq!(
+            cli.results.fq(),
+            Some("(property:refereed) AND (year:2020-2024)".to_owned())
+        );
+    }
+
+    #[test]
+    fn cache_keys() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            results: ResultArgs,
+        }
+
+        let cli = Cli::parse_from(["ads"]);
+        let key = cli
+            .results
+            .cache_key("https://api.adsabs.harvard.edu/v1/", "star");
+        assert_eq!(
+            key,
+            cli.results
+                .cache_key("https://api.adsabs.harvard.edu/v1/", "star")
+        );
+        assert_ne!(
+            key,
+            cli.results.cache_key("https://dev.example.com/v1/", "star")
+        );
+    }
+
+    #[test]
+    fn orcid() {
+        assert_eq!(
+            parse_orcid("0000-0002-9328-5652"),
+            Ok("0000-0002-9328-5652".to_owned())
+        );
+        assert_eq!(
+            parse_orcid("https://orcid.org/0000-0001-5109-301x"),
+            Ok("0000-0001-5109-301X".to_owned())
+        );
+        assert!(parse_orcid("0000-0002-9328").is_err());
+        assert!(parse_orcid("0000-000X-9328-5652").is_err());
+    }
+
+    #[test]
+    fn build_query() {
+        assert!(matches!(
+            query_args(None, None).build(),
+            Err(Error::EmptyQuery)
+        ));
+        assert_eq!(query_args(Some("star"), None).build().unwrap(), "star");
+        assert_eq!(
+            query_args(Some("year:2013"), Some("0000-0002-9328-5652"))
+                .build()
+                .unwrap(),
+            "(year:2013) AND (orcid:0000-0002-9328-5652)"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Summarizing the records that match a query, using facet counts.
+//!
+//! Only the number of records in each bucket is requested from the API, so
+//! this works for queries with any number of matches.
+
+use crate::error::{Error, Result};
+use crate::search::QueryArgs;
+use adsabs::Ads;
+use std::io::{self, Write};
+
+// The width of the longest bar in the histogram
+const BAR_WIDTH: u64 = 40;
+
+#[derive(clap::Args)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    query: QueryArgs,
+
+    /// How to group the matching records
+    #[arg(long, value_enum, default_value_t = By::Year)]
+    by: By,
+
+    /// The maximum number of groups to show, for groups sorted by count
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum By {
+    Year,
+    Journal,
+    Doctype,
+}
+
+impl By {
+    fn field(self) -> &'static str {
+        match self {
+            By::Year => "year",
+            By::Journal => "bibstem_facet",
+            By::Doctype => "doctype",
+        }
+    }
+}
+
+pub fn run(client: &Ads, args: &StatsArgs) -> Result<()> {
+    let query = args.query.build()?;
+    let mut counts = client.search(&query).facet_counts(args.by.field())?;
+    if counts.is_empty() {
+        return Err(Error::NoResults);
+    }
+    // Years are shown in order, and everything else from the largest group
+    if !matches!(args.by, By::Year) {
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(args.limit);
+    }
+    let stdout = io::stdout();
+    write_histogram(&mut stdout.lock(), &counts)
+}
+
+fn write_histogram(out: &mut impl Write, counts: &[(String, u64)]) -> Result<()> {
+    let label_width = counts.iter().map(|(label, _)| label.chars().count()).max();
+    let max = counts.iter().map(|&(_, count)| count).max().unwrap_or(1);
+    let count_width = max.to_string().len();
+    for (label, count) in counts {
+        // Round up, so that every non-empty group has a visible bar
+        let bar = (count * BAR_WIDTH).div_ceil(max) as usize;
+        writeln!(
+            out,
+            "{:<label_width$}  {:>count_width$}  {}",
+            label,
+            count,
+            "#".repeat(bar),
+            label_width = label_width.unwrap_or_default(),
+            count_width = count_width,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram() {
+        let counts = [
+            ("ApJ".to_owned(), 120),
+            ("MNRAS".to_owned(), 60),
+            ("A&A".to_owned(), 1),
+        ];
+        let mut out = Vec::new();
+        write_histogram(&mut out, &counts).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "ApJ    120  {}\nMNRAS   60  {}\nA&A      1  #\n",
+                "#".repeat(40),
+                "#".repeat(20)
+            )
+        );
+    }
+}
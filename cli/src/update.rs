@@ -0,0 +1,185 @@
+//! Updating the `ads` binary to the latest GitHub release.
+//!
+//! Each release is expected to include a binary for every supported platform,
+//! named `ads-{arch}-{os}`, e.g. `ads-x86_64-linux` or `ads-aarch64-macos`,
+//! with an `.exe` extension on Windows, along with its SHA-256 checksum in
+//! `ads-{arch}-{os}.sha256`, in the format written by `sha256sum`. The binary
+//! is only installed if it matches the checksum. Installations managed by cargo
+//! should be updated using `cargo install` instead.
+
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/dfm/adsabs-rs/releases/latest";
+
+#[derive(clap::Args)]
+pub struct SelfUpdateArgs {
+    /// Only report whether a newer version is available
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+pub fn run(args: &SelfUpdateArgs) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(concat!("ads/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(Error::Download)?;
+    let release: Release = client
+        .get(RELEASES_URL)
+        .send()
+        .and_then(reqwest::blocking::Response::error_for_status)
+        .and_then(reqwest::blocking::Response::json)
+        .map_err(Error::Download)?;
+
+    let current = env!("CARGO_PKG_VERSION");
+    if !is_newer(&release.tag_name, current) {
+        eprintln!("ads {} is the latest version", current);
+        return Ok(());
+    }
+    if args.check {
+        println!("{}", release.tag_name);
+        eprintln!(
+            "ads {} is available (installed: {}); run `ads self-update` to install it",
+            release.tag_name, current
+        );
+        return Ok(());
+    }
+
+    let name = asset_name(env::consts::ARCH, env::consts::OS);
+    let find_asset = |name: &str| release.assets.iter().find(|asset| asset.name == name);
+    let asset = find_asset(&name)
+        .ok_or_else(|| Error::NoReleaseBinary(release.tag_name.clone(), name.clone()))?;
+    let checksum = find_asset(&format!("{}.sha256", name))
+        .ok_or_else(|| Error::NoReleaseChecksum(release.tag_name.clone(), name.clone()))?;
+    let download = |asset: &Asset| {
+        client
+            .get(&asset.browser_download_url)
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .and_then(reqwest::blocking::Response::bytes)
+            .map_err(Error::Download)
+    };
+    let checksum = download(checksum)?;
+    let binary = download(asset)?;
+    if !matches_checksum(&binary, &String::from_utf8_lossy(&checksum)) {
+        return Err(Error::ChecksumMismatch(name));
+    }
+    replace_current_exe(&binary)?;
+    eprintln!("updated ads from {} to {}", current, release.tag_name);
+    Ok(())
+}
+
+/// The name of the release binary for a platform.
+fn asset_name(arch: &str, os: &str) -> String {
+    let suffix = if os == "windows" { ".exe" } else { "" };
+    format!("ads-{}-{}{}", arch, os, suffix)
+}
+
+/// Returns `true` if the SHA-256 digest of `binary` is the one at the start of
+/// `checksum`, a line like those written by `sha256sum`.
+fn matches_checksum(binary: &[u8], checksum: &str) -> bool {
+    let expected = checksum.split_whitespace().next().unwrap_or_default();
+    let digest = format!("{:x}", Sha256::digest(binary));
+    expected.eq_ignore_ascii_case(&digest)
+}
+
+/// Returns `true` if the release tag is a later version than `current`.
+fn is_newer(tag: &str, current: &str) -> bool {
+    version(tag) > version(current)
+}
+
+/// The numeric components of a version like `v1.2.3`, ignoring any
+/// pre-release or build suffix.
+fn version(value: &str) -> Vec<u64> {
+    value
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Replace the running executable with a new binary, writing it next to the
+/// old one first so that the final rename doesn't cross file systems.
+///
+/// Symlinks to the executable are resolved first, so that the binary they
+/// point to is replaced, instead of the link being replaced by a copy.
+fn replace_current_exe(binary: &[u8]) -> Result<()> {
+    let exe = env::current_exe()?.canonicalize()?;
+    let new = exe.with_extension("new");
+    fs::write(&new, binary)?;
+    make_executable(&new)?;
+    // Windows doesn't allow a running executable to be replaced, but it can be
+    // renamed out of the way
+    if cfg!(windows) {
+        let old = exe.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(&exe, &old)?;
+    }
+    fs::rename(&new, &exe)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions() {
+        assert!(is_newer("v0.2.0", "0.1.9"));
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(is_newer("v1.0.0-rc.1", "0.9.0"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("v0.1.0", "0.2.0"));
+    }
+
+    #[test]
+    fn checksums() {
+        let digest = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert!(matches_checksum(b"hello", digest));
+        assert!(matches_checksum(
+            b"hello",
+            &format!("{}  ads-x86_64-linux\n", digest.to_uppercase())
+        ));
+        assert!(!matches_checksum(b"hello!", digest));
+        assert!(!matches_checksum(b"hello", ""));
+    }
+
+    #[test]
+    fn asset_names() {
+        assert_eq!(asset_name("x86_64", "linux"), "ads-x86_64-linux");
+        assert_eq!(asset_name("aarch64", "macos"), "ads-aarch64-macos");
+        assert_eq!(asset_name("x86_64", "windows"), "ads-x86_64-windows.exe");
+    }
+}
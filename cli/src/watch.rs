@@ -0,0 +1,148 @@
+//! Watching a query for newly indexed records.
+//!
+//! The most recent `entdate` (the date when a record was added to ADS) for
+//! each watched query is stored in a local state file, along with the records
+//! already seen on that date, so that each run only reports new records. This
+//! can either be run periodically using `cron`, or left running with the
+//! `--interval` option.
+
+use crate::error::{Error, Result};
+use crate::output;
+use adsabs::{
+    search::{Document, HarvestState},
+    Ads,
+};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// The maximum number of new records reported in a single check
+const MAX_RECORDS: u64 = 2000;
+
+#[derive(clap::Args)]
+pub struct WatchArgs {
+    /// The search query to watch
+    query: String,
+
+    /// Keep running, checking for new records at this interval (e.g. 1d, 12h)
+    #[arg(long, value_parser = parse_interval)]
+    interval: Option<Duration>,
+
+    /// When watching a query for the first time, report records added since
+    /// this date (YYYY-MM-DD), instead of today
+    #[arg(long)]
+    since: Option<String>,
+
+    /// The output format
+    #[arg(short, long, value_enum, default_value_t = output::Format::Text)]
+    output: output::Format,
+}
+
+pub fn run(client: &Ads, args: &WatchArgs) -> Result<()> {
+    let path = state_path()?;
+    loop {
+        let mut state = load(&path)?;
+        let watched = state.remove(&args.query).unwrap_or_else(|| {
+            HarvestState::new(
+                &args
+                    .since
+                    .clone()
+                    .unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string()),
+            )
+        });
+
+        let (watched, docs) = check(client, &args.query, watched)?;
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        output::write_docs(&mut out, args.output, docs.into_iter().map(Ok))?;
+        out.flush()?;
+
+        state.insert(args.query.clone(), watched);
+        save(&path, &state)?;
+
+        match args.interval {
+            Some(interval) => std::thread::sleep(interval),
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Find the records matching `query` that haven't been seen yet.
+fn check(
+    client: &Ads,
+    query: &str,
+    mut watched: HarvestState,
+) -> Result<(HarvestState, Vec<Document>)> {
+    let mut new = Vec::new();
+    for doc in watched
+        .query(client.search(query).fl(output::DEFAULT_FIELDS))
+        .iter_docs()
+        .limit(MAX_RECORDS)
+    {
+        let doc = doc?;
+        if watched.observe(&doc) {
+            new.push(doc);
+        }
+    }
+    Ok((watched, new))
+}
+
+fn state_path() -> Result<PathBuf> {
+    let mut path = dirs::data_dir().ok_or(Error::DataDir)?;
+    path.push("adsabs");
+    path.push("watch.json");
+    Ok(path)
+}
+
+fn load(path: &Path) -> Result<BTreeMap<String, HarvestState>> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => Ok(serde_json::from_str(&data)?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn save(path: &Path, state: &BTreeMap<String, HarvestState>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+/// Parse an interval like `30m`, `12h`, `1d`, or `2w`.
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid interval '{}'", value))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" | "" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown interval unit '{}'", unit)),
+    };
+    Ok(Duration::from_secs(number * seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals() {
+        assert_eq!(parse_interval("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_interval("12h"), Ok(Duration::from_secs(43200)));
+        assert_eq!(parse_interval("1d"), Ok(Duration::from_secs(86400)));
+        assert_eq!(parse_interval("2"), Ok(Duration::from_secs(172_800)));
+        assert!(parse_interval("1y").is_err());
+        assert!(parse_interval("d").is_err());
+    }
+}
@@ -9,18 +9,16 @@ fn main() -> Result<(), AdsError> {
         .search("author:\"Foreman-Mackey\" AND (doctype:\"article\" OR doctype:\"eprint\")")
         .fl("id,title,author,doi,year,pubdate,pub,volume,page,identifier,doctype,citation_count,bibcode")
         .sort("date")
-        .iter_docs().map(|doc|
-    {
-        // Here I'm just removing HTML encoding since the API will encode
-        // characters like '&' as '&amp;', for example. 
-        doc.map(|mut doc|{
-            doc.title = doc.title.map(|t| {
-                t.iter()
-                    .map(|t| html_escape::decode_html_entities(t).to_string())
-                    .collect::<Vec<_>>()
-            });
+        .iter_docs()
+        .map(|doc| {
+            // The API encodes characters like '&' as '&amp;', for example, so
+            // those are decoded here
+            doc.map(|mut doc| {
+                doc.decode_html();
+                doc
+            })
         })
-    }).collect::<Result<Vec<_>, AdsError>>()?;
+        .collect::<Result<Vec<_>, AdsError>>()?;
 
     std::fs::write("examples/dfm.json", serde_json::to_string_pretty(&docs)?)?;
     Ok(())
@@ -12,6 +12,7 @@ use syn::{AttributeArgs, ItemStruct, NestedMeta};
 /// use adsabs_macro::make_optional;
 ///
 /// #[make_optional]
+/// #[derive(serde::Serialize)]
 /// struct ExampleStruct {
 ///     id: usize,
 ///     name: String,
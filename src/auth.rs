@@ -1,9 +1,63 @@
 use crate::{AdsError, Result};
+use reqwest::header::HeaderValue;
 use std::env;
 use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The `Authorization` headers for the configured tokens, and which one is
+/// currently in use.
+#[derive(Debug)]
+pub(crate) struct Tokens {
+    headers: Vec<HeaderValue>,
+    current: AtomicUsize,
+}
+
+impl Tokens {
+    pub fn new(tokens: &[String]) -> Result<Self> {
+        if tokens.is_empty() {
+            return Err(AdsError::Token);
+        }
+        let headers = tokens
+            .iter()
+            .map(|token| {
+                let mut header: HeaderValue = format!("Bearer {}", token).parse()?;
+                header.set_sensitive(true);
+                Ok(header)
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            headers,
+            current: AtomicUsize::new(0),
+        })
+    }
+
+    /// The index and header of the token in use.
+    pub fn current(&self) -> (usize, HeaderValue) {
+        let index = self.current.load(Ordering::SeqCst);
+        (index, self.headers[index].clone())
+    }
+
+    /// Returns `true` if there is another token after the one at `index`.
+    pub fn has_next(&self, index: usize) -> bool {
+        index + 1 < self.headers.len()
+    }
+
+    /// Switch to the token after the one at `index`, if no other thread has
+    /// already done so, returning `false` if there are no more tokens.
+    pub fn rotate(&self, index: usize) -> bool {
+        if !self.has_next(index) {
+            return false;
+        }
+        let _ = self
+            .current
+            .compare_exchange(index, index + 1, Ordering::SeqCst, Ordering::SeqCst);
+        true
+    }
+}
 
 pub fn get_token() -> Result<String> {
-    get_token_from_env_vars().or_else(|_| get_token_from_home_dir())
+    get_token_from_env_vars().or_else(|_| get_token_from_files())
 }
 
 fn get_token_from_env_vars() -> Result<String> {
@@ -16,14 +70,83 @@ fn get_token_from_env_vars() -> Result<String> {
     }
 }
 
-fn get_token_from_home_dir() -> Result<String> {
-    if let Some(mut ads_dir) = dirs::home_dir() {
-        ads_dir.push(".ads");
-        if let Ok(token) = fs::read_to_string(ads_dir.join("token")) {
-            return Ok(token.trim().to_owned());
-        } else if let Ok(token) = fs::read_to_string(ads_dir.join("dev_key")) {
+fn get_token_from_files() -> Result<String> {
+    let files = token_files(
+        |name| env::var_os(name).map(PathBuf::from),
+        dirs::config_dir(),
+        dirs::home_dir(),
+    );
+    for file in files {
+        if let Ok(token) = fs::read_to_string(file) {
             return Ok(token.trim().to_owned());
         }
     }
     Err(AdsError::Token)
 }
+
+// The files that might contain a token, in the order that they're checked
+fn token_files<F>(var: F, config_dir: Option<PathBuf>, home_dir: Option<PathBuf>) -> Vec<PathBuf>
+where
+    F: Fn(&str) -> Option<PathBuf>,
+{
+    let mut files = Vec::new();
+    files.extend(var("ADS_TOKEN_FILE"));
+    let xdg_config_dir = var("XDG_CONFIG_HOME").filter(|dir| dir.is_absolute());
+    for dir in xdg_config_dir.into_iter().chain(config_dir) {
+        let file = dir.join("adsabs").join("token");
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    if let Some(home_dir) = home_dir {
+        let ads_dir = home_dir.join(".ads");
+        files.push(ads_dir.join("token"));
+        files.push(ads_dir.join("dev_key"));
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_file_order() {
+        let files = token_files(
+            |name| match name {
+                "ADS_TOKEN_FILE" => Some(PathBuf::from("/secrets/ads")),
+                "XDG_CONFIG_HOME" => Some(PathBuf::from("/home/me/.config")),
+                _ => None,
+            },
+            Some(PathBuf::from("/home/me/.config")),
+            Some(PathBuf::from("/home/me")),
+        );
+        assert_eq!(
+            files,
+            [
+                "/secrets/ads",
+                "/home/me/.config/adsabs/token",
+                "/home/me/.ads/token",
+                "/home/me/.ads/dev_key",
+            ]
+            .iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>()
+        );
+
+        let files = token_files(
+            |name| match name {
+                "XDG_CONFIG_HOME" => Some(PathBuf::from("relative")),
+                _ => None,
+            },
+            Some(PathBuf::from("/Users/me/Library/Application Support")),
+            None,
+        );
+        assert_eq!(
+            files,
+            [PathBuf::from(
+                "/Users/me/Library/Application Support/adsabs/token"
+            )]
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Executing many search queries at once.
+//!
+//! # Examples
+//!
+//! To fetch the publications of every author in a department, using at most
+//! four concurrent requests and 100 requests in total:
+//!
+//! ```no_run
+//! # fn run() -> adsabs::Result<()> {
+//! use adsabs::{batch::Batch, Ads};
+//! let client = Ads::new("ADS_API_TOKEN")?;
+//! let authors = ["Hogg, D", "Foreman-Mackey, D", "Dalcanton, J"];
+//! let results = authors
+//!     .iter()
+//!     .map(|&author| {
+//!         let query = client.search(&format!("author:\"{}\"", author)).fl("bibcode");
+//!         (author, query)
+//!     })
+//!     .collect::<Batch<_>>()
+//!     .concurrency(4)
+//!     .budget(100)
+//!     .run();
+//! for (author, docs) in results {
+//!     println!("{}: {} records", author, docs?.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{AdsError, Result};
+use crate::search::{Document, Query, MAX_ROWS};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+// The number of queries executed at once, by default
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A set of search queries, each identified by a key, to be executed
+/// concurrently.
+///
+/// All the matching records are fetched for each query, subject to
+/// [`Batch::limit`]. The requests made by all the queries are counted against
+/// a shared budget, and no more requests are made once the API reports that
+/// the rate limit has been exhausted.
+#[derive(Debug)]
+#[must_use]
+pub struct Batch<'ads, K> {
+    queries: Vec<(K, Query<'ads>)>,
+    concurrency: usize,
+    limit: Option<u64>,
+    budget: Option<u64>,
+}
+
+impl<'ads, K> Default for Batch<'ads, K> {
+    fn default() -> Self {
+        Self {
+            queries: Vec::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+            limit: None,
+            budget: None,
+        }
+    }
+}
+
+impl<'ads, K> Batch<'ads, K>
+where
+    K: Eq + Hash + Send,
+{
+    /// Build a new, empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a query to the batch, identified by `key` in the results.
+    pub fn add(mut self, key: K, query: Query<'ads>) -> Self {
+        self.queries.push((key, query));
+        self
+    }
+
+    /// The maximum number of queries executed at once.
+    ///
+    /// The default is `4`, and values smaller than `1` are treated as `1`.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Limit the number of records returned for each query.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Limit the total number of API requests made by all the queries.
+    ///
+    /// Once the budget has been used up, the remaining queries fail with
+    /// [`AdsError::BudgetExhausted`].
+    pub fn budget(mut self, requests: u64) -> Self {
+        self.budget = Some(requests);
+        self
+    }
+
+    /// Execute all the queries, returning the records for each key.
+    ///
+    /// Each query succeeds or fails independently, so one failed query doesn't
+    /// affect the results of the others. If the same key is used for more than
+    /// one query, only one of the results is kept.
+    pub fn run(self) -> HashMap<K, Result<Vec<Document>>> {
+        let budget = Budget::new(self.budget);
+        let limit = self.limit;
+        let workers = self.concurrency.min(self.queries.len());
+        let queue = Mutex::new(self.queries.into_iter().collect::<VecDeque<_>>());
+        let results = Mutex::new(HashMap::new());
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let next = queue
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .pop_front();
+                    let (key, query) = match next {
+                        Some(next) => next,
+                        None => break,
+                    };
+                    let docs = fetch(&query, limit, &budget);
+                    results
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .insert(key, docs);
+                });
+            }
+        });
+        results.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<'ads, K> FromIterator<(K, Query<'ads>)> for Batch<'ads, K> {
+    fn from_iter<I: IntoIterator<Item = (K, Query<'ads>)>>(iter: I) -> Self {
+        let mut batch = Self::default();
+        batch.extend(iter);
+        batch
+    }
+}
+
+impl<'ads, K> Extend<(K, Query<'ads>)> for Batch<'ads, K> {
+    fn extend<I: IntoIterator<Item = (K, Query<'ads>)>>(&mut self, iter: I) {
+        self.queries.extend(iter);
+    }
+}
+
+/// Fetch all the pages of results for a query.
+fn fetch(query: &Query<'_>, limit: Option<u64>, budget: &Budget) -> Result<Vec<Document>> {
+    let start = query.start.unwrap_or(0);
+    let limit = limit.unwrap_or(u64::MAX);
+    let mut docs = Vec::new();
+    loop {
+        if let Some(rate_limit) = query.client.rate_limit() {
+            if rate_limit.is_exhausted() {
+                return Err(AdsError::RateLimited {
+                    reset: Some(rate_limit.reset),
+                });
+            }
+        }
+        budget.take()?;
+        let remaining = limit - docs.len() as u64;
+        let response = query
+            .clone()
+            .start(start + docs.len() as u64)
+            .rows(MAX_ROWS.min(remaining))
+            .send()?;
+        let count = response.docs.len() as u64;
+        docs.extend(response.docs);
+        if count == 0 || count >= remaining || start + docs.len() as u64 >= response.num_found {
+            return Ok(docs);
+        }
+    }
+}
+
+/// A number of requests shared between threads.
+struct Budget {
+    remaining: Option<AtomicU64>,
+}
+
+impl Budget {
+    fn new(requests: Option<u64>) -> Self {
+        Self {
+            remaining: requests.map(AtomicU64::new),
+        }
+    }
+
+    /// Use up one request from the budget, failing if none are left.
+    fn take(&self) -> Result<()> {
+        match &self.remaining {
+            None => Ok(()),
+            Some(remaining) => remaining
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .map(|_| ())
+                .map_err(|_| AdsError::BudgetExhausted),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget() {
+        let budget = Budget::new(Some(2));
+        assert!(budget.take().is_ok());
+        assert!(budget.take().is_ok());
+        assert!(matches!(budget.take(), Err(AdsError::BudgetExhausted)));
+        let unlimited = Budget::new(None);
+        assert!((0..10).all(|_| unlimited.take().is_ok()));
+    }
+
+    #[test]
+    fn empty_batch() {
+        assert!(Batch::<u32>::new().concurrency(0).run().is_empty());
+    }
+
+    #[test]
+    fn client_is_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<crate::Ads>();
+        assert_send_sync::<Query<'_>>();
+    }
+}
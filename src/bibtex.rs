@@ -0,0 +1,536 @@
+//! Reading and updating BibTeX files.
+//!
+//! This is a small parser that handles the files exported by ADS and most
+//! hand-written files, and a [`BibFile`] type for merging new entries into an
+//! existing file while keeping everything else as it was written.
+
+use crate::error::{AdsError, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::CharIndices;
+
+/// An entry parsed from a BibTeX file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibEntry {
+    /// The entry type, in lowercase, e.g. `article`.
+    pub kind: String,
+    /// The citation key.
+    pub key: String,
+    /// The fields, with lowercase names and the outer braces or quotes
+    /// removed from their values.
+    pub fields: BTreeMap<String, String>,
+}
+
+impl BibEntry {
+    /// Format the entry as BibTeX, with one field per line.
+    pub fn to_bibtex(&self) -> String {
+        let mut text = format!("@{}{{{},\n", self.kind, self.key);
+        for (name, value) in &self.fields {
+            text.push_str(&format!("    {} = {{{}}},\n", name, value));
+        }
+        text.push('}');
+        text
+    }
+
+    /// The value of a field, if present.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    /// The bibcode of this entry, if it is known without any API requests,
+    /// from the `adsurl` field or an ADS-style citation key.
+    pub fn bibcode(&self) -> Option<String> {
+        if let Some(url) = self.field("adsurl") {
+            if let Some((_, rest)) = url.split_once("/abs/") {
+                let bibcode = rest.split('/').next().unwrap_or_default();
+                if !bibcode.is_empty() {
+                    return Some(bibcode.replace("%26", "&"));
+                }
+            }
+        }
+        if looks_like_bibcode(&self.key) {
+            return Some(self.key.clone());
+        }
+        None
+    }
+
+    /// The DOI of this entry, if present.
+    pub fn doi(&self) -> Option<&str> {
+        self.field("doi")
+            .map(str::trim)
+            .filter(|doi| !doi.is_empty())
+    }
+
+    /// The arXiv identifier of this entry, from the `eprint` field.
+    pub fn arxiv_id(&self) -> Option<&str> {
+        let archive = self.field("archiveprefix").unwrap_or("arXiv");
+        if !archive.eq_ignore_ascii_case("arxiv") {
+            return None;
+        }
+        let eprint = self.field("eprint")?.trim();
+        let eprint = eprint
+            .strip_prefix("arXiv:")
+            .or_else(|| eprint.strip_prefix("arxiv:"))
+            .unwrap_or(eprint);
+        if eprint.is_empty() {
+            None
+        } else {
+            Some(eprint)
+        }
+    }
+
+    /// A free-text reference string for the ADS reference resolver, built from
+    /// the author, year, journal, volume, and page fields.
+    pub fn reference(&self) -> Option<String> {
+        let author = self.field("author")?;
+        let year = self.field("year")?;
+        let mut reference = format!("{} {}", strip_braces(author), year);
+        for name in &["journal", "booktitle", "volume", "pages"] {
+            if let Some(value) = self.field(name) {
+                reference.push_str(", ");
+                reference.push_str(&strip_braces(value));
+            }
+        }
+        Some(reference)
+    }
+}
+
+/// Parse the entries from the contents of a BibTeX file.
+///
+/// `@comment`, `@preamble`, and `@string` blocks are skipped, and string
+/// macros aren't expanded, so a field that uses one will contain the macro
+/// name.
+///
+/// # Errors
+///
+/// Fails with [`AdsError::Bibtex`] if an entry is malformed.
+pub fn parse_bibtex(input: &str) -> Result<Vec<BibEntry>> {
+    Ok(parse_spans(input)?
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect())
+}
+
+// Parse the entries along with their locations in the input
+fn parse_spans(input: &str) -> Result<Vec<(Range<usize>, BibEntry)>> {
+    let mut parser = Parser {
+        input,
+        chars: input.char_indices().peekable(),
+    };
+    let mut entries = Vec::new();
+    while let Some(start) = parser.skip_to('@') {
+        let kind = parser.identifier().to_lowercase();
+        parser.skip_whitespace();
+        let close = match parser.next() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => return Err(parser.error("expected '{' or '(' after the entry type")),
+        };
+        if matches!(kind.as_str(), "comment" | "preamble" | "string") {
+            parser.skip_block(close)?;
+            continue;
+        }
+        let entry = parser.entry(kind, close)?;
+        entries.push((start..parser.offset(), entry));
+    }
+    Ok(entries)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<char> {
+        self.chars.next().map(|(_, c)| c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn error(&mut self, message: &str) -> AdsError {
+        let offset = self.offset();
+        let line = self.input[..offset].matches('\n').count() + 1;
+        AdsError::Bibtex(format!("{} on line {}", message, line))
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.input.len(), |&(i, _)| i)
+    }
+
+    // Skip past the next `target`, returning its offset
+    fn skip_to(&mut self, target: char) -> Option<usize> {
+        self.chars.find(|&(_, c)| c == target).map(|(i, _)| i)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        self.skip_whitespace();
+        let mut identifier = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, '{' | '}' | '(' | ')' | ',' | '=' | '#' | '"') {
+                break;
+            }
+            identifier.push(c);
+            self.next();
+        }
+        identifier
+    }
+
+    // Skip to the end of a block, after its opening delimiter
+    fn skip_block(&mut self, close: char) -> Result<()> {
+        let mut depth = 0;
+        while let Some(c) = self.next() {
+            match c {
+                '{' | '(' => depth += 1,
+                c if c == close && depth == 0 => return Ok(()),
+                '}' | ')' => depth -= 1,
+                _ => {}
+            }
+        }
+        Err(self.error("unexpected end of file"))
+    }
+
+    fn entry(&mut self, kind: String, close: char) -> Result<BibEntry> {
+        let key = self.identifier();
+        let mut fields = BTreeMap::new();
+        loop {
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some(c) if c == close => break,
+                _ => return Err(self.error("expected ',' or the end of the entry")),
+            }
+            let name = self.identifier().to_lowercase();
+            if name.is_empty() {
+                // Allow a trailing comma before the end of the entry
+                self.skip_whitespace();
+                if self.peek() == Some(close) {
+                    self.next();
+                    break;
+                }
+                return Err(self.error("expected a field name"));
+            }
+            self.skip_whitespace();
+            if self.next() != Some('=') {
+                return Err(self.error("expected '=' after the field name"));
+            }
+            let value = self.value()?;
+            fields.insert(name, value);
+        }
+        Ok(BibEntry { kind, key, fields })
+    }
+
+    // Parse a field value, which may be a concatenation using '#'
+    fn value(&mut self) -> Result<String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.next();
+                    value.push_str(&self.delimited('}')?);
+                }
+                Some('"') => {
+                    self.next();
+                    value.push_str(&self.delimited('"')?);
+                }
+                _ => {
+                    let word = self.identifier();
+                    if word.is_empty() {
+                        return Err(self.error("expected a field value"));
+                    }
+                    value.push_str(&word);
+                }
+            }
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.next();
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    // Read until the closing delimiter, keeping any nested braces
+    fn delimited(&mut self, close: char) -> Result<String> {
+        let mut value = String::new();
+        let mut depth = 0;
+        while let Some(c) = self.next() {
+            match c {
+                c if c == close && depth == 0 => return Ok(value),
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            value.push(c);
+        }
+        Err(self.error("unexpected end of file in a field value"))
+    }
+}
+
+fn looks_like_bibcode(key: &str) -> bool {
+    key.len() == 19 && key.is_ascii() && key[..4].bytes().all(|b| b.is_ascii_digit())
+}
+
+fn strip_braces(value: &str) -> String {
+    value.chars().filter(|&c| c != '{' && c != '}').collect()
+}
+
+// The fields that are refreshed in existing entries by `BibFile::merge`, which
+// change when a preprint is published
+const PUBLICATION_FIELDS: &[&str] = &[
+    "adsnote",
+    "adsurl",
+    "archiveprefix",
+    "booktitle",
+    "doi",
+    "eid",
+    "eprint",
+    "journal",
+    "month",
+    "number",
+    "pages",
+    "primaryclass",
+    "volume",
+    "year",
+];
+
+/// A BibTeX file that can be updated with new entries, keeping the text of
+/// any entries that don't change.
+///
+/// # Example
+///
+/// ```rust
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::bibtex::{parse_bibtex, BibFile};
+/// let mut file = BibFile::parse("% My references\n@article{key, title = {{Old}}, note = {Mine}}\n")?;
+/// let report = file.merge(parse_bibtex("@article{key, title = {New}, volume = {1}}")?);
+/// assert_eq!(report.updated, ["key"]);
+/// let text = file.to_string();
+/// assert!(text.starts_with("% My references\n"));
+/// assert!(text.contains("title = {{Old}}") && text.contains("note = {Mine}"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BibFile {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Text(String),
+    Entry { text: String, entry: BibEntry },
+}
+
+/// The changes made by [`BibFile::merge`], as lists of citation keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// The entries that were added to the end of the file.
+    pub added: Vec<String>,
+    /// The existing entries that were updated.
+    pub updated: Vec<String>,
+    /// The existing entries that were already up to date.
+    pub unchanged: Vec<String>,
+}
+
+impl BibFile {
+    /// Parse the contents of a BibTeX file.
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut last = 0;
+        for (span, entry) in parse_spans(input)? {
+            if span.start > last {
+                segments.push(Segment::Text(input[last..span.start].to_owned()));
+            }
+            segments.push(Segment::Entry {
+                text: input[span.clone()].to_owned(),
+                entry,
+            });
+            last = span.end;
+        }
+        if last < input.len() {
+            segments.push(Segment::Text(input[last..].to_owned()));
+        }
+        Ok(Self { segments })
+    }
+
+    /// The entries in the file.
+    pub fn entries(&self) -> impl Iterator<Item = &BibEntry> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Entry { entry, .. } => Some(entry),
+            Segment::Text(_) => None,
+        })
+    }
+
+    /// Merge entries, like those exported from ADS, into the file.
+    ///
+    /// Entries are matched to existing entries using their citation keys, or
+    /// the bibcode in the `adsurl` field of the existing entries. Entries that
+    /// aren't in the file are added to the end. For existing entries, any
+    /// missing fields are added and the publication details (like `journal`,
+    /// `volume`, `pages`, and `doi`) are refreshed, but other fields, such as
+    /// titles that have been edited by hand, are left alone.
+    pub fn merge<I>(&mut self, entries: I) -> MergeReport
+    where
+        I: IntoIterator<Item = BibEntry>,
+    {
+        let mut report = MergeReport::default();
+        for new in entries {
+            let existing = self.segments.iter_mut().find_map(|segment| match segment {
+                Segment::Entry { text, entry }
+                    if entry.key == new.key || entry.bibcode().as_ref() == Some(&new.key) =>
+                {
+                    Some((text, entry))
+                }
+                _ => None,
+            });
+            let (text, entry) = match existing {
+                Some(existing) => existing,
+                None => {
+                    report.added.push(new.key.clone());
+                    self.push(new);
+                    continue;
+                }
+            };
+            let mut changed = false;
+            for (name, value) in new.fields {
+                let refresh = PUBLICATION_FIELDS.contains(&name.as_str());
+                match entry.fields.get_mut(&name) {
+                    Some(old) if refresh && *old != value => *old = value,
+                    Some(_) => continue,
+                    None => {
+                        entry.fields.insert(name, value);
+                    }
+                }
+                changed = true;
+            }
+            if changed {
+                *text = entry.to_bibtex();
+                report.updated.push(entry.key.clone());
+            } else {
+                report.unchanged.push(entry.key.clone());
+            }
+        }
+        report
+    }
+
+    fn push(&mut self, entry: BibEntry) {
+        let separator = match self.segments.last() {
+            None => "",
+            Some(Segment::Text(text)) if text.ends_with("\n\n") => "",
+            Some(Segment::Text(text)) if text.ends_with('\n') => "\n",
+            Some(_) => "\n\n",
+        };
+        if !separator.is_empty() {
+            self.segments.push(Segment::Text(separator.to_owned()));
+        }
+        self.segments.push(Segment::Entry {
+            text: entry.to_bibtex(),
+            entry,
+        });
+        self.segments.push(Segment::Text("\n".to_owned()));
+    }
+}
+
+impl fmt::Display for BibFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) | Segment::Entry { text, .. } => f.write_str(text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIB: &str = r#"
+        @comment{ignored {nested}}
+        @ARTICLE{2013PASP..125..306F,
+            author = {{Foreman-Mackey}, Daniel and {Hogg}, David W.},
+            title = "{emcee: The MCMC Hammer}",
+            year = 2013,
+        }
+        @misc(joss,
+            doi = {10.21105/joss.01864},
+            note = "part " # {two}
+        )
+        @article{preprint, eprint = {arXiv:1202.3665}, archivePrefix = {arXiv}}
+        @article{reference,
+            author = {Hogg, D. W.},
+            year = {2010},
+            journal = {ApJ},
+            volume = {725},
+            pages = {2166}
+        }
+        @book{unknown, title = {Nothing}}
+    "#;
+
+    #[test]
+    fn parse_entries() {
+        let entries = parse_bibtex(BIB).unwrap();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].kind, "article");
+        assert_eq!(entries[0].bibcode().unwrap(), "2013PASP..125..306F");
+        assert_eq!(
+            entries[0].field("title").unwrap(),
+            "{emcee: The MCMC Hammer}"
+        );
+        assert_eq!(entries[1].field("note").unwrap(), "part two");
+        assert_eq!(entries[1].doi().unwrap(), "10.21105/joss.01864");
+        assert_eq!(entries[2].arxiv_id().unwrap(), "1202.3665");
+        assert_eq!(
+            entries[3].reference().unwrap(),
+            "Hogg, D. W. 2010, ApJ, 725, 2166"
+        );
+        assert!(matches!(
+            parse_bibtex("@article{key, title = {open"),
+            Err(AdsError::Bibtex(_))
+        ));
+    }
+
+    #[test]
+    fn merge_file() {
+        let original =
+            "% header\n\n@article{a, title = {{Fixed} title}, journal = {arXiv e-prints}}\n\n\
+                        @misc{b,\n  adsurl = {https://ui.adsabs.harvard.edu/abs/2020X/abstract}}\n";
+        let mut file = BibFile::parse(original).unwrap();
+        assert_eq!(file.to_string(), original);
+        let report = file.merge(
+            parse_bibtex(
+                "@article{a, title = {Fixed title}, journal = {ApJ}, volume = {1}}\n\
+                 @misc{2020X, adsurl = {https://ui.adsabs.harvard.edu/abs/2020X/abstract}}\n\
+                 @misc{c, title = {New}}",
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            report,
+            MergeReport {
+                added: vec!["c".to_owned()],
+                updated: vec!["a".to_owned()],
+                unchanged: vec!["b".to_owned()],
+            }
+        );
+        assert_eq!(
+            file.to_string(),
+            "% header\n\n@article{a,\n    journal = {ApJ},\n    title = {{Fixed} title},\n    \
+             volume = {1},\n}\n\n@misc{b,\n  adsurl = {https://ui.adsabs.harvard.edu/abs/2020X/abstract}}\n\
+             \n@misc{c,\n    title = {New},\n}\n"
+        );
+    }
+}
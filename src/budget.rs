@@ -0,0 +1,73 @@
+use crate::error::{AdsError, Result};
+use std::sync::{Mutex, PoisonError};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A limit on the number of requests made by a client each day, in UTC.
+#[derive(Debug)]
+pub(crate) struct DailyBudget {
+    limit: u64,
+    // The day (since the Unix epoch) that the count applies to, and the number
+    // of requests made on that day
+    used: Mutex<(i64, u64)>,
+}
+
+impl DailyBudget {
+    pub(crate) fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            used: Mutex::new((0, 0)),
+        }
+    }
+
+    /// Use up one request from today's budget, failing if none are left.
+    pub(crate) fn take(&self) -> Result<()> {
+        self.take_on(crate::timestamp::now_unix().div_euclid(SECONDS_PER_DAY))
+    }
+
+    /// The number of requests remaining today.
+    pub(crate) fn remaining(&self) -> u64 {
+        self.remaining_on(crate::timestamp::now_unix().div_euclid(SECONDS_PER_DAY))
+    }
+
+    fn take_on(&self, today: i64) -> Result<()> {
+        let mut used = self.used.lock().unwrap_or_else(PoisonError::into_inner);
+        if used.0 != today {
+            *used = (today, 0);
+        }
+        if used.1 >= self.limit {
+            return Err(AdsError::BudgetExhausted);
+        }
+        used.1 += 1;
+        Ok(())
+    }
+
+    fn remaining_on(&self, today: i64) -> u64 {
+        let used = self.used.lock().unwrap_or_else(PoisonError::into_inner);
+        if used.0 == today {
+            self.limit.saturating_sub(used.1)
+        } else {
+            self.limit
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_budget() {
+        let budget = DailyBudget::new(2);
+        assert_eq!(budget.remaining_on(10), 2);
+        assert!(budget.take_on(10).is_ok());
+        assert!(budget.take_on(10).is_ok());
+        assert_eq!(budget.remaining_on(10), 0);
+        assert!(matches!(budget.take_on(10), Err(AdsError::BudgetExhausted)));
+
+        // The budget is renewed the next day
+        assert_eq!(budget.remaining_on(11), 2);
+        assert!(budget.take_on(11).is_ok());
+        assert_eq!(budget.remaining_on(11), 1);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::error::{AdsError, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// How often a cancellable wait checks whether it has been cancelled
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A way to stop long running operations, like iterating over many pages of
+/// search results, from another thread or after a deadline.
+///
+/// Clones of a token share the same state, so one clone can be kept by a
+/// shutdown handler while another is passed to
+/// [`crate::search::Query::cancel_token`]. The token is checked before each
+/// request, and while waiting for the rate limit to reset, so a request that
+/// has already been sent still finishes, or times out, first. Cancelled
+/// operations fail with [`AdsError::Cancelled`] or
+/// [`AdsError::DeadlineExceeded`].
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::{Ads, CancelToken};
+/// use std::time::{Duration, Instant};
+/// let client = Ads::new("ADS_API_TOKEN")?;
+/// let token = CancelToken::with_deadline(Instant::now() + Duration::from_secs(600));
+/// let shutdown = token.clone();
+/// ctrlc::set_handler(move || shutdown.cancel());
+/// for doc in client.search("supernova").cancel_token(token).iter_docs() {
+///     println!("{:?}", doc?.title);
+/// }
+/// # Ok(())
+/// # }
+/// # mod ctrlc { pub fn set_handler<F: Fn()>(_: F) {} }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// Constructs a new token, which is only cancelled using
+    /// [`CancelToken::cancel`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a new token that is also cancelled once `deadline` has
+    /// passed.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Cancel the operations using this token, or any of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the token has been cancelled, or its deadline has
+    /// passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.check().is_err()
+    }
+
+    /// The error for a cancelled token, if it is.
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            Err(AdsError::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Err(AdsError::DeadlineExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sleep for `duration`, returning early with an error if the token is
+    /// cancelled in the meantime.
+    pub(crate) fn sleep(&self, duration: Duration) -> Result<()> {
+        let end = Instant::now() + duration;
+        loop {
+            self.check()?;
+            let now = Instant::now();
+            if now >= end {
+                return Ok(());
+            }
+            std::thread::sleep(POLL_INTERVAL.min(end - now));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_token() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(AdsError::Cancelled)));
+        assert!(matches!(
+            token.sleep(Duration::from_secs(60)),
+            Err(AdsError::Cancelled)
+        ));
+
+        let expired = CancelToken::with_deadline(Instant::now());
+        assert!(matches!(expired.check(), Err(AdsError::DeadlineExceeded)));
+        let later = CancelToken::with_deadline(Instant::now() + Duration::from_millis(150));
+        assert!(matches!(
+            later.sleep(Duration::from_secs(60)),
+            Err(AdsError::DeadlineExceeded)
+        ));
+    }
+}
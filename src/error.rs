@@ -1,3 +1,4 @@
+use crate::Timestamp;
 use std::io;
 use std::result;
 
@@ -16,15 +17,105 @@ pub enum AdsError {
     #[error("HTTP header error")]
     InvalidHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
 
+    #[error("HTTP header name error")]
+    InvalidHeaderName(#[from] reqwest::header::InvalidHeaderName),
+
     #[error("URL parse error")]
     Url(#[from] url::ParseError),
 
     #[error("JSON parse error")]
     Json(#[from] serde_json::Error),
 
-    #[error("")]
+    #[cfg(feature = "simd-json")]
+    #[error("JSON parse error")]
+    SimdJson(#[from] simd_json::Error),
+
+    #[error("{0}")]
     Ads(String),
 
+    #[error("{message}")]
+    Api {
+        /// The HTTP status code of the response.
+        status: u16,
+        /// The error message from the body of the response.
+        message: String,
+    },
+
+    #[error("invalid HTTP method '{0}'")]
+    InvalidMethod(String),
+
+    #[error("BibTeX parse error: {0}")]
+    Bibtex(String),
+
+    #[error("invalid sort '{0}'; expected a field name optionally followed by 'asc' or 'desc'")]
+    InvalidSort(String),
+
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error("invalid query template '{0}'")]
+    InvalidTemplate(String),
+
+    #[error("no value for the query template parameter '{0}'")]
+    MissingParameter(String),
+
     #[error("unable to load API token from environment variables or home directory")]
     Token,
+
+    #[cfg(feature = "replay")]
+    #[error("no recorded response at {0}")]
+    MissingFixture(std::path::PathBuf),
+
+    #[error("the request budget has been exhausted")]
+    BudgetExhausted,
+
+    #[error("the operation was cancelled")]
+    Cancelled,
+
+    #[error("the deadline for the operation has passed")]
+    DeadlineExceeded,
+
+    #[error("the API rate limit has been exceeded")]
+    RateLimited {
+        /// The time when the rate limit will be reset, if reported by the API.
+        reset: Option<Timestamp>,
+    },
+}
+
+/// The body of an error response from the API.
+#[derive(serde::Deserialize)]
+pub(crate) struct ErrorBody {
+    pub error: ApiError,
+}
+
+/// An error reported by the API, which is either `"message"` or
+/// `{"msg": "message", ...}`.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ApiError {
+    Message(String),
+    Detail { msg: String },
+}
+
+impl ApiError {
+    pub fn into_message(self) -> String {
+        match self {
+            ApiError::Message(msg) | ApiError::Detail { msg } => msg,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_error_messages() {
+        let body: ErrorBody = serde_json::from_str(r#"{"error": "Unauthorized"}"#).unwrap();
+        assert_eq!(body.error.into_message(), "Unauthorized");
+        let body: ErrorBody =
+            serde_json::from_str(r#"{"error": {"msg": "syntax error", "code": 400}}"#).unwrap();
+        assert_eq!(body.error.into_message(), "syntax error");
+        assert!(serde_json::from_str::<ErrorBody>(r#"{"response": {}}"#).is_err());
+    }
 }
@@ -0,0 +1,518 @@
+//! Exporting records in BibTeX and other citation formats.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn run() -> adsabs::Result<()> {
+//! use adsabs::{export::FormatType, Ads};
+//! let client = Ads::new("ADS_API_TOKEN")?;
+//! let bibtex = client
+//!     .export(FormatType::Bibtex, ["2013PASP..125..306F", "2019JOSS....4.1864F"])
+//!     .send()?;
+//! println!("{}", bibtex);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::bibtex::{parse_bibtex, BibFile, MergeReport};
+use crate::error::Result;
+use crate::search::{Query, Sort, MAX_ROWS};
+use crate::{Ads, RawResponse};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// The maximum number of records exported by a single request
+pub(crate) const MAX_EXPORT: usize = 2000;
+
+/// The formats supported by the export API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FormatType {
+    /// BibTeX, without abstracts.
+    Bibtex,
+    /// BibTeX, including abstracts.
+    BibtexAbs,
+    /// The ADS tagged format.
+    Ads,
+    /// EndNote.
+    Endnote,
+    /// ProCite.
+    Procite,
+    /// RIS.
+    Ris,
+    /// RefWorks.
+    Refworks,
+    /// An RSS feed.
+    Rss,
+    /// MEDLARS.
+    Medlars,
+    /// Dublin Core XML.
+    Dcxml,
+    /// The ADS reference XML format.
+    Refxml,
+    /// The ADS reference XML format, including abstracts.
+    Refabsxml,
+    /// The AASTeX reference list format.
+    Aastex,
+    /// The Icarus reference list format.
+    Icarus,
+    /// The MNRAS reference list format.
+    Mnras,
+    /// The Solar Physics reference list format.
+    Soph,
+    /// VOTable.
+    Votable,
+    /// The IEEE reference list format.
+    Ieee,
+    /// JATS XML.
+    Jatsxml,
+    /// A reference list using one of the citation styles supported by the
+    /// CSL export endpoint.
+    Csl(CslStyle, CslFormat),
+}
+
+/// The citation styles supported by [`FormatType::Csl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CslStyle {
+    /// The American Astronomical Society journals.
+    Aastex,
+    /// Icarus.
+    Icarus,
+    /// Monthly Notices of the Royal Astronomical Society.
+    Mnras,
+    /// Solar Physics.
+    Soph,
+    /// The Astronomical Society of the Pacific Conference Series.
+    Aspc,
+    /// The American Physical Society journals.
+    Apsj,
+    /// The American Astronomical Society journals, in the style used by
+    /// the journals themselves.
+    Aasj,
+    /// IEEE.
+    Ieee,
+    /// The American Geophysical Union journals.
+    Agu,
+    /// The American Meteorological Society journals.
+    Ams,
+    /// The Geological Society of America journals.
+    Gsa,
+}
+
+impl CslStyle {
+    fn name(self) -> &'static str {
+        match self {
+            CslStyle::Aastex => "aastex",
+            CslStyle::Icarus => "icarus",
+            CslStyle::Mnras => "mnras",
+            CslStyle::Soph => "soph",
+            CslStyle::Aspc => "aspc",
+            CslStyle::Apsj => "apsj",
+            CslStyle::Aasj => "aasj",
+            CslStyle::Ieee => "ieee",
+            CslStyle::Agu => "agu",
+            CslStyle::Ams => "ams",
+            CslStyle::Gsa => "gsa",
+        }
+    }
+}
+
+/// The markup used for references exported with [`FormatType::Csl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CslFormat {
+    /// Plain Unicode text.
+    Unicode,
+    /// HTML.
+    Html,
+    /// LaTeX.
+    Latex,
+}
+
+impl CslFormat {
+    fn code(self) -> u8 {
+        match self {
+            CslFormat::Unicode => 1,
+            CslFormat::Html => 2,
+            CslFormat::Latex => 3,
+        }
+    }
+}
+
+impl FormatType {
+    /// The name of the format used in the export API endpoint.
+    pub fn name(self) -> &'static str {
+        match self {
+            FormatType::Bibtex => "bibtex",
+            FormatType::BibtexAbs => "bibtexabs",
+            FormatType::Ads => "ads",
+            FormatType::Endnote => "endnote",
+            FormatType::Procite => "procite",
+            FormatType::Ris => "ris",
+            FormatType::Refworks => "refworks",
+            FormatType::Rss => "rss",
+            FormatType::Medlars => "medlars",
+            FormatType::Dcxml => "dcxml",
+            FormatType::Refxml => "refxml",
+            FormatType::Refabsxml => "refabsxml",
+            FormatType::Aastex => "aastex",
+            FormatType::Icarus => "icarus",
+            FormatType::Mnras => "mnras",
+            FormatType::Soph => "soph",
+            FormatType::Votable => "votable",
+            FormatType::Ieee => "ieee",
+            FormatType::Jatsxml => "jatsxml",
+            FormatType::Csl(..) => "csl",
+        }
+    }
+}
+
+/// A builder for an export API request.
+///
+/// This should generally be accessed via [`crate::Ads::export`].
+#[derive(Serialize, Clone, Debug)]
+#[must_use]
+pub struct Export<'ads> {
+    #[serde(skip)]
+    client: &'ads Ads,
+    #[serde(skip)]
+    format: FormatType,
+    bibcode: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sort: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style: Option<&'static str>,
+    #[serde(rename = "format", skip_serializing_if = "Option::is_none")]
+    csl_format: Option<u8>,
+}
+
+#[derive(Deserialize)]
+struct ExportResponse {
+    export: String,
+}
+
+impl<'ads> Export<'ads> {
+    /// Build a new export request for the records with the given bibcodes.
+    pub fn new<I, S>(client: &'ads Ads, format: FormatType, bibcodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let (style, csl_format) = match format {
+            FormatType::Csl(style, csl_format) => (Some(style.name()), Some(csl_format.code())),
+            _ => (None, None),
+        };
+        Self {
+            client,
+            format,
+            bibcode: bibcodes.into_iter().map(Into::into).collect(),
+            sort: Vec::new(),
+            style,
+            csl_format,
+        }
+    }
+
+    /// The order of the exported records; by default, this is the order used
+    /// by the API, which is `date desc, bibcode desc`.
+    pub fn sort<T: Into<Sort>>(mut self, sort: T) -> Self {
+        self.sort.push(sort.into().to_string());
+        self
+    }
+
+    /// Submit the export request, returning the exported text.
+    ///
+    /// # Errors
+    ///
+    /// This fails under the same conditions as [`crate::search::Query::send`].
+    pub fn send(&self) -> Result<String> {
+        let response: ExportResponse = self
+            .client
+            .post_json(format!("export/{}", self.format.name()), self)?;
+        Ok(response.export)
+    }
+
+    /// Submit the export request, returning the status code and body of the
+    /// response without parsing it, even if the request failed.
+    ///
+    /// # Errors
+    ///
+    /// This only fails if the request couldn't be made, or the body couldn't
+    /// be read.
+    pub fn send_raw(&self) -> Result<RawResponse> {
+        RawResponse::read(
+            self.client
+                .post(format!("export/{}", self.format.name()), self)?,
+        )
+    }
+}
+
+impl Ads {
+    /// Export the records with the given bibcodes; see [`Export`].
+    pub fn export<I, S>(&self, format: FormatType, bibcodes: I) -> Export<'_>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Export::new(self, format, bibcodes)
+    }
+}
+
+/// The progress of [`fetch_bibtex_for_query`], passed to its callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExportProgress {
+    /// The bibcodes of the matching records are being collected.
+    Searching {
+        /// The number of bibcodes collected so far.
+        found: u64,
+        /// The total number of matching records, once it is known.
+        total: Option<u64>,
+    },
+    /// The records are being exported.
+    Exporting {
+        /// The number of records exported so far.
+        exported: usize,
+        /// The total number of records to export.
+        total: usize,
+    },
+}
+
+/// Export all the records matching a query as BibTeX.
+///
+/// The bibcodes of the matching records are collected first, using as many
+/// pages of search results as needed, and the records are then exported in
+/// chunks of up to 2000, which is the limit for a single export request. The
+/// `progress` callback is called after every request, and the export stops
+/// between requests if the query has a [`crate::CancelToken`] that is
+/// cancelled.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::{export::fetch_bibtex_for_query, Ads};
+/// let client = Ads::new("ADS_API_TOKEN")?;
+/// let bibtex = fetch_bibtex_for_query(client.search("author:\"^Foreman-Mackey, D\""), |progress| {
+///     eprintln!("{:?}", progress);
+/// })?;
+/// println!("{}", bibtex);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This fails if any of the search or export requests fail.
+pub fn fetch_bibtex_for_query<F>(query: Query<'_>, mut progress: F) -> Result<String>
+where
+    F: FnMut(ExportProgress),
+{
+    let client = query.client.clone();
+    let cancel = query.cancel.clone();
+    let mut docs = query.fl("bibcode").rows(MAX_ROWS).iter_docs();
+    let mut bibcodes = Vec::new();
+    let mut found = 0;
+    while let Some(doc) = docs.next() {
+        if let Some(bibcode) = doc?.bibcode {
+            bibcodes.push(bibcode);
+        }
+        found += 1;
+        // Report once for each page of results
+        if found % MAX_ROWS == 0 {
+            progress(ExportProgress::Searching {
+                found,
+                total: docs.num_found(),
+            });
+        }
+    }
+    if found % MAX_ROWS != 0 {
+        progress(ExportProgress::Searching {
+            found,
+            total: docs.num_found(),
+        });
+    }
+
+    let mut bibtex = String::new();
+    let mut exported = 0;
+    for chunk in bibcodes.chunks(MAX_EXPORT) {
+        if let Some(cancel) = &cancel {
+            cancel.check()?;
+        }
+        let text = client.export(FormatType::Bibtex, chunk).send()?;
+        bibtex.push_str(&text);
+        if !text.is_empty() && !text.ends_with('\n') {
+            bibtex.push('\n');
+        }
+        exported += chunk.len();
+        progress(ExportProgress::Exporting {
+            exported,
+            total: bibcodes.len(),
+        });
+    }
+    Ok(bibtex)
+}
+
+/// Add the records matching a query to a BibTeX file, or update them if they
+/// are already there.
+///
+/// All the matching records are exported from ADS and merged into the file
+/// using [`BibFile::merge`], so entries that have been edited by hand keep
+/// their changes, and any other text in the file is left as it was. The file
+/// is created if it doesn't exist.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::{export::update_bib_file, Ads};
+/// let client = Ads::new("ADS_API_TOKEN")?;
+/// let report = update_bib_file(client.search("author:\"^Foreman-Mackey, D\""), "mine.bib")?;
+/// println!("{} added, {} updated", report.added.len(), report.updated.len());
+/// # Ok(())
+/// # }
+/// ```
+pub fn update_bib_file<P: AsRef<Path>>(query: Query<'_>, path: P) -> Result<MergeReport> {
+    let path = path.as_ref();
+    let mut file = match std::fs::read_to_string(path) {
+        Ok(text) => BibFile::parse(&text)?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => BibFile::default(),
+        Err(err) => return Err(err.into()),
+    };
+
+    let entries = parse_bibtex(&fetch_bibtex_for_query(query, |_| {})?)?;
+    let report = file.merge(entries);
+    if !report.added.is_empty() || !report.updated.is_empty() {
+        std::fs::write(path, file.to_string())?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn export_request() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/export/bibtex")
+                .json_body(serde_json::json!({"bibcode": ["a", "b"], "sort": ["date asc"]}));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"msg": "Retrieved 2 abstracts", "export": "@article{a}"}"#);
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let export = client
+            .export(FormatType::Bibtex, ["a", "b"])
+            .sort(Sort::asc("date"))
+            .send()
+            .unwrap();
+        mock.assert();
+        assert_eq!(export, "@article{a}");
+    }
+
+    #[test]
+    fn bibtex_for_query() {
+        let server = MockServer::start();
+        let search = server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 2, "start": 0, "docs": [{"bibcode": "a"}, {"bibcode": "b"}]}}"#);
+        });
+        let export = server.mock(|when, then| {
+            when.method(POST)
+                .path("/export/bibtex")
+                .json_body(serde_json::json!({"bibcode": ["a", "b"]}));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"export": "@article{a}\n\n@article{b}"}"#);
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let mut updates = Vec::new();
+        let bibtex =
+            fetch_bibtex_for_query(client.search("star"), |progress| updates.push(progress))
+                .unwrap();
+        search.assert_hits(1);
+        export.assert_hits(1);
+        assert_eq!(bibtex, "@article{a}\n\n@article{b}\n");
+        assert_eq!(
+            updates,
+            [
+                ExportProgress::Searching {
+                    found: 2,
+                    total: Some(2)
+                },
+                ExportProgress::Exporting {
+                    exported: 2,
+                    total: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn csl_request() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/export/csl")
+                .json_body(serde_json::json!({"bibcode": ["a"], "style": "agu", "format": 3}));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"export": "\\bibitem[a]{a}"}"#);
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let export = client
+            .export(FormatType::Csl(CslStyle::Agu, CslFormat::Latex), ["a"])
+            .send()
+            .unwrap();
+        mock.assert();
+        assert_eq!(export, "\\bibitem[a]{a}");
+    }
+
+    #[test]
+    fn update_file() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 2, "start": 0, "docs": [{"bibcode": "a"}, {"bibcode": "b"}]}}"#);
+        });
+        server.mock(|when, then| {
+            when.method(POST).path("/export/bibtex");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({
+                    "export": "@article{a, journal = {ApJ}}\n\n@article{b, title = {B}}\n"
+                }));
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("adsabs-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("refs.bib");
+        std::fs::write(&path, "@article{a, journal = {ApJ}, note = {mine}}\n").unwrap();
+
+        let report = update_bib_file(client.search("star"), &path).unwrap();
+        assert_eq!(report.added, ["b"]);
+        assert_eq!(report.unchanged, ["a"]);
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("@article{a, journal = {ApJ}, note = {mine}}\n\n@article{b,"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,400 @@
+//! Building citation graphs from a set of seed records.
+//!
+//! # Examples
+//!
+//! To find the records cited by a paper, and the records cited by those:
+//!
+//! ```no_run
+//! # fn run() -> adsabs::Result<()> {
+//! use adsabs::{graph::GraphBuilder, Ads};
+//! let client = Ads::new("ADS_API_TOKEN")?;
+//! let graph = GraphBuilder::new(&client, ["2013PASP..125..306F"])
+//!     .depth(2)
+//!     .build()?;
+//! println!("{} records, {} citations", graph.len(), graph.edge_count());
+//! for (citing, cited) in graph.edges() {
+//!     println!("{} -> {}", citing, cited);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Result;
+use crate::Ads;
+use std::collections::{BTreeMap, BTreeSet};
+
+// The number of records requested in a single search query, by default
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// The links that are followed when building a [`CitationGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Follow the `reference` field, to the records cited by each record.
+    References,
+    /// Follow the `citation` field, to the records that cite each record.
+    Citations,
+    /// Follow both references and citations.
+    Both,
+}
+
+impl Direction {
+    fn fields(self) -> &'static str {
+        match self {
+            Direction::References => "bibcode,reference",
+            Direction::Citations => "bibcode,citation",
+            Direction::Both => "bibcode,reference,citation",
+        }
+    }
+}
+
+/// A builder for a [`CitationGraph`], starting from a set of seed records.
+#[derive(Debug)]
+#[must_use]
+pub struct GraphBuilder<'ads> {
+    client: &'ads Ads,
+    seeds: Vec<String>,
+    depth: usize,
+    direction: Direction,
+    batch_size: usize,
+}
+
+impl<'ads> GraphBuilder<'ads> {
+    /// Start building a graph from the records with the given bibcodes.
+    pub fn new<I, S>(client: &'ads Ads, seeds: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            client,
+            seeds: seeds.into_iter().map(Into::into).collect(),
+            depth: 1,
+            direction: Direction::References,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// The number of links to follow away from the seed records.
+    ///
+    /// The default is `1`, which includes only the records directly linked to
+    /// the seeds. A depth of `0` produces a graph without any edges.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Which links to follow; the default is [`Direction::References`].
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// The number of records fetched by each search query.
+    ///
+    /// The default is `100`, and values smaller than `1` are treated as `1`.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Fetch the records and build the graph.
+    ///
+    /// The records at each level are fetched together, in batches, so the
+    /// number of API requests grows with the number of records rather than the
+    /// number of edges.
+    ///
+    /// # Errors
+    ///
+    /// This fails if any of the search queries fail.
+    pub fn build(self) -> Result<CitationGraph> {
+        let mut graph = CitationGraph::default();
+        let mut frontier = BTreeSet::new();
+        for seed in self.seeds {
+            graph.add_node(&seed);
+            frontier.insert(seed);
+        }
+
+        let mut visited = BTreeSet::new();
+        for _ in 0..self.depth {
+            let pending = frontier
+                .into_iter()
+                .filter(|bibcode| visited.insert(bibcode.clone()))
+                .collect::<Vec<_>>();
+            let mut next = BTreeSet::new();
+            for chunk in pending.chunks(self.batch_size) {
+                let query = chunk
+                    .iter()
+                    .map(|bibcode| format!("\"{}\"", bibcode))
+                    .collect::<Vec<_>>()
+                    .join(" OR ");
+                let docs = self
+                    .client
+                    .search(&format!("bibcode:({})", query))
+                    .fl(self.direction.fields())
+                    .iter_docs()
+                    .limit(chunk.len() as u64);
+                for doc in docs {
+                    let doc = doc?;
+                    let bibcode = match doc.bibcode {
+                        Some(bibcode) => bibcode,
+                        None => continue,
+                    };
+                    for cited in doc.reference.unwrap_or_default() {
+                        graph.add_edge(&bibcode, &cited);
+                        next.insert(cited);
+                    }
+                    for citing in doc.citation.unwrap_or_default() {
+                        graph.add_edge(&citing, &bibcode);
+                        next.insert(citing);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        Ok(graph)
+    }
+}
+
+/// A directed graph of records, with an edge from each record to the records
+/// that it cites.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CitationGraph {
+    references: BTreeMap<String, BTreeSet<String>>,
+    citations: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl CitationGraph {
+    /// The number of records in the graph.
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    /// Returns `true` if the graph has no records.
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    /// The number of citations in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.references.values().map(BTreeSet::len).sum()
+    }
+
+    /// Returns `true` if the record with this bibcode is in the graph.
+    pub fn contains(&self, bibcode: &str) -> bool {
+        self.references.contains_key(bibcode)
+    }
+
+    /// The bibcodes of all the records in the graph, in sorted order.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.references.keys().map(String::as_str)
+    }
+
+    /// All the citations in the graph, as `(citing, cited)` pairs.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.references.iter().flat_map(|(citing, cited)| {
+            cited
+                .iter()
+                .map(move |cited| (citing.as_str(), cited.as_str()))
+        })
+    }
+
+    /// The records in the graph that are cited by `bibcode`.
+    pub fn references(&self, bibcode: &str) -> impl Iterator<Item = &str> {
+        self.references
+            .get(bibcode)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// The records in the graph that cite `bibcode`.
+    pub fn citations(&self, bibcode: &str) -> impl Iterator<Item = &str> {
+        self.citations
+            .get(bibcode)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Add a record to the graph, if it isn't already present.
+    pub fn add_node(&mut self, bibcode: &str) {
+        if !self.contains(bibcode) {
+            self.references.insert(bibcode.to_owned(), BTreeSet::new());
+            self.citations.insert(bibcode.to_owned(), BTreeSet::new());
+        }
+    }
+
+    /// Add a citation from `citing` to `cited`, adding the records as needed.
+    pub fn add_edge(&mut self, citing: &str, cited: &str) {
+        self.add_node(citing);
+        self.add_node(cited);
+        if let Some(references) = self.references.get_mut(citing) {
+            references.insert(cited.to_owned());
+        }
+        if let Some(citations) = self.citations.get_mut(cited) {
+            citations.insert(citing.to_owned());
+        }
+    }
+
+    /// Render the graph in the Graphviz DOT language, e.g. to draw it with
+    /// `dot -Tsvg`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use adsabs::graph::CitationGraph;
+    /// let mut graph = CitationGraph::default();
+    /// graph.add_edge("2019JOSS....4.1864F", "2013PASP..125..306F");
+    /// let dot = graph.to_dot();
+    /// assert!(dot.starts_with("digraph citations {"));
+    /// assert!(dot.contains("\"2019JOSS....4.1864F\" -> \"2013PASP..125..306F\";"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph citations {\n");
+        for node in self.nodes() {
+            dot.push_str(&format!("  {};\n", dot_id(node)));
+        }
+        for (citing, cited) in self.edges() {
+            dot.push_str(&format!("  {} -> {};\n", dot_id(citing), dot_id(cited)));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render the graph as GraphML, which can be opened by network analysis
+    /// tools like Gephi and Cytoscape.
+    ///
+    /// Each node has the bibcode as its `id`, and also as a `label`
+    /// attribute, since some tools don't display the node IDs.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <graph id=\"citations\" edgedefault=\"directed\">\n",
+        ));
+        for node in self.nodes() {
+            let node = xml_escape(node);
+            xml.push_str(&format!(
+                "    <node id=\"{0}\"><data key=\"label\">{0}</data></node>\n",
+                node
+            ));
+        }
+        for (citing, cited) in self.edges() {
+            xml.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\"/>\n",
+                xml_escape(citing),
+                xml_escape(cited)
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+/// Quote `id` as a DOT identifier, like the node names written by
+/// [`CitationGraph::to_dot`].
+pub fn dot_id(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Escape `text` for use in XML attributes and content, like the node ids
+/// written by [`CitationGraph::to_graphml`].
+pub fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn add_edges() {
+        let mut graph = CitationGraph::default();
+        graph.add_edge("a", "b");
+        graph.add_edge("a", "c");
+        graph.add_edge("b", "c");
+        graph.add_edge("b", "c");
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.references("a").collect::<Vec<_>>(), ["b", "c"]);
+        assert_eq!(graph.citations("c").collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(graph.references("missing").count(), 0);
+    }
+
+    #[test]
+    fn export_formats() {
+        let mut graph = CitationGraph::default();
+        graph.add_edge("2020A&A...1", "x\"y");
+        assert_eq!(
+            graph.to_dot(),
+            "digraph citations {\n  \"2020A&A...1\";\n  \"x\\\"y\";\n  \"2020A&A...1\" -> \"x\\\"y\";\n}\n"
+        );
+        let xml = graph.to_graphml();
+        assert!(xml.contains(
+            "<node id=\"2020A&amp;A...1\"><data key=\"label\">2020A&amp;A...1</data></node>"
+        ));
+        assert!(xml.contains("<edge source=\"2020A&amp;A...1\" target=\"x&quot;y\"/>"));
+        assert!(xml.ends_with("</graphml>\n"));
+    }
+
+    #[test]
+    fn escape_helpers() {
+        assert_eq!(dot_id("a\\\"b"), "\"a\\\\\\\"b\"");
+        assert_eq!(xml_escape("O'Neil & <co>"), "O&apos;Neil &amp; &lt;co&gt;");
+    }
+
+    #[test]
+    fn build_by_level() {
+        let server = MockServer::start();
+        let first = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("q", "bibcode:(\"A\")")
+                .query_param("fl", "bibcode,reference");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({"response": {
+                    "numFound": 1,
+                    "start": 0,
+                    "docs": [{"bibcode": "A", "reference": ["B", "C"]}],
+                }}));
+        });
+        let second = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("q", "bibcode:(\"B\" OR \"C\")");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({"response": {
+                    "numFound": 2,
+                    "start": 0,
+                    "docs": [{"bibcode": "B", "reference": ["C", "D"]}, {"bibcode": "C"}],
+                }}));
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let graph = GraphBuilder::new(&client, ["A"]).depth(2).build().unwrap();
+        first.assert();
+        second.assert();
+        assert_eq!(graph.nodes().collect::<Vec<_>>(), ["A", "B", "C", "D"]);
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            [("A", "B"), ("A", "C"), ("B", "C"), ("B", "D")]
+        );
+    }
+}
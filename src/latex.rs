@@ -0,0 +1,386 @@
+//! Converting between LaTeX markup and Unicode text.
+//!
+//! Titles and abstracts from ADS often include LaTeX markup, like math mode
+//! and accents, while BibTeX files need special characters to be escaped.
+//! [`to_unicode`] converts the common cases of the former to plain text, and
+//! [`escape`] goes the other way.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use adsabs::latex;
+//! assert_eq!(
+//!     latex::to_unicode(r"Planets around M dwarfs at $z\sim 0.5$ with H$_2$O by Erd{\'e}lyi"),
+//!     "Planets around M dwarfs at z∼0.5 with H₂O by Erdélyi"
+//! );
+//! assert_eq!(latex::escape("Erdélyi & 100% Å"), r"Erd{\'e}lyi \& 100\% {\AA}");
+//! ```
+
+// Precomposed characters for each accent command, as the base letters and
+// the matching accented letters
+const ACCENTS: &[(&str, &str, &str)] = &[
+    ("'", "aeiouyAEIOUYcnszCNSZ", "áéíóúýÁÉÍÓÚÝćńśźĆŃŚŹ"),
+    ("`", "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ("^", "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ("\"", "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+    ("~", "anoANO", "ãñõÃÑÕ"),
+    ("=", "aeiouAEIOU", "āēīōūĀĒĪŌŪ"),
+    (".", "zZI", "żŻİ"),
+    ("c", "cCsS", "çÇşŞ"),
+    ("v", "cCsSzZrReEnN", "čČšŠžŽřŘěĚňŇ"),
+    ("u", "aAgG", "ăĂğĞ"),
+    ("H", "oOuU", "őŐűŰ"),
+    ("r", "aAuU", "åÅůŮ"),
+    ("k", "aAeE", "ąĄęĘ"),
+];
+
+// Combining characters, used for accents without a precomposed character
+const COMBINING: &[(&str, char)] = &[
+    ("'", '\u{301}'),
+    ("`", '\u{300}'),
+    ("^", '\u{302}'),
+    ("\"", '\u{308}'),
+    ("~", '\u{303}'),
+    ("=", '\u{304}'),
+    (".", '\u{307}'),
+    ("c", '\u{327}'),
+    ("v", '\u{30c}'),
+    ("u", '\u{306}'),
+    ("H", '\u{30b}'),
+    ("r", '\u{30a}'),
+    ("k", '\u{328}'),
+    ("d", '\u{323}'),
+    ("b", '\u{331}'),
+    ("t", '\u{361}'),
+];
+
+// Commands for letters that aren't accented versions of ASCII letters
+const LETTERS: &[(&str, char)] = &[
+    ("ss", 'ß'),
+    ("o", 'ø'),
+    ("O", 'Ø'),
+    ("ae", 'æ'),
+    ("AE", 'Æ'),
+    ("oe", 'œ'),
+    ("OE", 'Œ'),
+    ("aa", 'å'),
+    ("AA", 'Å'),
+    ("l", 'ł'),
+    ("L", 'Ł'),
+    ("i", 'ı'),
+    ("j", 'ȷ'),
+];
+
+// Commands for symbols, which are mostly used in math mode
+const SYMBOLS: &[(&str, char)] = &[
+    ("alpha", 'α'),
+    ("beta", 'β'),
+    ("gamma", 'γ'),
+    ("delta", 'δ'),
+    ("epsilon", 'ϵ'),
+    ("varepsilon", 'ε'),
+    ("zeta", 'ζ'),
+    ("eta", 'η'),
+    ("theta", 'θ'),
+    ("iota", 'ι'),
+    ("kappa", 'κ'),
+    ("lambda", 'λ'),
+    ("mu", 'μ'),
+    ("nu", 'ν'),
+    ("xi", 'ξ'),
+    ("pi", 'π'),
+    ("rho", 'ρ'),
+    ("sigma", 'σ'),
+    ("tau", 'τ'),
+    ("upsilon", 'υ'),
+    ("phi", 'ϕ'),
+    ("varphi", 'φ'),
+    ("chi", 'χ'),
+    ("psi", 'ψ'),
+    ("omega", 'ω'),
+    ("Gamma", 'Γ'),
+    ("Delta", 'Δ'),
+    ("Theta", 'Θ'),
+    ("Lambda", 'Λ'),
+    ("Xi", 'Ξ'),
+    ("Pi", 'Π'),
+    ("Sigma", 'Σ'),
+    ("Phi", 'Φ'),
+    ("Psi", 'Ψ'),
+    ("Omega", 'Ω'),
+    ("sim", '∼'),
+    ("approx", '≈'),
+    ("simeq", '≃'),
+    ("lesssim", '≲'),
+    ("gtrsim", '≳'),
+    ("la", '≲'),
+    ("ga", '≳'),
+    ("le", '≤'),
+    ("leq", '≤'),
+    ("ge", '≥'),
+    ("geq", '≥'),
+    ("ll", '≪'),
+    ("gg", '≫'),
+    ("neq", '≠'),
+    ("propto", '∝'),
+    ("times", '×'),
+    ("pm", '±'),
+    ("mp", '∓'),
+    ("cdot", '·'),
+    ("odot", '⊙'),
+    ("sun", '☉'),
+    ("oplus", '⊕'),
+    ("earth", '⊕'),
+    ("infty", '∞'),
+    ("prime", '′'),
+    ("circ", '°'),
+    ("degree", '°'),
+    ("ell", 'ℓ'),
+    ("to", '→'),
+    ("rightarrow", '→'),
+    ("leftarrow", '←'),
+    ("partial", '∂'),
+    ("nabla", '∇'),
+    ("langle", '⟨'),
+    ("rangle", '⟩'),
+];
+
+const SUPERSCRIPTS: &str = "0⁰1¹2²3³4⁴5⁵6⁶7⁷8⁸9⁹+⁺-⁻=⁼(⁽)⁾nⁿiⁱ";
+const SUBSCRIPTS: &str = "0₀1₁2₂3₃4₄5₅6₆7₇8₈9₉+₊-₋=₌(₍)₎";
+
+/// Convert LaTeX markup to Unicode text.
+///
+/// Accents, special letters like `\ss`, Greek letters and common math
+/// symbols, escaped characters, dashes, and simple superscripts and
+/// subscripts are converted. Formatting commands like `\textit` and braces
+/// are removed, keeping their contents, as are any other unknown commands.
+pub fn to_unicode(latex: &str) -> String {
+    let mut parser = Parser {
+        chars: latex.chars().collect(),
+        pos: 0,
+    };
+    parser.parse(None, false)
+}
+
+/// Escape text for use in a BibTeX field.
+///
+/// The characters that are special in LaTeX are escaped, and accented
+/// letters and the letters that have LaTeX commands are written using those
+/// commands, wrapped in braces so that BibTeX sorts and capitalizes them
+/// correctly. Any other non-ASCII characters are left as they are.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            c if c.is_ascii() => escaped.push(c),
+            c => {
+                if let Some((name, _)) = LETTERS.iter().find(|(_, letter)| *letter == c) {
+                    escaped.push_str(&format!("{{\\{}}}", name));
+                } else if let Some((accent, base)) = decompose(c) {
+                    if accent.chars().all(char::is_alphabetic) {
+                        escaped.push_str(&format!("{{\\{}{{{}}}}}", accent, base));
+                    } else {
+                        escaped.push_str(&format!("{{\\{}{}}}", accent, base));
+                    }
+                } else {
+                    escaped.push(c);
+                }
+            }
+        }
+    }
+    escaped
+}
+
+fn compose(accent: &str, base: char) -> Option<char> {
+    let base = if base == 'ı' { 'i' } else { base };
+    let (_, bases, composed) = ACCENTS.iter().find(|(name, _, _)| *name == accent)?;
+    let index = bases.chars().position(|c| c == base)?;
+    composed.chars().nth(index)
+}
+
+fn decompose(c: char) -> Option<(&'static str, char)> {
+    ACCENTS.iter().find_map(|(accent, bases, composed)| {
+        let index = composed.chars().position(|x| x == c)?;
+        Some((*accent, bases.chars().nth(index)?))
+    })
+}
+
+fn script(table: &str, text: &str) -> Option<String> {
+    let table = table.chars().collect::<Vec<_>>();
+    text.chars()
+        .map(|c| {
+            table
+                .chunks(2)
+                .find(|pair| pair[0] == c)
+                .map(|pair| pair[1])
+        })
+        .collect()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    // Convert the input up to the closing delimiter `until`, if any
+    fn parse(&mut self, until: Option<char>, math: bool) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.next() {
+            match c {
+                c if Some(c) == until => return text,
+                '{' => text.push_str(&self.parse(Some('}'), math)),
+                '$' => text.push_str(&self.parse(Some('$'), true)),
+                '\\' => text.push_str(&self.command(math)),
+                '^' | '_' if math => {
+                    let argument = self.argument(math);
+                    let table = if c == '^' { SUPERSCRIPTS } else { SUBSCRIPTS };
+                    match script(table, &argument) {
+                        Some(converted) => text.push_str(&converted),
+                        // Symbols like `\odot` are written inline
+                        None if !argument.chars().any(|c| c.is_ascii_alphanumeric()) => {
+                            text.push_str(&argument)
+                        }
+                        None => {
+                            text.push(c);
+                            text.push_str(&argument);
+                        }
+                    }
+                }
+                '~' => text.push(' '),
+                '-' if !math && self.peek() == Some('-') => {
+                    self.pos += 1;
+                    if self.peek() == Some('-') {
+                        self.pos += 1;
+                        text.push('—');
+                    } else {
+                        text.push('–');
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+        text
+    }
+
+    // A command argument, which is either a group or a single character or
+    // command
+    fn argument(&mut self, math: bool) -> String {
+        match self.next() {
+            Some('{') => self.parse(Some('}'), math),
+            Some('\\') => self.command(math),
+            Some(c) => c.to_string(),
+            None => String::new(),
+        }
+    }
+
+    // Convert a command, after the backslash
+    fn command(&mut self, math: bool) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(c);
+            self.pos += 1;
+        }
+        if name.is_empty() {
+            match self.next() {
+                Some(c) => name.push(c),
+                None => return String::new(),
+            }
+        } else {
+            self.skip_spaces();
+        }
+
+        if COMBINING.iter().any(|(accent, _)| *accent == name) {
+            let argument = self.argument(math);
+            let mut chars = argument.chars();
+            let base = match chars.next() {
+                Some(base) => base,
+                None => return String::new(),
+            };
+            let mut text = String::new();
+            match compose(&name, base) {
+                Some(composed) => text.push(composed),
+                None => {
+                    text.push(base);
+                    text.extend(
+                        COMBINING
+                            .iter()
+                            .filter(|(accent, _)| *accent == name)
+                            .map(|(_, mark)| *mark),
+                    );
+                }
+            }
+            text.extend(chars);
+            return text;
+        }
+        if let Some((_, c)) = LETTERS.iter().chain(SYMBOLS).find(|(n, _)| *n == name) {
+            return c.to_string();
+        }
+        match name.as_str() {
+            "&" | "%" | "$" | "#" | "_" | "{" | "}" => name,
+            "\\" | " " | "," | ";" | ":" => " ".to_owned(),
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latex_to_unicode() {
+        assert_eq!(
+            to_unicode(r#"Sch{\"o}nberg \& Gr\"{u}n"#),
+            "Schönberg & Grün"
+        );
+        assert_eq!(
+            to_unicode(r#"\v{S}ubr and Mu\~noz and {\AA}ngstr\"om"#),
+            "Šubr and Muñoz and Ångström"
+        );
+        assert_eq!(
+            to_unicode(r"Garc\'{\i}a, \c{C}elik, K\ss ler"),
+            "García, Çelik, Kßler"
+        );
+        assert_eq!(to_unicode(r"\'{x}"), "x\u{301}");
+        assert_eq!(to_unicode(r"\textit{Kepler}--K2---TESS"), "Kepler–K2—TESS");
+        assert_eq!(
+            to_unicode(r"$10^{12}$ M$_\odot$ at $\alpha\approx 3^{\rm rd}$"),
+            "10¹² M⊙ at α≈3^rd"
+        );
+        assert_eq!(to_unicode("unclosed {brace $x"), "unclosed brace x");
+    }
+
+    #[test]
+    fn escape_for_bibtex() {
+        assert_eq!(
+            escape(r"50% of $ & a_b {c} \ ~ ^"),
+            r"50\% of \$ \& a\_b \{c\} \textbackslash{} \textasciitilde{} \textasciicircum{}"
+        );
+        assert_eq!(escape("Šubr Straße ø α"), r"{\v{S}}ubr Stra{\ss}e {\o} α");
+        assert_eq!(to_unicode(&escape("Garçía Ångström")), "Garçía Ångström");
+    }
+}
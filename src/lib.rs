@@ -12,8 +12,8 @@
 //! ```
 //!
 //! For now, only the `/search` endpoint is supported, as described below. Other
-//! endpoints could be manually accessed using [`Ads::get`] directly, and pull
-//! requests would be welcome!
+//! endpoints could be manually accessed using [`Ads::get_json`] and
+//! [`Ads::post_json`] directly, and pull requests would be welcome!
 //!
 //! ## Examples
 //!
@@ -89,30 +89,69 @@
 //!
 //! 1. The `ADS_API_TOKEN` environment variable,
 //! 2. The `ADS_DEV_KEY` environment variable,
-//! 3. The contents of the `~/.ads/token` file, and
-//! 4. The contents of the `~/.ads/dev_key` file.
+//! 3. The contents of the file named by the `ADS_TOKEN_FILE` environment
+//!    variable,
+//! 4. The contents of `adsabs/token` in `$XDG_CONFIG_HOME`, or the platform's
+//!    configuration directory (e.g. `~/.config` on Linux, `~/Library/Application
+//!    Support` on macOS, and `%APPDATA%` on Windows),
+//! 5. The contents of the `~/.ads/token` file, and
+//! 6. The contents of the `~/.ads/dev_key` file.
 //!
-//! Where these were chosen to be compatible with the locations supported by the
-//! Python client `ads`.
+//! Where the environment variables and the `~/.ads` files were chosen to be
+//! compatible with the locations supported by the Python client `ads`.
 //!
 //! [ADS settings page]: https://ui.adsabs.harvard.edu/user/settings/token
 
 mod auth;
+pub mod batch;
+pub mod bibtex;
+mod budget;
+mod cancel;
 mod error;
+pub mod export;
+pub mod graph;
+pub mod latex;
+pub mod metrics;
+mod ping;
+mod rate_limit;
+mod raw;
+#[cfg(feature = "replay")]
+pub mod replay;
 pub mod search;
+pub mod sync;
+mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod timestamp;
+mod transport;
+pub use cancel::CancelToken;
 pub use error::{AdsError, Result};
+pub use ping::Ping;
+pub use rate_limit::RateLimit;
+pub use raw::RawResponse;
+pub use telemetry::RequestMetrics;
+pub use timestamp::Timestamp;
 
 use reqwest::{
     blocking::{Client, Response},
     header,
 };
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
 
+/// The most commonly used types, including everything needed to run a search
+/// and work with its results.
 pub mod prelude {
-    pub use crate::{search::Sort, Ads, AdsError};
+    pub use crate::search::{Document, Query, QueryParams, Response, Sort};
+    pub use crate::{Ads, AdsError};
 }
 
 const API_BASE_URL: &str = "https://api.adsabs.harvard.edu/v1/";
 
+// The delay before the first retry of a failed request, by default
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
 /// An interface to the NASA ADS API.
 ///
 /// This has various configuration values to tweak, but the most important one
@@ -120,6 +159,9 @@ const API_BASE_URL: &str = "https://api.adsabs.harvard.edu/v1/";
 /// available on your [ADS settings page]. To configure your `Ads` interface,
 /// use [`Ads::builder`].
 ///
+/// Clones of an `Ads` interface share the same connection pool, and it can be
+/// shared between threads.
+///
 /// [ADS settings page]: https://ui.adsabs.harvard.edu/user/settings/token
 ///
 /// # Examples
@@ -135,7 +177,16 @@ const API_BASE_URL: &str = "https://api.adsabs.harvard.edu/v1/";
 #[derive(Clone)]
 pub struct Ads {
     base_url: reqwest::Url,
-    client: std::rc::Rc<Client>,
+    client: Arc<Client>,
+    tokens: Arc<auth::Tokens>,
+    rate_limit: Arc<Mutex<Option<RateLimit>>>,
+    on_request: Option<telemetry::Callback>,
+    transport: Option<Arc<dyn transport::Transport>>,
+    default_fl: Vec<String>,
+    default_sort: Vec<search::Sort>,
+    retries: u32,
+    retry_delay: Duration,
+    budget: Option<Arc<budget::DailyBudget>>,
 }
 
 /// A builder that can be used to create an [`Ads`] interface with custom
@@ -156,8 +207,22 @@ pub struct Ads {
 #[must_use]
 pub struct AdsBuilder {
     base_url: String,
-    token: String,
+    tokens: Vec<String>,
     user_agent: String,
+    on_request: Option<telemetry::Callback>,
+    transport: Option<Arc<dyn transport::Transport>>,
+    default_fl: Vec<String>,
+    default_sort: Vec<search::Sort>,
+    timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    // `None` uses the `reqwest` default, and `Some(None)` never expires
+    pool_idle_timeout: Option<Option<Duration>>,
+    pool_max_idle_per_host: Option<usize>,
+    http2_prior_knowledge: bool,
+    http2_adaptive_window: bool,
+    retries: u32,
+    retry_delay: Duration,
+    daily_budget: Option<u64>,
 }
 
 impl AdsBuilder {
@@ -167,23 +232,40 @@ impl AdsBuilder {
     pub fn new(token: &str) -> Self {
         Self {
             base_url: API_BASE_URL.to_owned(),
-            token: token.to_owned(),
+            tokens: vec![token.to_owned()],
             user_agent: format!("adsabs-rs/{}", env!("CARGO_PKG_VERSION")),
+            on_request: None,
+            transport: None,
+            default_fl: Vec::new(),
+            default_sort: Vec::new(),
+            timeout: None,
+            tcp_keepalive: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            http2_prior_knowledge: false,
+            http2_adaptive_window: false,
+            retries: 0,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            daily_budget: None,
         }
     }
 
     /// Constructs a new `AdsBuilder`, loading the API token from either
-    /// environment valiables or the user's home directory.
+    /// environment variables or a token file.
     ///
     /// The following locations are checked, in the listed order:
     ///
     /// 1. The `ADS_API_TOKEN` environment variable,
     /// 2. The `ADS_DEV_KEY` environment variable,
-    /// 3. The contents of the `~/.ads/token` file, and
-    /// 4. The contents of the `~/.ads/dev_key` file.
+    /// 3. The contents of the file named by the `ADS_TOKEN_FILE` environment
+    ///    variable,
+    /// 4. The contents of `adsabs/token` in `$XDG_CONFIG_HOME`, or the
+    ///    platform's configuration directory,
+    /// 5. The contents of the `~/.ads/token` file, and
+    /// 6. The contents of the `~/.ads/dev_key` file.
     ///
-    /// These were chosen to be compatible with the locations supported by the
-    /// Python client `ads`.
+    /// The environment variables and the `~/.ads` files were chosen to be
+    /// compatible with the locations supported by the Python client `ads`.
     ///
     /// # Errors
     ///
@@ -194,6 +276,10 @@ impl AdsBuilder {
     }
 
     /// Sets the base API URL to be used by this client.
+    ///
+    /// The URL can include a path prefix, e.g. for a proxy, and a trailing
+    /// slash is added if it is missing, so `https://proxy.example.com/ads/v1`
+    /// and `https://proxy.example.com/ads/v1/` are equivalent.
     pub fn base_url(mut self, url: &str) -> Self {
         self.base_url = url.to_owned();
         self
@@ -201,7 +287,37 @@ impl AdsBuilder {
 
     /// Sets the API token to be used by this client.
     pub fn token(mut self, token: &str) -> Self {
-        self.token = token.to_owned();
+        self.tokens = vec![token.to_owned()];
+        self
+    }
+
+    /// Sets a list of API tokens to be used by this client, in order.
+    ///
+    /// Requests use the first token until the API reports that its rate limit
+    /// has been exceeded, and then they are retried with the next token, and
+    /// so on. This should only be used for harvests that have been approved
+    /// by the ADS team, since the usual rate limits apply to each account.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::builder("FIRST_TOKEN")
+    ///     .tokens(["FIRST_TOKEN", "SECOND_TOKEN"])
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tokens<I, S>(mut self, tokens: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.tokens = tokens
+            .into_iter()
+            .map(|token| token.as_ref().to_owned())
+            .collect();
         self
     }
 
@@ -211,28 +327,253 @@ impl AdsBuilder {
         self
     }
 
+    /// Sets the fields returned by every search, unless a query selects its
+    /// own using [`search::Query::fl`].
+    ///
+    /// The value should be a comma separated list of field names, and this can
+    /// be called more than once to add more fields.
+    pub fn default_fl(mut self, fl: &str) -> Self {
+        search::push_fields(&mut self.default_fl, fl);
+        self
+    }
+
+    /// Sets the sort used by every search, unless a query specifies its own
+    /// using [`search::Query::sort`].
+    ///
+    /// This accepts the same values as [`search::Query::sort`], e.g.
+    /// `"date desc"` or `Sort::asc("date")`, and can be called more than once
+    /// to sort on more fields.
+    pub fn default_sort<T: Into<search::Sort>>(mut self, sort: T) -> Self {
+        self.default_sort.push(sort.into());
+        self
+    }
+
+    /// Sets a callback that is called with [`RequestMetrics`] after every
+    /// request made by this client.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::builder("ADS_API_TOKEN")
+    ///     .on_request(|metrics| {
+    ///         eprintln!("{} took {:?}", metrics.endpoint, metrics.latency);
+    ///     })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_request<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RequestMetrics) + Send + Sync + 'static,
+    {
+        self.on_request = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets the timeout for each request, from connecting until the response
+    /// body has been read; the default is 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sends TCP keep-alive probes on idle connections at this interval, so
+    /// that connections kept in the pool between requests aren't dropped by
+    /// firewalls or proxies; they are disabled by default.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Sets how long an idle connection is kept in the pool for reuse by a
+    /// later request, or `None` to keep them indefinitely; the default is 90
+    /// seconds.
+    ///
+    /// Keeping connections for longer avoids a new TLS handshake when there
+    /// are long gaps between requests, e.g. while waiting for the rate limit
+    /// or processing a page of results.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections to each host that are kept
+    /// in the pool; there is no limit by default.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Uses HTTP/2 for every request, without negotiating it first.
+    ///
+    /// This is only useful for servers that are known to support HTTP/2 over
+    /// plain HTTP, like some proxies; over HTTPS, HTTP/2 is negotiated
+    /// automatically when the `http2` feature is enabled.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Sets whether HTTP/2 connections adjust their flow control windows to
+    /// the measured bandwidth, which can speed up downloads of large pages of
+    /// results; this is disabled by default.
+    pub fn http2_adaptive_window(mut self, enabled: bool) -> Self {
+        self.http2_adaptive_window = enabled;
+        self
+    }
+
+    /// Sets the number of times that a request is retried after a transient
+    /// failure, like a timeout, a connection error, or a 502, 503, or 504
+    /// response from the server. The default is `0`, for no retries.
+    ///
+    /// Requests are only retried if their body can be sent again, which is
+    /// the case for all the requests made by this crate.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// use std::time::Duration;
+    /// let client = Ads::builder("ADS_API_TOKEN")
+    ///     .timeout(Duration::from_secs(10))
+    ///     .retries(3)
+    ///     .retry_delay(Duration::from_millis(500))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the delay before the first retry of a failed request, which is
+    /// doubled before each further retry; the default is one second.
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Limits the number of requests that this client (and its clones) can
+    /// make each day, counting every request to any endpoint, including
+    /// retries.
+    ///
+    /// Once the budget has been used up, requests fail immediately with
+    /// [`AdsError::BudgetExhausted`] until the budget is renewed at midnight
+    /// UTC. This can be used to leave some of the API's daily limit for
+    /// other users of a shared token. The count isn't persisted, so each
+    /// process has its own budget.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::builder("ADS_API_TOKEN").daily_budget(1000).build()?;
+    /// assert_eq!(client.budget_remaining(), Some(1000));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn daily_budget(mut self, requests: u64) -> Self {
+        self.daily_budget = Some(requests);
+        self
+    }
+
+    /// Record responses to, or replay them from, the fixture directory `dir`;
+    /// see the [`replay`] module for details.
+    #[cfg(feature = "replay")]
+    pub fn replay<P: Into<std::path::PathBuf>>(mut self, dir: P, mode: replay::ReplayMode) -> Self {
+        self.transport = Some(Arc::new(replay::ReplayTransport::new(dir.into(), mode)));
+        self
+    }
+
     /// Build the `Ads` API client
     ///
     /// # Errors
     ///
     /// This method fails when there are problems parsing any of the parameters
-    /// into the right formats for `reqwest`.
+    /// into the right formats for `reqwest`, or with [`AdsError::Token`] if the
+    /// list of tokens is empty.
     pub fn build(self) -> Result<Ads> {
-        let mut auth_value: header::HeaderValue = format!("Bearer {}", self.token).parse()?;
-        auth_value.set_sensitive(true);
-        let mut headers = header::HeaderMap::new();
-        headers.append(header::AUTHORIZATION, auth_value);
-        let client = Client::builder()
-            .user_agent(self.user_agent)
-            .default_headers(headers)
-            .build()?;
+        let tokens = auth::Tokens::new(&self.tokens)?;
+        let mut client = Client::builder().user_agent(self.user_agent);
+        if let Some(timeout) = self.timeout {
+            client = client.timeout(timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            client = client.tcp_keepalive(interval);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            client = client.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            client = client.pool_max_idle_per_host(max);
+        }
+        if self.http2_prior_knowledge {
+            client = client.http2_prior_knowledge();
+        }
+        if self.http2_adaptive_window {
+            client = client.http2_adaptive_window(true);
+        }
+        let client = client.build()?;
         Ok(Ads {
-            base_url: reqwest::Url::parse(&self.base_url)?,
-            client: std::rc::Rc::new(client),
+            base_url: normalize_base_url(&self.base_url)?,
+            client: Arc::new(client),
+            tokens: Arc::new(tokens),
+            rate_limit: Arc::default(),
+            on_request: self.on_request,
+            transport: self.transport,
+            default_fl: self.default_fl,
+            default_sort: self.default_sort,
+            retries: self.retries,
+            retry_delay: self.retry_delay,
+            budget: self
+                .daily_budget
+                .map(|requests| Arc::new(budget::DailyBudget::new(requests))),
         })
     }
 }
 
+// The token is deliberately left out of the `Debug` output, so that clients
+// can be logged safely
+impl fmt::Debug for AdsBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdsBuilder")
+            .field("base_url", &self.base_url)
+            .field("token", &"<redacted>")
+            .field("user_agent", &self.user_agent)
+            .field("default_fl", &self.default_fl)
+            .field("default_sort", &self.default_sort)
+            .field("timeout", &self.timeout)
+            .field("tcp_keepalive", &self.tcp_keepalive)
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+            .field("http2_prior_knowledge", &self.http2_prior_knowledge)
+            .field("http2_adaptive_window", &self.http2_adaptive_window)
+            .field("retries", &self.retries)
+            .field("retry_delay", &self.retry_delay)
+            .field("daily_budget", &self.daily_budget)
+            .field("on_request", &self.on_request.is_some())
+            .finish()
+    }
+}
+
+impl fmt::Debug for Ads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ads")
+            .field("base_url", &self.base_url.as_str())
+            .field("token", &"<redacted>")
+            .field("rate_limit", &self.rate_limit())
+            .field("default_fl", &self.default_fl)
+            .field("default_sort", &self.default_sort)
+            .field("on_request", &self.on_request.is_some())
+            .finish()
+    }
+}
+
 impl Ads {
     /// Get an API client with a given token.
     ///
@@ -244,7 +585,7 @@ impl Ads {
     }
 
     /// Constructs a new `Ads` interface, loading the API token from either
-    /// environment valiables or the user's home directory.
+    /// environment variables or a token file.
     ///
     /// # Errors
     ///
@@ -262,10 +603,106 @@ impl Ads {
 
     /// Constructs a query for Search API endpoint that can be customized using
     /// a [`search::Query`].
-    pub fn search(&self, query: &str) -> search::Query {
+    pub fn search(&self, query: &str) -> search::Query<'_> {
         search::Query::new(self, query)
     }
 
+    /// The base URL that requests are sent to.
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_str()
+    }
+
+    /// A clone of this client that sends its requests to a different base
+    /// URL, such as a mirror or a proxy.
+    ///
+    /// The clone shares its connection pool, tokens, and rate limit status
+    /// with this client, so it is cheap to create one for a single request.
+    /// The URL is normalized in the same way as [`AdsBuilder::base_url`].
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the URL can't be parsed, or can't be used as a
+    /// base URL.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let mirror = client.with_base_url("https://ads-mirror.example.com/v1")?;
+    /// let response = mirror.search("supernova").send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_base_url(&self, url: &str) -> Result<Self> {
+        Ok(Self {
+            base_url: normalize_base_url(url)?,
+            ..self.clone()
+        })
+    }
+
+    /// The most recent rate limit status reported by the API.
+    ///
+    /// This is updated after every request made by this client (or any of its
+    /// clones), and will be `None` before the first request.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .cloned()
+    }
+
+    /// The number of requests remaining in today's budget, or `None` if no
+    /// budget was set using [`AdsBuilder::daily_budget`].
+    pub fn budget_remaining(&self) -> Option<u64> {
+        self.budget.as_ref().map(|budget| budget.remaining())
+    }
+
+    /// Check that the API is reachable and that the token is accepted.
+    ///
+    /// This makes a single search request that doesn't return any records, so
+    /// it is cheap enough to use as a readiness probe before starting a long
+    /// job, although it does count against the rate limit.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the API can't be reached, or if it responds with
+    /// an unexpected error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::from_env()?;
+    /// let ping = client.ping()?;
+    /// if !ping.authenticated {
+    ///     eprintln!("the API token was rejected");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ping(&self) -> Result<Ping> {
+        let started = std::time::Instant::now();
+        let response = self.get(
+            "search/query",
+            Some(&[("q", "bibcode:0"), ("rows", "0"), ("fl", "id")]),
+        )?;
+        let latency = started.elapsed();
+        match ping::authenticated(response.status()) {
+            Some(authenticated) => Ok(Ping {
+                latency,
+                authenticated,
+                rate_limit: RateLimit::from_headers(response.headers()),
+            }),
+            None => Err(parse_json::<serde_json::Value>(response)
+                .err()
+                .unwrap_or(AdsError::Ads("unexpected response from the API".to_owned()))),
+        }
+    }
+
     /// Execute a general `GET` request to the API.
     ///
     /// # Errors
@@ -276,21 +713,504 @@ impl Ads {
         A: AsRef<str>,
         P: serde::Serialize + ?Sized,
     {
-        self._get(self.absolute_url(path)?, parameters)
+        self.get_with_headers(path, parameters, &[])
     }
 
-    fn _get<P>(&self, url: impl reqwest::IntoUrl, parameters: Option<&P>) -> Result<Response>
+    /// Execute a `GET` request with extra headers, which are added to (or
+    /// replace) the client's default headers.
+    pub(crate) fn get_with_headers<A, P>(
+        &self,
+        path: A,
+        parameters: Option<&P>,
+        headers: &[(String, String)],
+    ) -> Result<Response>
     where
+        A: AsRef<str>,
         P: serde::Serialize + ?Sized,
     {
-        let mut request = self.client.get(url);
+        let mut request = self.client.get(self.absolute_url(path)?);
         if let Some(parameters) = parameters {
             request = request.query(parameters);
         }
-        Ok(request.send()?)
+        for (name, value) in headers {
+            request = request.header(
+                header::HeaderName::from_bytes(name.as_bytes())?,
+                header::HeaderValue::from_str(value)?,
+            );
+        }
+        self.execute(request)
+    }
+
+    /// Execute a `GET` request to the API and deserialize the JSON response.
+    ///
+    /// This can be used to access endpoints that aren't directly supported by
+    /// this client.
+    ///
+    /// # Errors
+    ///
+    /// This method fails on HTTP errors, with messages from the server if
+    /// available, or if the response can't be deserialized as a `T`. If the
+    /// API rate limit has been exceeded, the error will be
+    /// [`AdsError::RateLimited`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let data: serde_json::Value = client.get_json("journals/summary/ApJ", None::<&()>)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_json<T, A, P>(&self, path: A, parameters: Option<&P>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        A: AsRef<str>,
+        P: serde::Serialize + ?Sized,
+    {
+        parse_json(self.get(path, parameters)?)
+    }
+
+    /// Execute a `POST` request to the API with a JSON body and deserialize
+    /// the JSON response.
+    ///
+    /// # Errors
+    ///
+    /// This method fails under the same conditions as [`Ads::get_json`].
+    pub fn post_json<T, A, B>(&self, path: A, body: &B) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        A: AsRef<str>,
+        B: serde::Serialize + ?Sized,
+    {
+        parse_json(self.post(path, body)?)
+    }
+
+    // Execute a `POST` request with a JSON body, without checking the status
+    pub(crate) fn post<A, B>(&self, path: A, body: &B) -> Result<Response>
+    where
+        A: AsRef<str>,
+        B: serde::Serialize + ?Sized,
+    {
+        let request = self.client.post(self.absolute_url(path)?).json(body);
+        self.execute(request)
+    }
+
+    /// Execute a request to any endpoint of the API, with any HTTP method,
+    /// returning the status code and body of the response without parsing
+    /// it.
+    ///
+    /// The parameters are added to the query string, and the body, if any, is
+    /// sent as JSON. This is the most general way to access endpoints that
+    /// aren't directly supported by this client.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the method isn't a valid HTTP method, or if the request
+    /// couldn't be made, but not for HTTP errors.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let response = client.request_raw("GET", "biblib/libraries", &[], None)?;
+    /// println!("{}", response.body);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_raw<A: AsRef<str>>(
+        &self,
+        method: &str,
+        path: A,
+        parameters: &[(String, String)],
+        body: Option<&serde_json::Value>,
+    ) -> Result<RawResponse> {
+        let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| AdsError::InvalidMethod(method.to_owned()))?;
+        let mut request = self.client.request(method, self.absolute_url(path)?);
+        if !parameters.is_empty() {
+            request = request.query(parameters);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        RawResponse::read(self.execute(request)?)
+    }
+
+    fn execute(&self, request: reqwest::blocking::RequestBuilder) -> Result<Response> {
+        let mut request = request.build()?;
+        loop {
+            let (index, token) = self.tokens.current();
+            // Keep a copy of the request to retry with the next token
+            let retry = if self.tokens.has_next(index) {
+                request.try_clone()
+            } else {
+                None
+            };
+            request.headers_mut().insert(header::AUTHORIZATION, token);
+            let response = self.send_with_retries(request);
+            match (response, retry) {
+                (Ok(response), Some(retry))
+                    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+                        && self.tokens.rotate(index) =>
+                {
+                    request = retry;
+                }
+                (response, _) => return response,
+            }
+        }
+    }
+
+    fn send_with_retries(&self, mut request: reqwest::blocking::Request) -> Result<Response> {
+        let mut delay = self.retry_delay;
+        for _ in 0..self.retries {
+            let retry = match request.try_clone() {
+                Some(retry) => retry,
+                None => break,
+            };
+            let response = self.send(request);
+            if !is_transient(&response) {
+                return response;
+            }
+            std::thread::sleep(delay);
+            delay *= 2;
+            request = retry;
+        }
+        self.send(request)
+    }
+
+    fn send(&self, request: reqwest::blocking::Request) -> Result<Response> {
+        if let Some(budget) = &self.budget {
+            budget.take()?;
+        }
+        let method = request.method().to_string();
+        let endpoint = telemetry::endpoint(&self.base_url, request.url());
+        let started = std::time::Instant::now();
+        let response = match &self.transport {
+            Some(transport) => transport.execute(&self.client, request),
+            None => self.client.execute(request).map_err(AdsError::from),
+        };
+        let rate_limit = response
+            .as_ref()
+            .ok()
+            .and_then(|response| RateLimit::from_headers(response.headers()));
+        if let Some(rate_limit) = &rate_limit {
+            *self
+                .rate_limit
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(rate_limit.clone());
+        }
+        if let Some(callback) = &self.on_request {
+            let response = response.as_ref().ok();
+            callback(&RequestMetrics {
+                method,
+                endpoint,
+                latency: started.elapsed(),
+                status: response.map(|response| response.status().as_u16()),
+                content_length: response.and_then(Response::content_length),
+                rate_limit,
+            });
+        }
+        response
     }
 
+    // Paths are always relative to the base URL, even with a leading slash
     fn absolute_url(&self, url: impl AsRef<str>) -> Result<reqwest::Url> {
-        Ok(self.base_url.join(url.as_ref())?)
+        Ok(self.base_url.join(url.as_ref().trim_start_matches('/'))?)
+    }
+}
+
+/// Parse a base URL, making sure that its path ends with a slash so that
+/// joining a relative path keeps any prefix, e.g. `v1/`, and dropping any
+/// query string or fragment.
+fn normalize_base_url(url: &str) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(url)?;
+    if url.cannot_be_a_base() {
+        return Err(url::ParseError::RelativeUrlWithCannotBeABaseBase.into());
+    }
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    url.set_query(None);
+    url.set_fragment(None);
+    Ok(url)
+}
+
+/// Returns `true` for failures that might not happen again if the request is
+/// retried.
+fn is_transient(response: &Result<Response>) -> bool {
+    match response {
+        Ok(response) => matches!(response.status().as_u16(), 502..=504),
+        Err(AdsError::Reqwest(err)) => err.is_timeout() || err.is_connect(),
+        Err(_) => false,
+    }
+}
+
+/// The time given by a `Retry-After` header, in seconds from now.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Timestamp> {
+    let seconds = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<i64>()
+        .ok()?;
+    timestamp::from_unix(timestamp::now_unix() + seconds)
+}
+
+/// Deserialize a JSON response, converting HTTP errors into [`AdsError`]s.
+fn parse_json<T: serde::de::DeserializeOwned>(response: Response) -> Result<T> {
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let headers = response.headers();
+        return Err(AdsError::RateLimited {
+            reset: RateLimit::from_headers(headers)
+                .map(|r| r.reset)
+                .or_else(|| retry_after(headers)),
+        });
+    }
+    if let Err(err) = response.error_for_status_ref() {
+        // Prefer the error message from the API, if any, over the HTTP status
+        return Err(match response.json::<error::ErrorBody>() {
+            Ok(body) => AdsError::Api {
+                status: status.as_u16(),
+                message: body.error.into_message(),
+            },
+            Err(_) => err.into(),
+        });
+    }
+    parse_body(response)
+}
+
+#[cfg(not(feature = "simd-json"))]
+fn parse_body<T: serde::de::DeserializeOwned>(response: Response) -> Result<T> {
+    Ok(response.json()?)
+}
+
+// simd-json parses in place, so it needs a mutable copy of the body
+#[cfg(feature = "simd-json")]
+fn parse_body<T: serde::de::DeserializeOwned>(response: Response) -> Result<T> {
+    let mut body = response.bytes()?.to_vec();
+    Ok(simd_json::serde::from_slice(&mut body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_token() {
+        let builder = Ads::builder("secret-token").user_agent("test");
+        assert!(!format!("{:?}", builder).contains("secret-token"));
+        let client = builder.build().unwrap();
+        assert!(!format!("{:?}", client).contains("secret-token"));
+        assert!(!format!("{:?}", client.search("supernova")).contains("secret-token"));
+    }
+
+    #[test]
+    fn base_urls() {
+        let normalized = |url| normalize_base_url(url).unwrap().to_string();
+        assert_eq!(
+            normalized("https://api.adsabs.harvard.edu/v1"),
+            "https://api.adsabs.harvard.edu/v1/"
+        );
+        assert_eq!(
+            normalized("https://proxy.example.com/ads/v1/?key=x#top"),
+            "https://proxy.example.com/ads/v1/"
+        );
+        assert_eq!(
+            normalized("http://localhost:8080"),
+            "http://localhost:8080/"
+        );
+        assert!(normalize_base_url("mailto:ads@example.com").is_err());
+        assert!(normalize_base_url("not a url").is_err());
+
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/proxy/v1/search/query");
+            then.status(200).body("{}");
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/proxy/v1"))
+            .build()
+            .unwrap();
+        client.get("search/query", None::<&()>).unwrap();
+        client.get("/search/query", None::<&()>).unwrap();
+        mock.assert_hits(2);
+
+        let mirror = Ads::new("token")
+            .unwrap()
+            .with_base_url(&server.url("/proxy/v1"))
+            .unwrap();
+        mirror.get("search/query", None::<&()>).unwrap();
+        mock.assert_hits(3);
+    }
+
+    #[test]
+    fn api_error_status() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/accounts/bootstrap");
+            then.status(403)
+                .header("content-type", "application/json")
+                .body(r#"{"error": "Unauthorized"}"#);
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let err = client
+            .get_json::<serde_json::Value, _, _>("accounts/bootstrap", None::<&()>)
+            .unwrap_err();
+        assert!(
+            matches!(&err, AdsError::Api { status: 403, message } if message == "Unauthorized")
+        );
+        assert_eq!(err.to_string(), "Unauthorized");
+    }
+
+    #[test]
+    fn connection_settings() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(200).body("{}");
+        });
+        let builder = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .tcp_keepalive(Duration::from_secs(30))
+            .pool_idle_timeout(None)
+            .pool_max_idle_per_host(2)
+            .http2_adaptive_window(true);
+        assert!(format!("{:?}", builder).contains("pool_max_idle_per_host: Some(2)"));
+        let client = builder.build().unwrap();
+        client.get("search/query", None::<&()>).unwrap();
+        client.get("search/query", None::<&()>).unwrap();
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn raw_request() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(PUT)
+                .path("/biblib/documents/abc")
+                .query_param("q", "star")
+                .header("authorization", "Bearer token")
+                .json_body(serde_json::json!({"action": "add"}));
+            then.status(404).body(r#"{"error": "no such library"}"#);
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let response = client
+            .request_raw(
+                "put",
+                "biblib/documents/abc",
+                &[("q".to_owned(), "star".to_owned())],
+                Some(&serde_json::json!({"action": "add"})),
+            )
+            .unwrap();
+        mock.assert();
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body, r#"{"error": "no such library"}"#);
+        assert!(matches!(
+            client.request_raw("NOT A METHOD", "search/query", &[], None),
+            Err(AdsError::InvalidMethod(_))
+        ));
+    }
+
+    #[test]
+    fn retry_transient_errors() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let unavailable = server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(503);
+        });
+        let not_found = server.mock(|when, then| {
+            when.method(GET).path("/missing");
+            then.status(404);
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .retries(2)
+            .retry_delay(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        let response = client.get("search/query", None::<&()>).unwrap();
+        assert_eq!(response.status(), 503);
+        unavailable.assert_hits(3);
+        client.get("missing", None::<&()>).unwrap();
+        not_found.assert_hits(1);
+    }
+
+    #[test]
+    fn daily_budget() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 0, "start": 0, "docs": []}}"#);
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .daily_budget(2)
+            .build()
+            .unwrap();
+        client.search("star").send().unwrap();
+        client.clone().search("star").send().unwrap();
+        assert_eq!(client.budget_remaining(), Some(0));
+        assert!(matches!(
+            client.search("star").send(),
+            Err(AdsError::BudgetExhausted)
+        ));
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn rotate_tokens() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let first = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .header("authorization", "Bearer first");
+            then.status(429)
+                .header("content-type", "application/json")
+                .body(r#"{"error": "Too many requests"}"#);
+        });
+        let second = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .header("authorization", "Bearer second");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 0, "start": 0, "docs": []}}"#);
+        });
+        let client = Ads::builder("unused")
+            .base_url(&server.url("/"))
+            .tokens(["first", "second"])
+            .build()
+            .unwrap();
+        client.search("star").send().unwrap();
+        client.search("star").send().unwrap();
+        first.assert_hits(1);
+        second.assert_hits(2);
+
+        assert!(matches!(
+            Ads::builder("token").tokens(Vec::<String>::new()).build(),
+            Err(AdsError::Token)
+        ));
     }
 }
@@ -0,0 +1,294 @@
+//! Citation and usage metrics for a set of records.
+//!
+//! # Examples
+//!
+//! To compute the h-index and total citations of an author's refereed
+//! papers:
+//!
+//! ```no_run
+//! # fn run() -> adsabs::Result<()> {
+//! use adsabs::Ads;
+//! let client = Ads::new("ADS_API_TOKEN")?;
+//! let metrics = client.metrics_for_query(client.search("author:\"^Foreman-Mackey, D\""))?;
+//! println!(
+//!     "h = {}, {} citations",
+//!     metrics.refereed.h_index, metrics.refereed.citations
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Result;
+use crate::search::{Query, MAX_ROWS};
+use crate::Ads;
+use serde::{Deserialize, Serialize};
+
+// The maximum number of records included in a single metrics request
+const MAX_METRICS: usize = 2000;
+
+/// The metrics for a set of records, as returned by
+/// [`Ads::metrics_for_query`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    /// The metrics for all the records.
+    pub all: MetricsTotals,
+    /// The metrics for the refereed records only.
+    pub refereed: MetricsTotals,
+}
+
+/// The metrics for one subset of the records in [`Metrics`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsTotals {
+    /// The number of records.
+    pub papers: u64,
+    /// The number of records, where each is divided by its number of authors.
+    pub normalized_papers: f64,
+    /// The total number of times the records were read in ADS.
+    pub reads: u64,
+    /// The total number of times the records were downloaded from ADS.
+    pub downloads: u64,
+    /// The total number of citations to the records.
+    pub citations: u64,
+    /// The total number of citations from refereed records.
+    pub refereed_citations: u64,
+    /// The total number of citations from records that share an author.
+    pub self_citations: u64,
+    /// The total number of citations, where the citations to each record are
+    /// divided by its number of authors.
+    pub normalized_citations: f64,
+    /// The largest number `h` such that `h` records have at least `h`
+    /// citations each.
+    pub h_index: u64,
+    /// The largest number `g` such that the `g` most cited records have at
+    /// least `g²` citations in total.
+    pub g_index: u64,
+    /// The number of records with at least 10 citations.
+    pub i10_index: u64,
+    /// The number of records with at least 100 citations.
+    pub i100_index: u64,
+    /// The tori index, which normalizes citations by the number of authors
+    /// and references of the citing records.
+    pub tori: f64,
+    /// The number of recent reads, normalized by the number of authors.
+    pub read10: f64,
+}
+
+impl MetricsTotals {
+    fn add(&mut self, basic: &BasicStats, citations: &CitationStats, indicators: &Indicators) {
+        self.papers += basic.papers as u64;
+        self.normalized_papers += basic.normalized_papers;
+        self.reads += basic.reads as u64;
+        self.downloads += basic.downloads as u64;
+        self.citations += citations.citations as u64;
+        self.refereed_citations += citations.refereed_citations as u64;
+        self.self_citations += citations.self_citations as u64;
+        self.normalized_citations += citations.normalized_citations;
+        self.i10_index += indicators.i10 as u64;
+        self.i100_index += indicators.i100 as u64;
+        self.tori += indicators.tori;
+        self.read10 += indicators.read10;
+    }
+}
+
+#[derive(Serialize)]
+struct MetricsRequest<'a> {
+    bibcodes: &'a [String],
+    types: [&'static str; 3],
+}
+
+#[derive(Deserialize)]
+struct MetricsResponse {
+    #[serde(rename = "basic stats", default)]
+    basic: BasicStats,
+    #[serde(rename = "basic stats refereed", default)]
+    basic_refereed: BasicStats,
+    #[serde(rename = "citation stats", default)]
+    citations: CitationStats,
+    #[serde(rename = "citation stats refereed", default)]
+    citations_refereed: CitationStats,
+    #[serde(default)]
+    indicators: Indicators,
+    #[serde(rename = "indicators refereed", default)]
+    indicators_refereed: Indicators,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct BasicStats {
+    #[serde(rename = "number of papers")]
+    papers: f64,
+    #[serde(rename = "normalized paper count")]
+    normalized_papers: f64,
+    #[serde(rename = "total number of reads")]
+    reads: f64,
+    #[serde(rename = "total number of downloads")]
+    downloads: f64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct CitationStats {
+    #[serde(rename = "total number of citations")]
+    citations: f64,
+    #[serde(rename = "total number of refereed citations")]
+    refereed_citations: f64,
+    #[serde(rename = "number of self-citations")]
+    self_citations: f64,
+    #[serde(rename = "normalized number of citations")]
+    normalized_citations: f64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Indicators {
+    i10: f64,
+    i100: f64,
+    tori: f64,
+    read10: f64,
+}
+
+impl Ads {
+    /// Compute the metrics for all the records matching a search query.
+    ///
+    /// The bibcodes and citation counts of the matching records are collected
+    /// first, and the metrics endpoint is then called in batches of up to
+    /// 2000 records. The totals are summed over the batches, while the h- and
+    /// g-indices are computed from the `citation_count` of each record, so
+    /// that they are correct for any number of records.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the search or any of the metrics requests fail.
+    pub fn metrics_for_query(&self, query: Query<'_>) -> Result<Metrics> {
+        let mut bibcodes = Vec::new();
+        let mut all_counts = Vec::new();
+        let mut refereed_counts = Vec::new();
+        for doc in query
+            .fl("bibcode,citation_count,property")
+            .rows(MAX_ROWS)
+            .iter_docs()
+        {
+            let doc = doc?;
+            let count = doc.citation_count.unwrap_or_default();
+            if doc.is_refereed() {
+                refereed_counts.push(count);
+            }
+            all_counts.push(count);
+            bibcodes.extend(doc.bibcode);
+        }
+
+        let mut metrics = Metrics::default();
+        for chunk in bibcodes.chunks(MAX_METRICS) {
+            let request = MetricsRequest {
+                bibcodes: chunk,
+                types: ["basic", "citations", "indicators"],
+            };
+            let response: MetricsResponse = self.post_json("metrics", &request)?;
+            metrics
+                .all
+                .add(&response.basic, &response.citations, &response.indicators);
+            metrics.refereed.add(
+                &response.basic_refereed,
+                &response.citations_refereed,
+                &response.indicators_refereed,
+            );
+        }
+        metrics.all.h_index = h_index(&mut all_counts);
+        metrics.all.g_index = g_index(&all_counts);
+        metrics.refereed.h_index = h_index(&mut refereed_counts);
+        metrics.refereed.g_index = g_index(&refereed_counts);
+        Ok(metrics)
+    }
+}
+
+// Sorts the citation counts in descending order, as required by `g_index`
+fn h_index(counts: &mut [u64]) -> u64 {
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    counts
+        .iter()
+        .zip(1..)
+        .take_while(|&(&count, rank)| count >= rank)
+        .count() as u64
+}
+
+fn g_index(sorted_counts: &[u64]) -> u64 {
+    let mut total = 0;
+    let mut g = 0;
+    for (&count, rank) in sorted_counts.iter().zip(1..) {
+        total += count;
+        if total >= rank * rank {
+            g = rank;
+        }
+    }
+    g
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn indices() {
+        let mut counts = vec![1, 10, 3, 0, 5, 4];
+        assert_eq!(h_index(&mut counts), 3);
+        assert_eq!(counts, [10, 5, 4, 3, 1, 0]);
+        assert_eq!(g_index(&counts), 4);
+        assert_eq!(h_index(&mut []), 0);
+        assert_eq!(g_index(&[]), 0);
+    }
+
+    #[test]
+    fn metrics_request() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("fl", "bibcode,citation_count,property");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    r#"{"response": {"numFound": 2, "start": 0, "docs": [
+                        {"bibcode": "a", "citation_count": 12, "property": ["REFEREED"]},
+                        {"bibcode": "b", "citation_count": 1, "property": ["NOT REFEREED"]}
+                    ]}}"#,
+                );
+        });
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/metrics")
+                .json_body(serde_json::json!({
+                    "bibcodes": ["a", "b"],
+                    "types": ["basic", "citations", "indicators"]
+                }));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({
+                    "basic stats": {"number of papers": 2, "total number of reads": 40},
+                    "basic stats refereed": {"number of papers": 1, "total number of reads": 30},
+                    "citation stats": {"total number of citations": 13, "number of self-citations": 2},
+                    "citation stats refereed": {"total number of citations": 12},
+                    "indicators": {"h": 1, "i10": 1, "tori": 0.5},
+                    "indicators refereed": {"h": 1, "i10": 1, "tori": 0.4},
+                    "skipped bibcodes": []
+                }));
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let metrics = client.metrics_for_query(client.search("star")).unwrap();
+        mock.assert();
+        assert_eq!(metrics.all.papers, 2);
+        assert_eq!(metrics.all.reads, 40);
+        assert_eq!(metrics.all.citations, 13);
+        assert_eq!(metrics.all.self_citations, 2);
+        assert_eq!(metrics.all.h_index, 1);
+        assert_eq!(metrics.all.g_index, 2);
+        assert_eq!(metrics.all.i10_index, 1);
+        assert_eq!(metrics.refereed.papers, 1);
+        assert_eq!(metrics.refereed.citations, 12);
+        assert_eq!(metrics.refereed.g_index, 1);
+        assert_eq!(metrics.refereed.tori, 0.4);
+    }
+}
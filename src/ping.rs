@@ -0,0 +1,62 @@
+use crate::RateLimit;
+use std::time::Duration;
+
+/// The result of a health check using [`crate::Ads::ping`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ping {
+    /// The round trip time for the request.
+    pub latency: Duration,
+    /// Whether the API token was accepted.
+    pub authenticated: bool,
+    /// The rate limit status reported by the API, if any.
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl Ping {
+    /// Returns `true` if the API is reachable, the token was accepted, and
+    /// there are requests remaining.
+    pub fn is_ready(&self) -> bool {
+        self.authenticated && !self.rate_limit.as_ref().is_some_and(|r| r.is_exhausted())
+    }
+}
+
+/// Interpret the status code of a ping request, returning whether the token
+/// was accepted, or `None` for statuses that indicate some other problem.
+pub(crate) fn authenticated(status: reqwest::StatusCode) -> Option<bool> {
+    match status.as_u16() {
+        // A rate limited token is still a valid token
+        200..=299 | 429 => Some(true),
+        401 | 403 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn status_codes() {
+        assert_eq!(authenticated(StatusCode::OK), Some(true));
+        assert_eq!(authenticated(StatusCode::TOO_MANY_REQUESTS), Some(true));
+        assert_eq!(authenticated(StatusCode::UNAUTHORIZED), Some(false));
+        assert_eq!(authenticated(StatusCode::FORBIDDEN), Some(false));
+        assert_eq!(authenticated(StatusCode::BAD_GATEWAY), None);
+    }
+
+    #[test]
+    fn readiness() {
+        let ping = Ping {
+            latency: Duration::from_millis(10),
+            authenticated: true,
+            rate_limit: None,
+        };
+        assert!(ping.is_ready());
+        assert!(!Ping {
+            authenticated: false,
+            ..ping
+        }
+        .is_ready());
+    }
+}
@@ -0,0 +1,60 @@
+use crate::Timestamp;
+use reqwest::header::HeaderMap;
+
+/// The rate limit status reported by the API.
+///
+/// The ADS API enforces a daily limit on the number of requests for each
+/// token, and reports the current status in the headers of every response.
+/// The most recent values seen by a client are available from
+/// [`crate::Ads::rate_limit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The total number of requests allowed per day.
+    pub limit: u64,
+    /// The number of requests remaining before the limit is reached.
+    pub remaining: u64,
+    /// The time when the limit will be reset.
+    pub reset: Timestamp,
+}
+
+impl RateLimit {
+    /// Parse the rate limit status from the `X-RateLimit-*` response headers.
+    ///
+    /// Returns `None` if any of the headers are missing or malformed.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let get = |name: &str| headers.get(name)?.to_str().ok();
+        Some(Self {
+            limit: get("x-ratelimit-limit")?.parse().ok()?,
+            remaining: get("x-ratelimit-remaining")?.parse().ok()?,
+            reset: crate::timestamp::from_unix(get("x-ratelimit-reset")?.parse().ok()?)?,
+        })
+    }
+
+    /// Returns `true` if there are no requests remaining.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Limit", "5000".parse().unwrap());
+        headers.insert("X-RateLimit-Remaining", "4999".parse().unwrap());
+        assert_eq!(RateLimit::from_headers(&headers), None);
+
+        headers.insert("X-RateLimit-Reset", "1635724800".parse().unwrap());
+        let rate_limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(rate_limit.limit, 5000);
+        assert_eq!(rate_limit.remaining, 4999);
+        assert_eq!(
+            crate::timestamp::to_unix(&rate_limit.reset),
+            Some(1_635_724_800)
+        );
+        assert!(!rate_limit.is_exhausted());
+    }
+}
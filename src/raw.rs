@@ -0,0 +1,29 @@
+use crate::error::Result;
+use reqwest::blocking::Response;
+
+/// An unparsed response from the API, as returned by [`crate::Ads::request_raw`],
+/// [`crate::search::Query::send_raw`], and [`crate::export::Export::send_raw`].
+///
+/// This is useful for inspecting exactly what the server returned, for
+/// example when a response can't be deserialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The body of the response.
+    pub body: String,
+}
+
+impl RawResponse {
+    pub(crate) fn read(response: Response) -> Result<Self> {
+        Ok(Self {
+            status: response.status().as_u16(),
+            body: response.text()?,
+        })
+    }
+
+    /// Returns `true` if the status code indicates success.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
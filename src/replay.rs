@@ -0,0 +1,188 @@
+//! Recording API responses to disk and replaying them later.
+//!
+//! This is only available with the `replay` feature, and it is designed for
+//! writing deterministic integration tests, and for developing offline against
+//! realistic data. Each response is saved as a JSON file in a fixture
+//! directory, named using a hash of the request method, URL, and body, and
+//! these files can be committed alongside the tests that use them.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # fn run() -> adsabs::Result<()> {
+//! use adsabs::{replay::ReplayMode, Ads};
+//! let client = Ads::builder("ADS_API_TOKEN")
+//!     .replay("tests/fixtures", ReplayMode::Auto)
+//!     .build()?;
+//! // The first run hits the API, and later runs use the saved response
+//! let response = client.search("supernova").send()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{transport::Transport, AdsError, Result};
+use reqwest::blocking::{Client, Request, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// When to send requests to the API, and when to use saved responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Use saved responses when they exist, and record the others.
+    Auto,
+    /// Always send requests, overwriting any saved responses.
+    Record,
+    /// Only use saved responses, failing with [`AdsError::MissingFixture`]
+    /// for requests that haven't been recorded.
+    Replay,
+}
+
+/// A response saved to disk.
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    method: String,
+    url: String,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+pub(crate) struct ReplayTransport {
+    dir: PathBuf,
+    mode: ReplayMode,
+}
+
+impl ReplayTransport {
+    pub fn new(dir: PathBuf, mode: ReplayMode) -> Self {
+        Self { dir, mode }
+    }
+
+    fn record(&self, client: &Client, request: Request, path: &Path) -> Result<Fixture> {
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+        let response = client.execute(request)?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| *name != reqwest::header::SET_COOKIE)
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        let fixture = Fixture {
+            method,
+            url,
+            status,
+            headers,
+            body: response.text()?,
+        };
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, serde_json::to_string_pretty(&fixture)?)?;
+        Ok(fixture)
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn execute(&self, client: &Client, request: Request) -> Result<Response> {
+        let path = self.dir.join(fixture_name(&request));
+        let fixture = match self.mode {
+            ReplayMode::Record => self.record(client, request, &path)?,
+            ReplayMode::Auto if !path.exists() => self.record(client, request, &path)?,
+            ReplayMode::Replay if !path.exists() => return Err(AdsError::MissingFixture(path)),
+            _ => serde_json::from_str(&fs::read_to_string(&path)?)?,
+        };
+        let mut response = http::Response::builder().status(fixture.status);
+        for (name, value) in &fixture.headers {
+            response = response.header(name.as_str(), value.as_str());
+        }
+        let response = response
+            .body(fixture.body)
+            .map_err(|err| AdsError::Ads(format!("invalid fixture {}: {}", path.display(), err)))?;
+        Ok(response.into())
+    }
+}
+
+/// The file name for a request, based on a hash of its method, URL, and body.
+fn fixture_name(request: &Request) -> String {
+    let mut hash = Fnv1a::new();
+    hash.write(request.method().as_str().as_bytes());
+    hash.write(request.url().as_str().as_bytes());
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        hash.write(body);
+    }
+    let endpoint = request
+        .url()
+        .path_segments()
+        .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+        .unwrap_or("root");
+    format!("{}-{:016x}.json", endpoint, hash.0)
+}
+
+/// The 64-bit FNV-1a hash, which is stable across platforms and versions,
+/// unlike the standard library hasher.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    const BODY: &str = r#"{"response": {"numFound": 1, "start": 0, "docs": [{"id": "1"}]}}"#;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("adsabs-replay-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn record_then_replay() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("x-ratelimit-limit", "5000")
+                .header("x-ratelimit-remaining", "4999")
+                .header("x-ratelimit-reset", "1635724800")
+                .body(BODY);
+        });
+        let dir = temp_dir("record");
+        let client = |mode| {
+            crate::Ads::builder("token")
+                .base_url(&server.url("/"))
+                .replay(&dir, mode)
+                .build()
+                .unwrap()
+        };
+
+        let recorded = client(ReplayMode::Auto).search("star").send().unwrap();
+        assert_eq!(recorded.num_found, 1);
+        let replaying = client(ReplayMode::Replay);
+        let replayed = replaying.search("star").send().unwrap();
+        assert_eq!(replayed.docs[0].id.as_deref(), Some("1"));
+        assert_eq!(replaying.rate_limit().unwrap().remaining, 4999);
+        mock.assert_hits(1);
+
+        assert!(matches!(
+            replaying.search("galaxy").send(),
+            Err(AdsError::MissingFixture(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -38,11 +38,57 @@
 //! API servers.
 
 use crate::error::{AdsError, Result};
-use chrono::{DateTime, Utc};
+use crate::{CancelToken, RateLimit, Timestamp};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+mod affiliation;
+mod arxiv;
+mod borrowed;
+mod data;
+mod diff;
+mod duplicates;
+mod facet;
+mod fulltext;
+mod grant;
+mod harvest;
+mod html;
+mod identity;
+mod lenient;
+mod links;
+mod normalize;
+mod object;
+mod template;
+mod validate;
+pub use affiliation::{group_by_institution, AuthorAffiliation, Institution};
+pub use borrowed::DocumentRef;
+pub use data::DataArchive;
+pub use diff::FieldChange;
+pub use duplicates::{merge_duplicates, DUPLICATE_FIELDS};
+pub use grant::Grant;
+pub use harvest::HarvestState;
+pub use links::{AbstractTab, FulltextLink, LinkKind};
+pub use template::QueryTemplate;
 
 // The maximum number of rows that the API allows
-const MAX_ROWS: u64 = 2000;
+pub(crate) const MAX_ROWS: u64 = 2000;
+
+// The address of the ADS web interface, used by `Query::ui_url`
+const UI_URL: &str = "https://ui.adsabs.harvard.edu/";
+
+// The number of results beyond which `IterDocs` pages using `cursorMark`,
+// since Solr gets slow (and eventually refuses) for large values of `start`
+const DEEP_PAGING_THRESHOLD: u64 = 10_000;
+
+// The size of the first page when `IterDocs` uses adaptive paging, and the
+// response time below which the following pages grow
+const ADAPTIVE_FIRST_PAGE: u64 = 100;
+const ADAPTIVE_TARGET_LATENCY: Duration = Duration::from_secs(2);
 
 /// A builder for a search API query that can be used to customize and filter
 /// the query.
@@ -60,32 +106,205 @@ const MAX_ROWS: u64 = 2000;
 /// # Ok(())
 /// # }
 /// ```
-#[derive(serde::Serialize, Clone)]
+#[derive(serde::Serialize, Clone, Debug)]
 #[must_use]
 pub struct Query<'ads> {
     #[serde(skip)]
-    client: &'ads crate::Ads,
+    pub(crate) client: Cow<'ads, crate::Ads>,
     q: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     rows: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    start: Option<u64>,
+    pub(crate) start: Option<u64>,
     #[serde(serialize_with = "fl_defaults")]
     fl: Vec<String>,
+    // Whether `fl` holds the client's default fields, which are replaced by
+    // the first call to `Query::fl`
+    #[serde(skip)]
+    fl_is_default: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     fq: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(serialize_with = "comma_separated")]
     sort: Vec<Sort>,
+    // Whether `sort` holds the client's default sort, like `fl_is_default`
+    #[serde(skip)]
+    sort_is_default: bool,
+    #[serde(skip)]
+    headers: Vec<(String, String)>,
+    #[serde(rename = "cursorMark", skip_serializing_if = "Option::is_none")]
+    cursor_mark: Option<String>,
+    #[serde(skip)]
+    pub(crate) cancel: Option<CancelToken>,
+}
+
+/// The parameters of a [`Query`], without the client, which can be stored in
+/// configuration files and loaded at runtime.
+///
+/// # Example
+///
+/// ```rust
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::{search::{Query, QueryParams}, Ads};
+/// let client = Ads::new("ADS_API_TOKEN")?;
+/// let params: QueryParams = serde_json::from_str(
+///     r#"{"q": "supernova", "fl": ["bibcode", "title"], "sort": ["date asc"]}"#,
+/// )?;
+/// let query = Query::from_params(&client, params);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct QueryParams {
+    /// The search query.
+    pub q: String,
+    /// The number of results to return per page; see [`Query::rows`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<u64>,
+    /// The starting point for returned results; see [`Query::start`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<u64>,
+    /// The fields to return; see [`Query::fl`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fl: Vec<String>,
+    /// The filter query; see [`Query::fq`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fq: Option<String>,
+    /// The sorting fields and directions; see [`Query::sort`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sort: Vec<Sort>,
 }
 
 /// A single page of responses from the search API.
-#[derive(Serialize, Deserialize, Clone)]
-pub struct Response {
+///
+/// The records are [`Document`]s by default, but other types can be used when
+/// parsing a response body with [`Response::from_slice`], e.g.
+/// [`DocumentRef`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Response<D = Document> {
     #[serde(rename = "numFound")]
     pub num_found: u64,
     pub start: u64,
-    pub docs: Vec<Document>,
+    pub docs: Vec<D>,
+}
+
+impl<D> Response<D> {
+    /// The number of records on this page.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Returns `true` if there are no records on this page.
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Returns `true` if there are no more records after this page.
+    pub fn is_last_page(&self) -> bool {
+        self.next_start().is_none()
+    }
+
+    /// The value of [`Query::start`] for fetching the next page of results, or
+    /// `None` if this is the last page.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// # use adsabs::Ads;
+    /// # let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.search("supernova").rows(100);
+    /// let mut response = query.send()?;
+    /// loop {
+    ///     for doc in &response {
+    ///         println!("{:?}", doc.title);
+    ///     }
+    ///     match response.next_start() {
+    ///         Some(start) => response = query.clone().start(start).send()?,
+    ///         None => break,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_start(&self) -> Option<u64> {
+        let next = self.start + self.docs.len() as u64;
+        if self.docs.is_empty() || next >= self.num_found {
+            None
+        } else {
+            Some(next)
+        }
+    }
+
+    /// An iterator over the records on this page.
+    pub fn iter(&self) -> std::slice::Iter<'_, D> {
+        self.docs.iter()
+    }
+}
+
+impl<D> IntoIterator for Response<D> {
+    type Item = D;
+    type IntoIter = std::vec::IntoIter<D>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.docs.into_iter()
+    }
+}
+
+impl<'a, D> IntoIterator for &'a Response<D> {
+    type Item = &'a D;
+    type IntoIter = std::slice::Iter<'a, D>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.docs.iter()
+    }
+}
+
+impl<'de, D: Deserialize<'de>> Response<D> {
+    /// Parse the body of a search API response.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the body can't be parsed, or if it contains an
+    /// error message from the API.
+    pub fn from_slice(body: &'de [u8]) -> Result<Self> {
+        serde_json::from_slice::<Envelope<D>>(body)?.into_result()
+    }
+}
+
+/// The body of a search API response, which is deserialized directly, without
+/// an intermediate `serde_json::Value`.
+#[derive(Deserialize)]
+struct Envelope<D> {
+    #[serde(default = "Option::default")]
+    response: Option<Response<D>>,
+    #[serde(default)]
+    error: Option<crate::error::ApiError>,
+    #[serde(default, rename = "nextCursorMark")]
+    next_cursor_mark: Option<String>,
+}
+
+impl<D> Envelope<D> {
+    fn into_result(self) -> Result<Response<D>> {
+        self.into_parts().map(|(response, _)| response)
+    }
+
+    fn into_parts(self) -> Result<(Response<D>, Option<String>)> {
+        let next_cursor_mark = self.next_cursor_mark;
+        let response = match self {
+            Envelope {
+                error: Some(error), ..
+            } => Err(AdsError::Ads(error.into_message())),
+            Envelope {
+                response: Some(response),
+                ..
+            } => Ok(response),
+            _ => Err(AdsError::Ads(
+                "the API returned neither results nor an error".to_owned(),
+            )),
+        }?;
+        Ok((response, next_cursor_mark))
+    }
 }
 
 /// A `Document` returned from a search query. All of the fields are `Option`s
@@ -103,25 +322,31 @@ pub struct Document {
     pub alternate_title: Vec<String>,
     pub arxiv_class: Vec<String>,
     pub author: Vec<String>,
+    #[serde(default, deserialize_with = "lenient::count")]
     pub author_count: u64,
     pub author_norm: Vec<String>,
     pub bibcode: String,
     pub bibgroup: Vec<String>,
     pub bibstem: Vec<String>,
     pub citation: Vec<String>,
+    #[serde(default, deserialize_with = "lenient::count")]
     pub citation_count: u64,
+    #[serde(default, deserialize_with = "lenient::score")]
     pub cite_read_boost: f32,
+    #[serde(default, deserialize_with = "lenient::count")]
     pub classic_factor: u64,
     pub comment: String,
     pub copyright: String,
     pub data: Vec<String>,
     pub database: Vec<Database>,
-    pub date: DateTime<Utc>,
+    #[serde(default, with = "crate::timestamp::option")]
+    pub date: Timestamp,
     pub doctype: DocType,
     pub doi: Vec<String>,
     pub eid: String,
     pub entdate: String, // YYYY-MM-DD
-    pub entry_date: DateTime<Utc>,
+    #[serde(default, with = "crate::timestamp::option")]
+    pub entry_date: Timestamp,
     pub esources: Vec<String>,
     pub facility: Vec<String>,
     pub first_author: String,
@@ -131,7 +356,8 @@ pub struct Document {
     pub grant_id: Vec<String>,
     pub id: String,
     pub identifier: Vec<String>,
-    pub indexstamp: DateTime<Utc>,
+    #[serde(default, with = "crate::timestamp::option")]
+    pub indexstamp: Timestamp,
     pub inst: Vec<String>,
     pub isbn: Vec<String>,
     pub issn: Vec<String>,
@@ -155,6 +381,7 @@ pub struct Document {
     pub pub_raw: String,
     pub pubdate: String, // YYYY-MM-DD
     pub pubnote: Vec<String>,
+    #[serde(default, deserialize_with = "lenient::count")]
     pub read_count: u64,
     pub reference: Vec<String>,
     pub simbid: Vec<String>,
@@ -207,13 +434,90 @@ impl<'ads> Query<'ads> {
     /// of this method directly.
     pub fn new(client: &'ads crate::Ads, query: &str) -> Self {
         Self {
-            client,
+            fl: client.default_fl.clone(),
+            fl_is_default: true,
+            client: Cow::Borrowed(client),
             q: query.to_owned(),
             rows: None,
             start: None,
-            fl: Vec::new(),
             fq: None,
-            sort: Vec::new(),
+            sort: client.default_sort.clone(),
+            sort_is_default: true,
+            headers: Vec::new(),
+            cursor_mark: None,
+            cancel: None,
+        }
+    }
+
+    /// Build a query from a set of stored parameters.
+    ///
+    /// The client's default fields and sort are used if `params` doesn't
+    /// specify any.
+    pub fn from_params(client: &'ads crate::Ads, params: QueryParams) -> Self {
+        let mut query = Self::new(client, &params.q);
+        query.rows = params.rows;
+        query.start = params.start;
+        query.fq = params.fq;
+        if !params.sort.is_empty() {
+            query.sort = params.sort;
+            query.sort_is_default = false;
+        }
+        if !params.fl.is_empty() {
+            query.fl.clear();
+            query.fl_is_default = false;
+            for fl in &params.fl {
+                push_fields(&mut query.fl, fl);
+            }
+        }
+        query
+    }
+
+    /// The parameters of this query, which can be serialized and used to
+    /// rebuild the query later using [`Query::from_params`].
+    pub fn params(&self) -> QueryParams {
+        QueryParams {
+            q: self.q.clone(),
+            rows: self.rows,
+            start: self.start,
+            fl: self.fl.clone(),
+            fq: self.fq.clone(),
+            sort: self.sort.clone(),
+        }
+    }
+
+    /// Convert this query into one that owns a handle to the client, instead
+    /// of borrowing it.
+    ///
+    /// Cloning an [`crate::Ads`] interface is cheap, and the resulting query
+    /// can be sent to other threads or stored without being tied to the
+    /// lifetime of the client.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// # use adsabs::Ads;
+    /// # let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.search("supernova").into_owned();
+    /// let handle = std::thread::spawn(move || query.send());
+    /// let response = handle.join().unwrap()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_owned(self) -> Query<'static> {
+        Query {
+            client: Cow::Owned(self.client.into_owned()),
+            q: self.q,
+            rows: self.rows,
+            start: self.start,
+            fl: self.fl,
+            fl_is_default: self.fl_is_default,
+            fq: self.fq,
+            sort: self.sort,
+            sort_is_default: self.sort_is_default,
+            headers: self.headers,
+            cursor_mark: self.cursor_mark,
+            cancel: self.cancel,
         }
     }
 
@@ -231,11 +535,22 @@ impl<'ads> Query<'ads> {
     /// The list of fields to return.
     ///
     /// The value should be a comma separated list of field names, e.g.
-    /// `fl=bibcode,author,title`. The default is the document id (`fl=id`). A
-    /// non-exhaustive list of available fields is available at:
+    /// `fl=bibcode,author,title`, and this can be called more than once to add
+    /// more fields. These replace the default fields set using
+    /// [`crate::AdsBuilder::default_fl`], or `author,first_author,bibcode,id,
+    /// year,title` if there are none. A non-exhaustive list of available
+    /// fields is available at:
     /// <https://adsabs.github.io/help/search/comprehensive-solr-term-list>
+    ///
+    /// Repeated field names are only requested once, and
+    /// [`Query::validate`] can be used to catch misspelled fields, which
+    /// would otherwise be silently missing from the results.
     pub fn fl(mut self, fl: &str) -> Self {
-        self.fl.push(fl.to_owned());
+        if self.fl_is_default {
+            self.fl.clear();
+            self.fl_is_default = false;
+        }
+        push_fields(&mut self.fl, fl);
         self
     }
 
@@ -246,19 +561,60 @@ impl<'ads> Query<'ads> {
     /// searches only the results returned by the search entered via the `q`
     /// parameter, not the entire index.
     ///
-    /// Note: multiple values for this are not yet supported by this client.
-    pub fn fq(mut self, fq: &str) -> Self {
-        self.fq = Some(fq.to_owned());
+    /// Calling this more than once, or after one of the filter shortcuts like
+    /// [`Query::refereed`], combines the filters using `AND`.
+    pub fn fq(self, fq: &str) -> Self {
+        if self.fq.is_some() {
+            self.and_fq(&format!("({})", fq))
+        } else {
+            self.and_fq(fq)
+        }
+    }
+
+    // Combine a filter with the existing filter query, if any
+    fn and_fq(mut self, filter: &str) -> Self {
+        self.fq = Some(match self.fq {
+            Some(fq) => format!("({}) AND {}", fq, filter),
+            None => filter.to_owned(),
+        });
         self
     }
 
+    /// Only return refereed records, by adding `property:refereed` to the
+    /// filter query.
+    ///
+    /// Like the other property shortcuts, this is combined with any filter
+    /// query set using [`Query::fq`].
+    pub fn refereed(self) -> Self {
+        self.and_fq("property:refereed")
+    }
+
+    /// Only return records with an open access full text, by adding
+    /// `property:openaccess` to the filter query.
+    pub fn open_access(self) -> Self {
+        self.and_fq("property:openaccess")
+    }
+
+    /// Only return records that aren't journal articles, like conference
+    /// abstracts, catalogs, and software, by adding `property:nonarticle` to
+    /// the filter query.
+    pub fn not_article(self) -> Self {
+        self.and_fq("property:nonarticle")
+    }
+
     /// The sorting field and direction to be used when returning results.
     ///
     /// The `field` argument should be a valid field name. The default sort
     /// method is the relevancy score as calculated by the search engine. Other
     /// useful fields to sort on may be `date`, `read_count`, `first_author`, or
-    /// `bibcode`.
+    /// `bibcode`. This can be called more than once to sort on more fields,
+    /// and these replace the default sort set using
+    /// [`crate::AdsBuilder::default_sort`].
     pub fn sort<T: Into<Sort>>(mut self, field: T) -> Self {
+        if self.sort_is_default {
+            self.sort.clear();
+            self.sort_is_default = false;
+        }
         self.sort.push(field.into());
         self
     }
@@ -273,31 +629,188 @@ impl<'ads> Query<'ads> {
         self
     }
 
+    /// Add an HTTP header to the requests made for this query, e.g. for
+    /// `X-Forwarded-For` when running behind a proxy.
+    ///
+    /// These are sent in addition to the client's default headers, replacing
+    /// any with the same name. Invalid header names or values are reported
+    /// when the query is sent.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// A link to the same search in the ADS web interface, including the sort
+    /// order and filter query.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.search("author:\"^Foreman-Mackey\"").sort("date");
+    /// assert_eq!(
+    ///     query.ui_url(),
+    ///     "https://ui.adsabs.harvard.edu/search/q=author%3A%22%5EForeman-Mackey%22&sort=date%20desc&p_=0"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ui_url(&self) -> String {
+        let mut url = format!("{}search/q={}", UI_URL, encode_component(&self.q));
+        if let Some(fq) = &self.fq {
+            url.push_str("&fq=");
+            url.push_str(&encode_component(fq));
+        }
+        if !self.sort.is_empty() {
+            let sort = self
+                .sort
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            url.push_str("&sort=");
+            url.push_str(&encode_component(&sort));
+        }
+        url.push_str("&p_=0");
+        url
+    }
+
+    /// Stop sending requests for this query once `token` is cancelled, or
+    /// its deadline has passed.
+    ///
+    /// This applies to [`Query::send`], every page of [`Query::iter_docs`],
+    /// including waits for the rate limit to reset, and the export requests
+    /// made by [`crate::export::fetch_bibtex_for_query`]. See
+    /// [`CancelToken`] for an example.
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
     /// Submit the seach query.
     ///
     /// # Errors
     ///
-    /// This method fails on HTTP errors, with messages from the server.
+    /// This method fails on HTTP errors, with messages from the server. If the
+    /// API rate limit has been exceeded, the error will be
+    /// [`AdsError::RateLimited`].
     pub fn send(&self) -> Result<Response> {
-        let data: serde_json::Value = self.client.get("search/query", Some(self))?.json()?;
-        if let Some(serde_json::Value::String(msg)) = data.get("error").and_then(|x| x.get("msg")) {
-            return Err(AdsError::Ads(msg.clone()));
+        self.send_envelope()?.into_result()
+    }
+
+    /// Submit the search query, returning the status code and body of the
+    /// response without parsing it, even if the request failed.
+    ///
+    /// This is useful for debugging when [`Query::send`] fails to
+    /// deserialize a response.
+    ///
+    /// # Errors
+    ///
+    /// This only fails if the request couldn't be made, or the body couldn't
+    /// be read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let raw = client.search("supernova").fl("bibcode").send_raw()?;
+    /// println!("{}: {}", raw.status, raw.body);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_raw(&self) -> Result<crate::RawResponse> {
+        crate::RawResponse::read(self.client.get_with_headers(
+            "search/query",
+            Some(self),
+            &self.headers,
+        )?)
+    }
+
+    fn send_envelope(&self) -> Result<Envelope<Document>> {
+        if let Some(cancel) = &self.cancel {
+            cancel.check()?;
         }
-        Ok(serde_json::from_value(data["response"].clone())?)
+        let response = self
+            .client
+            .get_with_headers("search/query", Some(self), &self.headers)?;
+        crate::parse_json(response)
+    }
+
+    // Deep paging with `cursorMark` requires the sort to include the unique
+    // `id` field as a tie breaker
+    fn with_tie_breaker(mut self) -> Self {
+        // Keep the API's default relevance order, which an explicit sort
+        // would otherwise replace
+        if self.sort.is_empty() {
+            self.sort.push(Sort::desc("score"));
+        }
+        if !self.sort.iter().any(|sort| sort.field() == "id") {
+            self.sort.push(Sort::asc("id"));
+        }
+        self
     }
 
     /// Get an iterator over all search results with transparent support for
     /// pagination.
+    ///
+    /// Results are fetched using `start` and `rows` for shallow result sets,
+    /// and the iterator switches to Solr's `cursorMark` when more than 10,000
+    /// results are expected. In that case an `id asc` tie breaker is appended
+    /// to the sort order if the sort doesn't already include `id`.
     pub fn iter_docs(self) -> IterDocs<'ads> {
         let start = self.start.unwrap_or(0);
         IterDocs {
             query: self,
             num_found: 0,
+            fetched: false,
+            done: false,
+            first: start,
             start,
             limit: None,
+            max_wait: None,
+            adaptive: None,
+            cursor: None,
             docs: Vec::new().into_iter(),
         }
     }
+
+    /// Get an iterator over the search results that follow a page of results
+    /// that has already been fetched, for example using [`Query::send`].
+    ///
+    /// The query should be the same as the one used to fetch `response`, and
+    /// no requests are made if that page was the last one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.search("supernova").rows(100);
+    /// let first = query.send()?;
+    /// println!("{} records found", first.num_found);
+    /// for doc in query.continue_from(&first) {
+    ///     println!("{:?}", doc?.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn continue_from<D>(self, response: &Response<D>) -> IterDocs<'ads> {
+        let start = response.start + response.docs.len() as u64;
+        let mut iter = Query {
+            start: Some(start),
+            ..self
+        }
+        .iter_docs();
+        iter.fetched = true;
+        iter.done = response.next_start().is_none();
+        iter.num_found = response.num_found;
+        iter
+    }
 }
 
 /// Used to set the order for sorting query results.
@@ -357,35 +870,115 @@ impl Sort {
     pub fn desc(field: &str) -> Self {
         Sort::Desc(field.to_owned())
     }
+
+    /// The name of the field being sorted on.
+    pub fn field(&self) -> &str {
+        match self {
+            Sort::Asc(field) | Sort::Desc(field) => field,
+        }
+    }
 }
 
+/// A field name is sorted in descending order, unless it is followed by a
+/// direction, e.g. `"date asc"`.
 impl From<&str> for Sort {
     fn from(s: &str) -> Self {
-        Sort::Desc(s.to_owned())
+        s.parse().unwrap_or_else(|_| Sort::Desc(s.to_owned()))
     }
 }
 
-impl ToString for Sort {
-    fn to_string(&self) -> String {
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Sort::Asc(fl) => format!("{} asc", fl),
-            Sort::Desc(fl) => format!("{} desc", fl),
+            Sort::Asc(fl) => write!(f, "{} asc", fl),
+            Sort::Desc(fl) => write!(f, "{} desc", fl),
+        }
+    }
+}
+
+impl Serialize for Sort {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sort {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parse a sort like `"date asc"` or `"citation_count desc"`, where the
+/// direction is optional and defaults to descending.
+///
+/// # Example
+///
+/// ```rust
+/// use adsabs::search::Sort;
+/// assert_eq!("date asc".parse::<Sort>().unwrap(), Sort::asc("date"));
+/// assert_eq!("citation_count".parse::<Sort>().unwrap(), Sort::desc("citation_count"));
+/// ```
+impl FromStr for Sort {
+    type Err = AdsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(field), None, None) => Ok(Sort::desc(field)),
+            (Some(field), Some(direction), None) if direction.eq_ignore_ascii_case("asc") => {
+                Ok(Sort::asc(field))
+            }
+            (Some(field), Some(direction), None) if direction.eq_ignore_ascii_case("desc") => {
+                Ok(Sort::desc(field))
+            }
+            _ => Err(AdsError::InvalidSort(s.to_owned())),
         }
     }
 }
 
 /// An iterator over the results of a query with transparent support for
 /// pagination.
+#[derive(Debug)]
 #[must_use]
 pub struct IterDocs<'ads> {
     query: Query<'ads>,
     num_found: u64,
+    fetched: bool,
+    // Set once the results are exhausted, so that the iterator is fused
+    done: bool,
+    first: u64,
     start: u64,
     limit: Option<u64>,
+    // The longest time to wait for the rate limit to reset, if enabled
+    max_wait: Option<Duration>,
+    // The size of the next page, when adaptive paging is enabled
+    adaptive: Option<u64>,
+    // The `nextCursorMark` from the last page, only kept when the results are
+    // deep enough to need cursor based paging
+    cursor: Option<String>,
     docs: <Vec<Document> as IntoIterator>::IntoIter,
 }
 
 impl<'ads> IterDocs<'ads> {
+    /// Convert this iterator into one that owns a handle to the client; see
+    /// [`Query::into_owned`].
+    pub fn into_owned(self) -> IterDocs<'static> {
+        IterDocs {
+            query: self.query.into_owned(),
+            num_found: self.num_found,
+            fetched: self.fetched,
+            done: self.done,
+            first: self.first,
+            start: self.start,
+            limit: self.limit,
+            max_wait: self.max_wait,
+            adaptive: self.adaptive,
+            cursor: self.cursor,
+            docs: self.docs,
+        }
+    }
+
     /// Limit the total number of results returned.
     ///
     /// Every attempt will be made to minimize the number of API calls, so this
@@ -395,36 +988,163 @@ impl<'ads> IterDocs<'ads> {
         self
     }
 
+    /// Wait for the rate limit to reset, and then continue, when a page of
+    /// results is rejected with a `429` status, instead of returning
+    /// [`AdsError::RateLimited`].
+    ///
+    /// The wait is based on the `X-RateLimit-Reset` or `Retry-After` headers
+    /// of the response, and the error is still returned if neither is present
+    /// or if the wait would be longer than `max_wait`. Since the ADS limits are
+    /// daily, the wait can be many hours.
+    pub fn wait_on_rate_limit(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    /// Start with small pages of results, and make them larger as the
+    /// iteration continues, instead of always using the largest pages.
+    ///
+    /// The first page has 100 results, so they arrive quickly, and each page
+    /// after a fast response is twice as large, up to the maximum of 2000
+    /// rows, to use fewer requests overall. Slow responses shrink the pages
+    /// again. Full pages are used straight away when the daily rate limit is
+    /// running low, and the remaining results are fetched together when they
+    /// would only need one more page. This overrides [`Query::rows`].
+    pub fn adaptive_page_size(mut self) -> Self {
+        self.adaptive = Some(ADAPTIVE_FIRST_PAGE);
+        self
+    }
+
+    /// Stop fetching pages once `token` is cancelled; see
+    /// [`Query::cancel_token`].
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.query.cancel = Some(token);
+        self
+    }
+
+    /// The total number of records matching the query.
+    ///
+    /// This is only known after the first page of results has been fetched, and
+    /// it will be `None` before then.
+    pub fn num_found(&self) -> Option<u64> {
+        if self.fetched {
+            Some(self.num_found)
+        } else {
+            None
+        }
+    }
+
     #[inline]
     fn page_size(&self) -> u64 {
-        MAX_ROWS.min(
-            self.limit
+        let size = match self.adaptive {
+            // Avoid a small final page by fetching the rest along with this one
+            Some(size) => {
+                let remaining = self.end().saturating_sub(self.start);
+                if remaining <= 2 * size {
+                    remaining
+                } else {
+                    size
+                }
+            }
+            None => self
+                .limit
                 .unwrap_or_else(|| self.query.rows.unwrap_or(MAX_ROWS)),
-        )
+        };
+        MAX_ROWS.min(size)
+    }
+
+    // The index one past the last result that will be returned, if known
+    fn end(&self) -> u64 {
+        let end = self
+            .limit
+            .map_or(u64::MAX, |limit| self.first.saturating_add(limit));
+        if self.fetched {
+            end.min(self.num_found)
+        } else {
+            end
+        }
     }
 
     fn try_next(&mut self) -> Result<Option<Document>> {
+        if self.done || self.start >= self.end() {
+            self.done = true;
+            return Ok(None);
+        }
+
         if let Some(doc) = self.docs.next() {
             self.start += 1;
             return Ok(Some(doc));
         }
 
-        if self.start > 0
-            && (self.start >= self.num_found || self.start >= self.limit.unwrap_or(u64::MAX))
-        {
-            return Ok(None);
+        let mut query = self.query.clone().rows(self.page_size());
+        if !self.fetched {
+            // Cursors can only start from the first result, so the first page
+            // asks for one whenever the results might turn out to be deep,
+            // which keeps the order consistent if we switch to it later
+            let shallow = matches!(self.limit, Some(limit) if limit <= DEEP_PAGING_THRESHOLD);
+            if self.start == 0 && !shallow {
+                query = query.with_tie_breaker();
+                query.cursor_mark = Some("*".to_owned());
+                self.query = self.query.clone().with_tie_breaker();
+            }
+        } else if let Some(cursor) = &self.cursor {
+            // The cursor is only replaced once the page has been fetched, so
+            // that a failed page can be retried from the same place
+            query.cursor_mark = Some(cursor.clone());
+        }
+        if query.cursor_mark.is_none() {
+            query = query.start(self.start);
         }
 
-        let response = self
-            .query
-            .clone()
-            .start(self.start)
-            .rows(self.page_size())
-            .send()?;
+        let previous = query.cursor_mark.clone();
+        let sent = Instant::now();
+        let (response, next_cursor) = self.send_waiting(&query)?.into_parts()?;
+        if let Some(size) = self.adaptive {
+            let rate_limit = self.query.client.rate_limit();
+            self.adaptive = Some(next_page_size(size, sent.elapsed(), rate_limit.as_ref()));
+        }
         self.num_found = response.num_found;
-        self.start = response.start + 1;
+        self.fetched = true;
+        let expected = self.num_found.min(self.limit.unwrap_or(u64::MAX));
+        self.cursor =
+            if expected > DEEP_PAGING_THRESHOLD && previous.is_some() && next_cursor != previous {
+                next_cursor
+            } else {
+                None
+            };
         self.docs = response.docs.into_iter();
-        Ok(self.docs.next())
+        let doc = self.docs.next();
+        match doc {
+            Some(_) => self.start += 1,
+            None => self.done = true,
+        }
+        Ok(doc)
+    }
+
+    // Send the request for a page, waiting for the rate limit to reset first
+    // if necessary and enabled
+    fn send_waiting(&self, query: &Query<'_>) -> Result<Envelope<Document>> {
+        loop {
+            match query.send_envelope() {
+                Err(AdsError::RateLimited { reset: Some(reset) }) => {
+                    let wait = crate::timestamp::until(&reset)
+                        .filter(|&wait| self.max_wait.is_some_and(|max| wait <= max));
+                    match wait {
+                        // The reset time is only known to the second, so
+                        // allow for some rounding
+                        Some(wait) => {
+                            let wait = wait + Duration::from_secs(1);
+                            match &query.cancel {
+                                Some(cancel) => cancel.sleep(wait)?,
+                                None => std::thread::sleep(wait),
+                            }
+                        }
+                        None => return Err(AdsError::RateLimited { reset: Some(reset) }),
+                    }
+                }
+                result => return result,
+            }
+        }
     }
 }
 
@@ -438,6 +1158,46 @@ impl<'ads> Iterator for IterDocs<'ads> {
             Err(err) => Some(Err(err)),
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        let remaining = self.end().saturating_sub(self.start);
+        let lower = (self.docs.len() as u64).min(remaining) as usize;
+        if self.fetched || self.limit.is_some() {
+            (lower, usize::try_from(remaining).ok())
+        } else {
+            (lower, None)
+        }
+    }
+}
+
+impl<'ads> FusedIterator for IterDocs<'ads> {}
+
+// The size of the page after one of `size` rows that took `latency` to fetch,
+// for adaptive paging
+fn next_page_size(size: u64, latency: Duration, rate_limit: Option<&RateLimit>) -> u64 {
+    // Use as few requests as possible once there are less than 10% left
+    if rate_limit.is_some_and(|limit| limit.remaining * 10 < limit.limit) {
+        MAX_ROWS
+    } else if latency < ADAPTIVE_TARGET_LATENCY {
+        (2 * size).min(MAX_ROWS)
+    } else if latency > 2 * ADAPTIVE_TARGET_LATENCY {
+        (size / 2).max(ADAPTIVE_FIRST_PAGE)
+    } else {
+        size
+    }
+}
+
+// Add the names from a comma separated list of fields, skipping any that are
+// already included
+pub(crate) fn push_fields(fields: &mut Vec<String>, fl: &str) {
+    for name in fl.split(',').map(str::trim) {
+        if !name.is_empty() && !fields.iter().any(|field| field == name) {
+            fields.push(name.to_owned());
+        }
+    }
 }
 
 // Helpers for serialization of search queries:
@@ -449,6 +1209,19 @@ fn fl_defaults<S: serde::Serializer>(items: &[String], serializer: S) -> Result<
     }
 }
 
+// Percent encode everything except the unreserved characters from RFC 3986
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
 fn comma_separated<T: ToString, S: serde::Serializer>(
     items: &[T],
     serializer: S,
@@ -460,7 +1233,6 @@ fn comma_separated<T: ToString, S: serde::Serializer>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Datelike;
 
     #[test]
     fn deserialize_document() {
@@ -478,7 +1250,10 @@ mod tests {
         assert_eq!(response.aff.unwrap()[0], "aff1");
         assert!(matches!(response.database.unwrap()[0], Database::Astronomy));
         assert_eq!(response.entdate.unwrap(), "2021-09-25");
-        assert_eq!(response.indexstamp.unwrap().year(), 2021);
+        assert_eq!(
+            crate::timestamp::to_unix(&response.indexstamp.unwrap()),
+            Some(1_635_062_213)
+        );
     }
 
     #[test]
@@ -501,6 +1276,50 @@ mod tests {
         assert_eq!(response.start, 12);
         assert_eq!(response.docs.len(), 2);
         assert_eq!(response.docs[0].id.as_ref().unwrap(), "312911");
+        assert_eq!(response.next_start(), Some(14));
+        assert!(!response.is_last_page());
+        assert_eq!(response.iter().count(), 2);
+    }
+
+    #[test]
+    fn last_page() {
+        let response: Response =
+            serde_json::from_str(r#"{"numFound": 3, "start": 2, "docs": [{"id": "1"}]}"#).unwrap();
+        assert!(response.is_last_page());
+        assert_eq!(response.next_start(), None);
+        let ids = response
+            .into_iter()
+            .map(|doc| doc.id.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["1"]);
+
+        let empty: Response =
+            serde_json::from_str(r#"{"numFound": 10, "start": 20, "docs": []}"#).unwrap();
+        assert!(empty.is_empty());
+        assert!(empty.is_last_page());
+    }
+
+    #[test]
+    fn deserialize_envelope() {
+        let envelope: Envelope<Document> = serde_json::from_str(
+            r#"{
+                "responseHeader": {"status": 0, "QTime": 3},
+                "response": {"numFound": 1, "start": 0, "docs": [{"id": "312911"}]}
+            }"#,
+        )
+        .unwrap();
+        assert!(envelope.error.is_none());
+        assert_eq!(envelope.response.unwrap().num_found, 1);
+
+        let envelope: Envelope<Document> = serde_json::from_str(
+            r#"{"error": {"msg": "org.apache.solr.search.SyntaxError", "code": 400}}"#,
+        )
+        .unwrap();
+        assert!(envelope.response.is_none());
+        assert_eq!(
+            envelope.error.unwrap().into_message(),
+            "org.apache.solr.search.SyntaxError"
+        );
     }
 
     #[test]
@@ -527,6 +1346,516 @@ mod tests {
         )
     }
 
+    #[test]
+    fn owned_query() {
+        fn assert_static<T: Send + 'static>(value: T) -> T {
+            value
+        }
+        let client = crate::Ads::new("token").unwrap();
+        let query = assert_static(client.search("supernova").rows(5).into_owned());
+        drop(client);
+        assert_eq!(
+            serde_json::to_value(query).unwrap()["q"],
+            serde_json::json!("supernova")
+        );
+    }
+
+    #[test]
+    fn parse_sort() {
+        assert_eq!("date".parse::<Sort>().unwrap(), Sort::desc("date"));
+        assert_eq!(" date  ASC ".parse::<Sort>().unwrap(), Sort::asc("date"));
+        assert_eq!(
+            "citation_count desc".parse::<Sort>().unwrap(),
+            Sort::desc("citation_count")
+        );
+        assert!("".parse::<Sort>().is_err());
+        assert!("date up".parse::<Sort>().is_err());
+        assert!("date asc desc".parse::<Sort>().is_err());
+        for sort in &[Sort::asc("date"), Sort::desc("bibcode")] {
+            assert_eq!(&sort.to_string().parse::<Sort>().unwrap(), sort);
+        }
+        assert_eq!(Sort::from("date asc"), Sort::asc("date"));
+    }
+
+    #[test]
+    fn params_round_trip() {
+        let client = crate::Ads::new("token").unwrap();
+        let query = client
+            .search("au:foreman-mackey")
+            .rows(50)
+            .fl("bibcode,title")
+            .fq("property:refereed")
+            .sort(Sort::asc("date"))
+            .sort("citation_count");
+        let params = query.params();
+        let data = serde_json::to_value(&params).unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({
+                "q": "au:foreman-mackey",
+                "rows": 50,
+                "fl": ["bibcode", "title"],
+                "fq": "property:refereed",
+                "sort": ["date asc", "citation_count desc"],
+            })
+        );
+        let loaded: QueryParams = serde_json::from_value(data).unwrap();
+        assert_eq!(loaded, params);
+        assert_eq!(
+            serde_json::to_value(Query::from_params(&client, loaded)).unwrap(),
+            serde_json::to_value(query).unwrap()
+        );
+
+        let minimal: QueryParams = serde_json::from_str(r#"{"q": "star"}"#).unwrap();
+        assert_eq!(minimal.q, "star");
+        assert!(minimal.fl.is_empty());
+        assert!(
+            serde_json::from_str::<QueryParams>(r#"{"q": "star", "sort": ["date up"]}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn default_fl() {
+        let client = crate::Ads::builder("token")
+            .default_fl("bibcode,doi")
+            .default_fl("citation_count")
+            .build()
+            .unwrap();
+        let fl = |query: Query<'_>| serde_json::to_value(query).unwrap()["fl"].clone();
+        assert_eq!(fl(client.search("star")), "bibcode,doi,citation_count");
+        assert_eq!(fl(client.search("star").fl("title")), "title");
+        assert_eq!(fl(client.search("star").fl("id").fl("title")), "id,title");
+        assert_eq!(
+            fl(client.search("star").fl("id, title").fl("title,id,,doi")),
+            "id,title,doi"
+        );
+        let params = QueryParams {
+            q: "star".to_owned(),
+            ..QueryParams::default()
+        };
+        assert_eq!(
+            fl(Query::from_params(&client, params)),
+            "bibcode,doi,citation_count"
+        );
+    }
+
+    #[test]
+    fn default_sort() {
+        let client = crate::Ads::builder("token")
+            .default_sort("date asc")
+            .build()
+            .unwrap();
+        let sort = |query: Query<'_>| serde_json::to_value(query).unwrap()["sort"].clone();
+        assert_eq!(sort(client.search("star")), "date asc");
+        assert_eq!(
+            sort(client.search("star").sort("citation_count").sort("bibcode")),
+            "citation_count desc,bibcode desc"
+        );
+        let params = QueryParams {
+            q: "star".to_owned(),
+            ..QueryParams::default()
+        };
+        assert_eq!(sort(Query::from_params(&client, params)), "date asc");
+    }
+
+    #[test]
+    fn request_headers() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .header("x-forwarded-for", "203.0.113.1")
+                .header("x-experiment", "on");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 0, "start": 0, "docs": []}}"#);
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let query = client
+            .search("star")
+            .header("X-Forwarded-For", "203.0.113.1")
+            .header("X-Experiment", "on");
+        assert_eq!(query.send().unwrap().num_found, 0);
+        mock.assert();
+        assert!(matches!(
+            client.search("star").header("bad header", "value").send(),
+            Err(AdsError::InvalidHeaderName(_))
+        ));
+    }
+
+    #[test]
+    fn raw_response() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(400)
+                .header("content-type", "application/json")
+                .body(r#"{"error": {"msg": "undefined field foo", "code": 400}}"#);
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let raw = client.search("foo:bar").send_raw().unwrap();
+        assert_eq!(raw.status, 400);
+        assert!(!raw.is_success());
+        assert!(raw.body.contains("undefined field foo"));
+    }
+
+    #[test]
+    fn deep_paging_cursor() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let page = |when: httpmock::When, then: httpmock::Then, cursor, next, bibcode| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("cursorMark", cursor)
+                .query_param("sort", "date desc,id asc");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({
+                    "nextCursorMark": next,
+                    "response": {"numFound": 20000, "start": 0, "docs": [{"bibcode": bibcode}]},
+                }));
+        };
+        let first = server.mock(|when, then| page(when, then, "*", "a", "one"));
+        let second = server.mock(|when, then| page(when, then, "a", "b", "two"));
+        let third = server.mock(|when, then| page(when, then, "b", "c", "three"));
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let docs = client
+            .search("star")
+            .sort("date")
+            .rows(1)
+            .iter_docs()
+            .take(3)
+            .map(|doc| doc.unwrap().bibcode.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(docs, vec!["one", "two", "three"]);
+        first.assert();
+        second.assert();
+        third.assert();
+    }
+
+    #[test]
+    fn resume_cursor_after_error() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let page = |cursor: &'static str, next: &'static str, bibcode: &'static str| {
+            server.mock(move |when, then| {
+                when.method(GET)
+                    .path("/search/query")
+                    .query_param("cursorMark", cursor);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(serde_json::json!({
+                        "nextCursorMark": next,
+                        "response": {"numFound": 20000, "start": 0, "docs": [{"bibcode": bibcode}]},
+                    }));
+            })
+        };
+        let first = page("*", "a", "one");
+        let mut failing = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("cursorMark", "a");
+            then.status(503);
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let mut docs = client.search("star").rows(1).iter_docs();
+        assert_eq!(docs.next().unwrap().unwrap().bibcode.unwrap(), "one");
+        assert!(docs.next().unwrap().is_err());
+        failing.assert();
+        failing.delete();
+
+        // The failed page is retried using the same cursor, not `start`
+        let second = page("a", "b", "two");
+        assert_eq!(docs.next().unwrap().unwrap().bibcode.unwrap(), "two");
+        first.assert();
+        second.assert();
+    }
+
+    #[test]
+    fn tie_breaker_keeps_relevance() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("cursorMark", "*")
+                .query_param("sort", "score desc,id asc");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 1, "start": 0, "docs": [{}]}}"#);
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let docs = client
+            .search("star")
+            .iter_docs()
+            .collect::<Result<Vec<_>>>();
+        assert_eq!(docs.unwrap().len(), 1);
+        mock.assert();
+    }
+
+    #[test]
+    fn iter_docs_size_hint() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 3, "start": 0, "docs": [{}, {}, {}]}}"#);
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let mut docs = client.search("star").iter_docs().limit(2);
+        assert_eq!(docs.size_hint(), (0, Some(2)));
+        assert!(docs.next().unwrap().is_ok());
+        assert_eq!(docs.size_hint(), (1, Some(1)));
+        assert!(docs.next().unwrap().is_ok());
+        assert_eq!(docs.size_hint(), (0, Some(0)));
+        assert!(docs.next().is_none());
+        assert!(docs.next().is_none());
+        mock.assert_hits(1);
+    }
+
+    #[test]
+    fn continue_iteration() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("start", "2");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 3, "start": 2, "docs": [{"bibcode": "c"}]}}"#);
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let first: Response =
+            serde_json::from_str(r#"{"numFound": 3, "start": 0, "docs": [{}, {}]}"#).unwrap();
+        let mut docs = client.search("star").rows(2).continue_from(&first);
+        assert_eq!(docs.num_found(), Some(3));
+        assert_eq!(docs.next().unwrap().unwrap().bibcode.unwrap(), "c");
+        assert!(docs.next().is_none());
+        mock.assert_hits(1);
+
+        let last: Response =
+            serde_json::from_str(r#"{"numFound": 2, "start": 0, "docs": [{}, {}]}"#).unwrap();
+        assert!(client.search("star").continue_from(&last).next().is_none());
+    }
+
+    #[test]
+    fn adaptive_page_size() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let page = |start: u64, rows: u64| {
+            let docs = vec!["{}"; rows as usize].join(",");
+            format!(
+                r#"{{"response": {{"numFound": 5000, "start": {}, "docs": [{}]}}}}"#,
+                start, docs
+            )
+        };
+        let mocks = [(0, 100), (100, 200), (300, 700)]
+            .iter()
+            .map(|&(start, rows)| {
+                let body = page(start, rows);
+                server.mock(move |when, then| {
+                    when.method(GET)
+                        .path("/search/query")
+                        .query_param("start", start.to_string())
+                        .query_param("rows", rows.to_string());
+                    then.status(200)
+                        .header("content-type", "application/json")
+                        .body(body);
+                })
+            })
+            .collect::<Vec<_>>();
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let docs = client
+            .search("star")
+            .iter_docs()
+            .limit(1000)
+            .adaptive_page_size()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(docs.len(), 1000);
+        for mock in &mocks {
+            mock.assert();
+        }
+
+        let fast = Duration::from_millis(100);
+        let slow = Duration::from_secs(10);
+        assert_eq!(next_page_size(100, fast, None), 200);
+        assert_eq!(next_page_size(1500, fast, None), MAX_ROWS);
+        assert_eq!(next_page_size(800, slow, None), 400);
+        assert_eq!(next_page_size(100, slow, None), 100);
+        assert_eq!(next_page_size(400, Duration::from_secs(3), None), 400);
+        let low = RateLimit {
+            limit: 5000,
+            remaining: 20,
+            reset: crate::timestamp::from_unix(0).unwrap(),
+        };
+        assert_eq!(next_page_size(100, slow, Some(&low)), MAX_ROWS);
+    }
+
+    #[test]
+    fn cancel_iter_docs() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"response": {"numFound": 4, "start": 0, "docs": [{}, {}]}}"#);
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let token = CancelToken::new();
+        let mut docs = client
+            .search("star")
+            .rows(2)
+            .iter_docs()
+            .cancel_token(token.clone());
+        assert!(docs.next().unwrap().is_ok());
+        token.cancel();
+        // The page that was already fetched is still returned
+        assert!(docs.next().unwrap().is_ok());
+        assert!(matches!(docs.next(), Some(Err(AdsError::Cancelled))));
+        mock.assert_hits(1);
+
+        let expired = CancelToken::with_deadline(std::time::Instant::now());
+        let err = client
+            .search("star")
+            .cancel_token(expired)
+            .send()
+            .unwrap_err();
+        assert!(matches!(err, AdsError::DeadlineExceeded));
+        mock.assert_hits(1);
+    }
+
+    #[test]
+    fn wait_on_rate_limit() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let mut limited = server.mock(|when, then| {
+            when.method(GET).path("/search/query");
+            then.status(429)
+                .header("content-type", "application/json")
+                .header("retry-after", "2")
+                .body(r#"{"error": "Too many requests"}"#);
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let mut docs = client.search("star").iter_docs();
+        assert!(matches!(
+            docs.next(),
+            Some(Err(AdsError::RateLimited { reset: Some(_) }))
+        ));
+        let mut docs = client
+            .search("star")
+            .iter_docs()
+            .wait_on_rate_limit(Duration::ZERO);
+        assert!(matches!(
+            docs.next(),
+            Some(Err(AdsError::RateLimited { reset: Some(_) }))
+        ));
+        limited.assert_hits(2);
+
+        // Replace the rate limited response once the iterator starts waiting
+        let mut docs = client
+            .search("star")
+            .iter_docs()
+            .wait_on_rate_limit(Duration::from_secs(10));
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                while limited.hits() < 3 {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                limited.delete();
+                server.mock(|when, then| {
+                    when.method(GET).path("/search/query");
+                    then.status(200)
+                        .header("content-type", "application/json")
+                        .body(r#"{"response": {"numFound": 1, "start": 0, "docs": [{"bibcode": "a"}]}}"#);
+                });
+            });
+            assert_eq!(docs.next().unwrap().unwrap().bibcode.unwrap(), "a");
+            assert!(docs.next().is_none());
+        });
+    }
+
+    #[test]
+    fn ui_url() {
+        let client = crate::Ads::new("token").unwrap();
+        let query = client
+            .search("title:\"dark energy\" year:2020")
+            .fq("property:refereed")
+            .sort("citation_count")
+            .sort(Sort::asc("bibcode"));
+        assert_eq!(
+            query.ui_url(),
+            "https://ui.adsabs.harvard.edu/search/q=title%3A%22dark%20energy%22%20year%3A2020\
+             &fq=property%3Arefereed&sort=citation_count%20desc%2C%20bibcode%20asc&p_=0"
+        );
+        assert_eq!(
+            client.search("é").ui_url(),
+            "https://ui.adsabs.harvard.edu/search/q=%C3%A9&p_=0"
+        );
+    }
+
+    #[test]
+    fn property_filters() {
+        let client = crate::Ads::new("token").unwrap();
+        assert_eq!(
+            client.search("star").refereed().fq.as_deref(),
+            Some("property:refereed")
+        );
+        let query = client
+            .search("star")
+            .fq("year:2020")
+            .open_access()
+            .not_article();
+        assert_eq!(
+            query.fq.as_deref(),
+            Some("((year:2020) AND property:openaccess) AND property:nonarticle")
+        );
+        let query = client
+            .search("star")
+            .refereed()
+            .fq("year:2020 OR year:2021");
+        assert_eq!(
+            query.fq.as_deref(),
+            Some("(property:refereed) AND (year:2020 OR year:2021)")
+        );
+    }
+
     #[test]
     fn vec_fls() {
         let client = crate::Ads::new("token").unwrap();
@@ -0,0 +1,178 @@
+use super::Document;
+use std::collections::BTreeMap;
+
+// The separator used by the API for multiple affiliations of one author
+const SEPARATOR: &str = "; ";
+
+// The placeholder used by the API for missing values in `aff` and `aff_id`
+const MISSING: &str = "-";
+
+/// The affiliations of one author of a [`Document`], from the parallel `aff`
+/// and `aff_id` fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorAffiliation<'a> {
+    /// The position of the author in the author list, starting from `0`.
+    pub position: usize,
+    /// The name of the author, if the `author` field was requested.
+    pub author: Option<&'a str>,
+    /// The institutions listed for this author, which may be empty.
+    pub institutions: Vec<Institution<'a>>,
+}
+
+/// A single institution from an affiliation string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Institution<'a> {
+    /// The affiliation as it appears in the record.
+    pub name: &'a str,
+    /// The canonical identifier of the institution, if it is known.
+    pub id: Option<&'a str>,
+}
+
+impl Document {
+    /// Pair up the affiliations of each author with their canonical
+    /// identifiers.
+    ///
+    /// This requires the `aff` field, and the identifiers and author names are
+    /// included if the `aff_id` and `author` fields were also requested.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let docs = client
+    ///     .search("bibcode:2013PASP..125..306F")
+    ///     .fl("author,aff,aff_id")
+    ///     .send()?
+    ///     .docs;
+    /// for affiliation in docs[0].affiliations() {
+    ///     for institution in affiliation.institutions {
+    ///         println!("{:?}: {:?}", affiliation.author, institution.id);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn affiliations(&self) -> Vec<AuthorAffiliation<'_>> {
+        let affs = self.aff.as_deref().unwrap_or_default();
+        let ids = self.aff_id.as_deref().unwrap_or_default();
+        let authors = self.author.as_deref().unwrap_or_default();
+        affs.iter()
+            .enumerate()
+            .map(|(position, aff)| {
+                let mut ids = ids
+                    .get(position)
+                    .map(|ids| ids.split(SEPARATOR))
+                    .into_iter()
+                    .flatten();
+                let institutions = aff
+                    .split(SEPARATOR)
+                    .map(|name| Institution {
+                        name: name.trim(),
+                        id: ids.next().map(str::trim).filter(|id| is_present(id)),
+                    })
+                    .filter(|institution| is_present(institution.name))
+                    .collect();
+                AuthorAffiliation {
+                    position,
+                    author: authors.get(position).map(String::as_str),
+                    institutions,
+                }
+            })
+            .collect()
+    }
+
+    /// The canonical identifiers of all the institutions in this record,
+    /// without duplicates.
+    pub fn institution_ids(&self) -> Vec<&str> {
+        let mut ids = self
+            .aff_id
+            .iter()
+            .flatten()
+            .flat_map(|ids| ids.split(SEPARATOR))
+            .map(str::trim)
+            .filter(|id| is_present(id))
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Group records by the canonical identifiers of their institutions.
+///
+/// Each record is listed once under every institution that appears in its
+/// `aff_id` field, and records without any identified institutions are left
+/// out.
+pub fn group_by_institution<'a, I>(docs: I) -> BTreeMap<&'a str, Vec<&'a Document>>
+where
+    I: IntoIterator<Item = &'a Document>,
+{
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for doc in docs {
+        for id in doc.institution_ids() {
+            groups.entry(id).or_default().push(doc);
+        }
+    }
+    groups
+}
+
+fn is_present(value: &str) -> bool {
+    !value.is_empty() && value != MISSING
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(aff: &[&str], aff_id: &[&str]) -> Document {
+        Document {
+            author: Some(vec!["Hogg, D".to_owned(), "Dalcanton, J".to_owned()]),
+            aff: Some(aff.iter().map(|&s| s.to_owned()).collect()),
+            aff_id: Some(aff_id.iter().map(|&s| s.to_owned()).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pair_affiliations() {
+        let doc = doc(&["NYU; Flatiron", "-"], &["A01; -", "-"]);
+        assert_eq!(
+            doc.affiliations(),
+            vec![
+                AuthorAffiliation {
+                    position: 0,
+                    author: Some("Hogg, D"),
+                    institutions: vec![
+                        Institution {
+                            name: "NYU",
+                            id: Some("A01")
+                        },
+                        Institution {
+                            name: "Flatiron",
+                            id: None
+                        },
+                    ],
+                },
+                AuthorAffiliation {
+                    position: 1,
+                    author: Some("Dalcanton, J"),
+                    institutions: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn group_documents() {
+        let a = doc(&["NYU", "UW"], &["A01", "A02"]);
+        let b = doc(&["UW; NYU", "UW"], &["A02; A01", "A02"]);
+        let c = doc(&["Somewhere"], &["-"]);
+        let docs = vec![a, b, c];
+        let groups = group_by_institution(&docs);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), [&"A01", &"A02"]);
+        assert_eq!(groups["A02"].len(), 2);
+        assert!(std::ptr::eq(groups["A01"][1], &docs[1]));
+    }
+}
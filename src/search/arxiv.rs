@@ -0,0 +1,66 @@
+use super::template::push_value;
+use super::{Query, Sort};
+use crate::Ads;
+
+impl Ads {
+    /// Build a query for the arXiv preprints in the given classes, like
+    /// `astro-ph.EP`, that were added to ADS in the last `days` days, with the
+    /// newest first.
+    ///
+    /// The query can be customized further before it is sent, for example
+    /// using [`Query::fl`] or [`Query::abs`]. If `classes` is empty, all new
+    /// arXiv postings are returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.arxiv_listing(["astro-ph.EP", "astro-ph.SR"], 7);
+    /// let params = query.params();
+    /// assert_eq!(params.q, "bibstem:arXiv");
+    /// assert_eq!(
+    ///     params.fq.unwrap(),
+    ///     "(arxiv_class:(astro-ph.EP OR astro-ph.SR)) AND entdate:[NOW-7DAYS TO *]"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn arxiv_listing<I, S>(&self, classes: I, days: u32) -> Query<'_>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut filter = String::new();
+        for class in classes {
+            filter.push_str(if filter.is_empty() { "(" } else { " OR " });
+            push_value(&mut filter, class.as_ref());
+        }
+        let mut query = self.search("bibstem:arXiv");
+        if !filter.is_empty() {
+            query = query.fq(&format!("arxiv_class:{})", filter));
+        }
+        query
+            .and_fq(&format!("entdate:[NOW-{}DAYS TO *]", days))
+            .sort(Sort::desc("entry_date"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing() {
+        let client = Ads::new("token").unwrap();
+        let query = client.arxiv_listing(Vec::<String>::new(), 1);
+        assert_eq!(query.fq.as_deref(), Some("entdate:[NOW-1DAYS TO *]"));
+        assert_eq!(query.sort[0].to_string(), "entry_date desc");
+        let query = client.arxiv_listing(["astro-ph"], 7);
+        assert_eq!(
+            query.fq.as_deref(),
+            Some("(arxiv_class:(astro-ph)) AND entdate:[NOW-7DAYS TO *]")
+        );
+    }
+}
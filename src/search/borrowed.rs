@@ -0,0 +1,557 @@
+use super::{Database, DocType, Document};
+use crate::Timestamp;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+
+/// A borrowed version of [`Document`], for processing large numbers of
+/// records without allocating a `String` for every field.
+///
+/// The string fields borrow from the response body wherever possible, and
+/// only strings containing escape sequences are copied. This is useful when
+/// harvesting large result sets where most of each record is discarded.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::{search::{DocumentRef, Response}, Ads};
+/// let client = Ads::new("ADS_API_TOKEN")?;
+/// let query = client.search("supernova").fl("bibcode,title").rows(2000);
+/// let body = client.get("search/query", Some(&query))?.bytes()?;
+/// let response: Response<DocumentRef<'_>> = Response::from_slice(&body)?;
+/// for doc in response.docs {
+///     println!("{:?}", doc.bibcode);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(bound(deserialize = "'de: 'a"))]
+#[serde(default)]
+pub struct DocumentRef<'a> {
+    #[serde(rename = "abstract")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub abs: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub ack: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub aff: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub aff_id: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub alternate_bibcode: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub alternate_title: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub arxiv_class: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub author: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::lenient::count"
+    )]
+    pub author_count: Option<u64>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub author_norm: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub bibcode: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub bibgroup: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub bibstem: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub citation: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::lenient::count"
+    )]
+    pub citation_count: Option<u64>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::lenient::score"
+    )]
+    pub cite_read_boost: Option<f32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::lenient::count"
+    )]
+    pub classic_factor: Option<u64>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub comment: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub copyright: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub data: Option<Vec<Cow<'a, str>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<Vec<Database>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::timestamp::option"
+    )]
+    pub date: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doctype: Option<DocType>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub doi: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub eid: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub entdate: Option<Cow<'a, str>>, // YYYY-MM-DD
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::timestamp::option"
+    )]
+    pub entry_date: Option<Timestamp>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub esources: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub facility: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub first_author: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub first_author_norm: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub grant: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub grant_agencies: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub grant_id: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub id: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub identifier: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::timestamp::option"
+    )]
+    pub indexstamp: Option<Timestamp>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub inst: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub isbn: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub issn: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub issue: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub keyword: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub keyword_norm: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub keyword_schema: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub lang: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub links_data: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub nedid: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub nedtype: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub orcid_pub: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub orcid_other: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub orcid_user: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub page: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub page_count: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub page_range: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub property: Option<Vec<Cow<'a, str>>>,
+    #[serde(rename = "pub")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub publication: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub pub_raw: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub pubdate: Option<Cow<'a, str>>, // YYYY-MM-DD
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub pubnote: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::lenient::count"
+    )]
+    pub read_count: Option<u64>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub reference: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub simbid: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub title: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_strs"
+    )]
+    pub vizier: Option<Vec<Cow<'a, str>>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub volume: Option<Cow<'a, str>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_str"
+    )]
+    pub year: Option<Cow<'a, str>>,
+}
+
+impl DocumentRef<'_> {
+    /// Copy all the borrowed fields to build an owned [`Document`].
+    pub fn into_owned(self) -> Document {
+        Document {
+            abs: self.abs.map(Cow::into_owned),
+            ack: self.ack.map(Cow::into_owned),
+            aff: self.aff.map(into_owned_vec),
+            aff_id: self.aff_id.map(into_owned_vec),
+            alternate_bibcode: self.alternate_bibcode.map(into_owned_vec),
+            alternate_title: self.alternate_title.map(into_owned_vec),
+            arxiv_class: self.arxiv_class.map(into_owned_vec),
+            author: self.author.map(into_owned_vec),
+            author_count: self.author_count,
+            author_norm: self.author_norm.map(into_owned_vec),
+            bibcode: self.bibcode.map(Cow::into_owned),
+            bibgroup: self.bibgroup.map(into_owned_vec),
+            bibstem: self.bibstem.map(into_owned_vec),
+            citation: self.citation.map(into_owned_vec),
+            citation_count: self.citation_count,
+            cite_read_boost: self.cite_read_boost,
+            classic_factor: self.classic_factor,
+            comment: self.comment.map(Cow::into_owned),
+            copyright: self.copyright.map(Cow::into_owned),
+            data: self.data.map(into_owned_vec),
+            database: self.database,
+            date: self.date,
+            doctype: self.doctype,
+            doi: self.doi.map(into_owned_vec),
+            eid: self.eid.map(Cow::into_owned),
+            entdate: self.entdate.map(Cow::into_owned),
+            entry_date: self.entry_date,
+            esources: self.esources.map(into_owned_vec),
+            facility: self.facility.map(into_owned_vec),
+            first_author: self.first_author.map(Cow::into_owned),
+            first_author_norm: self.first_author_norm.map(Cow::into_owned),
+            grant: self.grant.map(into_owned_vec),
+            grant_agencies: self.grant_agencies.map(into_owned_vec),
+            grant_id: self.grant_id.map(into_owned_vec),
+            id: self.id.map(Cow::into_owned),
+            identifier: self.identifier.map(into_owned_vec),
+            indexstamp: self.indexstamp,
+            inst: self.inst.map(into_owned_vec),
+            isbn: self.isbn.map(into_owned_vec),
+            issn: self.issn.map(into_owned_vec),
+            issue: self.issue.map(Cow::into_owned),
+            keyword: self.keyword.map(into_owned_vec),
+            keyword_norm: self.keyword_norm.map(into_owned_vec),
+            keyword_schema: self.keyword_schema.map(into_owned_vec),
+            lang: self.lang.map(Cow::into_owned),
+            links_data: self.links_data.map(into_owned_vec),
+            nedid: self.nedid.map(into_owned_vec),
+            nedtype: self.nedtype.map(into_owned_vec),
+            orcid_pub: self.orcid_pub.map(into_owned_vec),
+            orcid_other: self.orcid_other.map(into_owned_vec),
+            orcid_user: self.orcid_user.map(into_owned_vec),
+            page: self.page.map(into_owned_vec),
+            page_count: self.page_count.map(Cow::into_owned),
+            page_range: self.page_range.map(Cow::into_owned),
+            property: self.property.map(into_owned_vec),
+            publication: self.publication.map(Cow::into_owned),
+            pub_raw: self.pub_raw.map(Cow::into_owned),
+            pubdate: self.pubdate.map(Cow::into_owned),
+            pubnote: self.pubnote.map(into_owned_vec),
+            read_count: self.read_count,
+            reference: self.reference.map(into_owned_vec),
+            simbid: self.simbid.map(into_owned_vec),
+            title: self.title.map(into_owned_vec),
+            vizier: self.vizier.map(into_owned_vec),
+            volume: self.volume.map(Cow::into_owned),
+            year: self.year.map(Cow::into_owned),
+        }
+    }
+}
+
+impl From<DocumentRef<'_>> for Document {
+    fn from(doc: DocumentRef<'_>) -> Self {
+        doc.into_owned()
+    }
+}
+
+fn into_owned_vec(values: Vec<Cow<'_, str>>) -> Vec<String> {
+    values.into_iter().map(Cow::into_owned).collect()
+}
+
+/// A string that borrows from the input when it doesn't contain any escapes.
+struct BorrowedStr<'a>(Cow<'a, str>);
+
+impl<'de> Deserialize<'de> for BorrowedStr<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = BorrowedStr<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E> {
+                Ok(BorrowedStr(Cow::Borrowed(value)))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(BorrowedStr(Cow::Owned(value.to_owned())))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+                Ok(BorrowedStr(Cow::Owned(value)))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+fn borrow_str<'de, D>(deserializer: D) -> Result<Option<Cow<'de, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<BorrowedStr<'de>>::deserialize(deserializer)?.map(|s| s.0))
+}
+
+fn borrow_strs<'de, D>(deserializer: D) -> Result<Option<Vec<Cow<'de, str>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Vec<BorrowedStr<'de>>>::deserialize(deserializer)?
+        .map(|values| values.into_iter().map(|s| s.0).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrows_from_input() {
+        let data = r#"{
+            "bibcode": "2013PASP..125..306F",
+            "title": ["emcee: The \"MCMC\" Hammer"],
+            "author": ["Foreman-Mackey, D.", "Hogg, D. W."],
+            "citation_count": 5000
+        }"#;
+        let doc: DocumentRef<'_> = serde_json::from_str(data).unwrap();
+        assert!(matches!(
+            doc.bibcode,
+            Some(Cow::Borrowed("2013PASP..125..306F"))
+        ));
+        assert!(matches!(
+            doc.author.as_ref().unwrap()[1],
+            Cow::Borrowed("Hogg, D. W.")
+        ));
+        assert!(matches!(doc.title.as_ref().unwrap()[0], Cow::Owned(_)));
+        assert_eq!(doc.citation_count, Some(5000));
+
+        let owned = doc.into_owned();
+        let expected: Document = serde_json::from_str(data).unwrap();
+        assert_eq!(
+            serde_json::to_value(owned).unwrap(),
+            serde_json::to_value(expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        let body = br#"{"response": {"numFound": 1, "start": 0, "docs": [{
+            "bibcode": "2013PASP..125..306F",
+            "date": "2013-03-01T00:00:00Z",
+            "entry_date": "2013-02-11T00:00:00Z",
+            "indexstamp": "2021-10-24T07:56:53Z"
+        }]}}"#;
+        let response = crate::search::Response::<DocumentRef<'_>>::from_slice(body).unwrap();
+        let doc = &response.docs[0];
+        assert_eq!(
+            crate::timestamp::to_unix(doc.indexstamp.as_ref().unwrap()),
+            Some(1_635_062_213)
+        );
+        assert!(doc.date.is_some() && doc.entry_date.is_some());
+
+        let owned = doc.clone().into_owned();
+        let expected: Document = serde_json::from_slice(
+            br#"{
+                "bibcode": "2013PASP..125..306F",
+                "date": "2013-03-01T00:00:00Z",
+                "entry_date": "2013-02-11T00:00:00Z",
+                "indexstamp": "2021-10-24T07:56:53Z"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(owned).unwrap(),
+            serde_json::to_value(expected).unwrap()
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use super::Document;
+use std::fmt;
+
+/// The archives that are listed in the `data` field of a [`Document`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum DataArchive {
+    Alma,
+    Ari,
+    Author,
+    Cds,
+    Chandra,
+    Esa,
+    Eso,
+    Gcpd,
+    Heasarc,
+    Herschel,
+    Ines,
+    Irsa,
+    Iso,
+    Koa,
+    Mast,
+    Ned,
+    Noao,
+    Pasa,
+    Pds,
+    Simbad,
+    Tns,
+    Vizier,
+    Xmm,
+    Zenodo,
+    /// An archive that isn't known to this library, with the name used by
+    /// the API.
+    Other(String),
+}
+
+impl DataArchive {
+    /// The name used for this archive by the API, e.g. `"SIMBAD"`.
+    pub fn name(&self) -> &str {
+        match self {
+            DataArchive::Alma => "ALMA",
+            DataArchive::Ari => "ARI",
+            DataArchive::Author => "Author",
+            DataArchive::Cds => "CDS",
+            DataArchive::Chandra => "Chandra",
+            DataArchive::Esa => "ESA",
+            DataArchive::Eso => "ESO",
+            DataArchive::Gcpd => "GCPD",
+            DataArchive::Heasarc => "HEASARC",
+            DataArchive::Herschel => "Herschel",
+            DataArchive::Ines => "INES",
+            DataArchive::Irsa => "IRSA",
+            DataArchive::Iso => "ISO",
+            DataArchive::Koa => "KOA",
+            DataArchive::Mast => "MAST",
+            DataArchive::Ned => "NED",
+            DataArchive::Noao => "NOAO",
+            DataArchive::Pasa => "PASA",
+            DataArchive::Pds => "PDS",
+            DataArchive::Simbad => "SIMBAD",
+            DataArchive::Tns => "TNS",
+            DataArchive::Vizier => "Vizier",
+            DataArchive::Xmm => "XMM",
+            DataArchive::Zenodo => "Zenodo",
+            DataArchive::Other(name) => name,
+        }
+    }
+
+    fn from_name(name: &str) -> Self {
+        match name.to_ascii_uppercase().as_str() {
+            "ALMA" => DataArchive::Alma,
+            "ARI" => DataArchive::Ari,
+            "AUTHOR" => DataArchive::Author,
+            "CDS" => DataArchive::Cds,
+            "CHANDRA" => DataArchive::Chandra,
+            "ESA" => DataArchive::Esa,
+            "ESO" => DataArchive::Eso,
+            "GCPD" => DataArchive::Gcpd,
+            "HEASARC" => DataArchive::Heasarc,
+            "HERSCHEL" => DataArchive::Herschel,
+            "INES" => DataArchive::Ines,
+            "IRSA" => DataArchive::Irsa,
+            "ISO" => DataArchive::Iso,
+            "KOA" => DataArchive::Koa,
+            "MAST" => DataArchive::Mast,
+            "NED" => DataArchive::Ned,
+            "NOAO" => DataArchive::Noao,
+            "PASA" => DataArchive::Pasa,
+            "PDS" => DataArchive::Pds,
+            "SIMBAD" => DataArchive::Simbad,
+            "TNS" => DataArchive::Tns,
+            "VIZIER" => DataArchive::Vizier,
+            "XMM" => DataArchive::Xmm,
+            "ZENODO" => DataArchive::Zenodo,
+            _ => DataArchive::Other(name.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for DataArchive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Document {
+    /// The archives with data products for this record, and the number of
+    /// products in each, parsed from the `data` field.
+    ///
+    /// Entries without a valid count are skipped, and this is empty if the
+    /// `data` field wasn't requested.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use adsabs::search::{DataArchive, Document};
+    /// let doc = Document {
+    ///     data: Some(vec!["CDS:5".to_owned(), "SIMBAD:12".to_owned()]),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     doc.data_products(),
+    ///     [(DataArchive::Cds, 5), (DataArchive::Simbad, 12)]
+    /// );
+    /// ```
+    pub fn data_products(&self) -> Vec<(DataArchive, u64)> {
+        self.data
+            .iter()
+            .flatten()
+            .filter_map(|entry| {
+                let (name, count) = entry.rsplit_once(':')?;
+                let count = count.trim().parse().ok()?;
+                Some((DataArchive::from_name(name.trim()), count))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_data() {
+        let doc = Document {
+            data: Some(
+                [
+                    "NED:1234",
+                    "Vizier:1",
+                    "HEASARC: 2",
+                    "Spitzer:3",
+                    "MAST",
+                    "ESO:x",
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            doc.data_products(),
+            [
+                (DataArchive::Ned, 1234),
+                (DataArchive::Vizier, 1),
+                (DataArchive::Heasarc, 2),
+                (DataArchive::Other("Spitzer".to_owned()), 3),
+            ]
+        );
+        assert_eq!(
+            DataArchive::Other("Spitzer".to_owned()).to_string(),
+            "Spitzer"
+        );
+        assert_eq!(DataArchive::from_name("vizier"), DataArchive::Vizier);
+        assert!(Document::default().data_products().is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use super::Document;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// A field that differs between two versions of a [`Document`], as returned
+/// by [`Document::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// The name of the field, as used in the API.
+    pub field: String,
+    /// The value in the older record, or `None` if the field wasn't present.
+    pub old: Option<Value>,
+    /// The value in the newer record, or `None` if the field wasn't present.
+    pub new: Option<Value>,
+}
+
+impl FieldChange {
+    /// The change in a numeric field like `citation_count`, if both values are
+    /// integers.
+    pub fn delta(&self) -> Option<i64> {
+        let old = self.old.as_ref()?.as_i64()?;
+        let new = self.new.as_ref()?.as_i64()?;
+        Some(new - old)
+    }
+}
+
+impl Document {
+    /// Compare this record to a newer version of the same record, listing the
+    /// fields that have changed in alphabetical order.
+    ///
+    /// Fields that are missing from one of the records are reported as
+    /// changes, so both should be fetched with the same field list.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use adsabs::search::Document;
+    /// let old = Document { citation_count: Some(10), ..Default::default() };
+    /// let new = Document { citation_count: Some(12), ..Default::default() };
+    /// let changes = old.diff(&new);
+    /// assert_eq!(changes[0].field, "citation_count");
+    /// assert_eq!(changes[0].delta(), Some(2));
+    /// ```
+    pub fn diff(&self, newer: &Document) -> Vec<FieldChange> {
+        let old = to_map(self);
+        let new = to_map(newer);
+        old.keys()
+            .chain(new.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|&field| old.get(field) != new.get(field))
+            .map(|field| FieldChange {
+                field: field.clone(),
+                old: old.get(field).cloned(),
+                new: new.get(field).cloned(),
+            })
+            .collect()
+    }
+}
+
+fn to_map(doc: &Document) -> Map<String, Value> {
+    match serde_json::to_value(doc) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_fields() {
+        let old = Document {
+            bibcode: Some("2013PASP..125..306F".to_owned()),
+            title: Some(vec!["emcee".to_owned()]),
+            citation_count: Some(100),
+            ..Default::default()
+        };
+        let new = Document {
+            bibcode: Some("2013PASP..125..306F".to_owned()),
+            title: Some(vec!["emcee: The MCMC Hammer".to_owned()]),
+            citation_count: Some(150),
+            read_count: Some(3),
+            ..Default::default()
+        };
+        let changes = old.diff(&new);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.field.as_str())
+                .collect::<Vec<_>>(),
+            ["citation_count", "read_count", "title"]
+        );
+        assert_eq!(changes[0].delta(), Some(50));
+        assert_eq!(changes[1].old, None);
+        assert_eq!(changes[2].new, Some(json!(["emcee: The MCMC Hammer"])));
+        assert!(new.diff(&new).is_empty());
+    }
+}
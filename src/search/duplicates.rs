@@ -0,0 +1,194 @@
+use super::{DocType, Document};
+use std::collections::HashMap;
+
+/// The fields used by [`Document::same_work`] and [`merge_duplicates`], which
+/// should be requested using [`super::Query::fl`].
+pub const DUPLICATE_FIELDS: &str = "bibcode,alternate_bibcode,doi,identifier,property,doctype";
+
+impl Document {
+    /// Returns `true` if both records describe the same work, such as an
+    /// arXiv preprint and the published article.
+    ///
+    /// Records are matched using their bibcodes and the `alternate_bibcode`,
+    /// `doi`, and `identifier` fields, so those should be requested to get
+    /// reliable results.
+    pub fn same_work(&self, other: &Document) -> bool {
+        let keys = self.work_keys();
+        other.work_keys().iter().any(|key| keys.contains(key))
+    }
+
+    /// Whether the record has been peer reviewed, according to `property`.
+    pub fn is_refereed(&self) -> bool {
+        self.property
+            .iter()
+            .flatten()
+            .any(|property| property.eq_ignore_ascii_case("REFEREED"))
+    }
+
+    // The normalized identifiers that can be shared by different records of
+    // the same work
+    fn work_keys(&self) -> Vec<String> {
+        let mut keys = self
+            .bibcode
+            .iter()
+            .chain(self.alternate_bibcode.iter().flatten())
+            .chain(self.doi.iter().flatten())
+            .chain(self.identifier.iter().flatten())
+            .map(|key| normalize(key))
+            .filter(|key| !key.is_empty())
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+
+    // Used to pick the record that is kept from a set of duplicates, where
+    // larger is better
+    fn preference(&self) -> (bool, bool) {
+        (
+            self.is_refereed(),
+            !matches!(self.doctype, Some(DocType::Eprint) | None),
+        )
+    }
+}
+
+/// Collapse records that describe the same work into a single record, as
+/// determined by [`Document::same_work`].
+///
+/// From each set of duplicates, the refereed record is kept, falling back on
+/// a record that isn't a preprint, and then on the first one. The results are
+/// in the order that each work first appears in `docs`.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::{search::merge_duplicates, Ads};
+/// let client = Ads::new("ADS_API_TOKEN")?;
+/// use adsabs::search::DUPLICATE_FIELDS;
+/// let docs = client
+///     .search("author:\"^Foreman-Mackey, D\"")
+///     .fl("title")
+///     .fl(DUPLICATE_FIELDS)
+///     .iter_docs()
+///     .collect::<adsabs::Result<Vec<_>>>()?;
+/// for doc in merge_duplicates(docs) {
+///     println!("{:?}", doc.title);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn merge_duplicates<I>(docs: I) -> Vec<Document>
+where
+    I: IntoIterator<Item = Document>,
+{
+    let docs = docs.into_iter().collect::<Vec<_>>();
+
+    // Union the records that share any identifier
+    let mut parents = (0..docs.len()).collect::<Vec<_>>();
+    let mut owners = HashMap::new();
+    for (index, doc) in docs.iter().enumerate() {
+        for key in doc.work_keys() {
+            let owner = *owners.entry(key).or_insert(index);
+            let (a, b) = (root(&mut parents, owner), root(&mut parents, index));
+            parents[a.max(b)] = a.min(b);
+        }
+    }
+
+    // Pick the preferred record from each group, ordered by the first record
+    let mut best = HashMap::<usize, usize>::new();
+    for index in 0..docs.len() {
+        let group = root(&mut parents, index);
+        let current = best.entry(group).or_insert(index);
+        if docs[index].preference() > docs[*current].preference() {
+            *current = index;
+        }
+    }
+    let mut keep = best.into_iter().collect::<Vec<_>>();
+    keep.sort_unstable();
+    let keep = keep.into_iter().map(|(_, index)| index).collect::<Vec<_>>();
+
+    let mut docs = docs.into_iter().map(Some).collect::<Vec<_>>();
+    keep.into_iter()
+        .filter_map(|index| docs[index].take())
+        .collect()
+}
+
+fn root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+fn normalize(key: &str) -> String {
+    let key = key.trim().to_lowercase();
+    for prefix in ["arxiv:", "doi:", "https://doi.org/"] {
+        if let Some(rest) = key.strip_prefix(prefix) {
+            return rest.to_owned();
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(bibcode: &str, identifier: &[&str], refereed: bool) -> Document {
+        Document {
+            bibcode: Some(bibcode.to_owned()),
+            identifier: Some(identifier.iter().map(|&s| s.to_owned()).collect()),
+            property: Some(if refereed {
+                vec!["REFEREED".to_owned()]
+            } else {
+                vec!["NOT REFEREED".to_owned()]
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn same_work() {
+        let preprint = doc("2012arXiv1202.3665F", &["arXiv:1202.3665"], false);
+        let published = doc("2013PASP..125..306F", &["ARXIV:1202.3665"], true);
+        let other = doc("2019JOSS....4.1864F", &["10.21105/joss.01864"], true);
+        assert!(preprint.same_work(&published));
+        assert!(!preprint.same_work(&other));
+        assert!(published.is_refereed());
+    }
+
+    #[test]
+    fn merge() {
+        let docs = vec![
+            doc("2012arXiv1202.3665F", &["arXiv:1202.3665"], false),
+            doc("2019JOSS....4.1864F", &["10.21105/joss.01864"], true),
+            doc(
+                "2013PASP..125..306F",
+                &["arXiv:1202.3665", "10.1086/670067"],
+                true,
+            ),
+            doc("2013PASP..125..306X", &["doi:10.1086/670067"], false),
+        ];
+        let merged = merge_duplicates(docs)
+            .into_iter()
+            .map(|doc| doc.bibcode.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(merged, ["2013PASP..125..306F", "2019JOSS....4.1864F"]);
+
+        // A later record can link two groups that were separate until then
+        let docs = vec![
+            doc("2012arXiv1202.3665F", &["arXiv:1202.3665"], false),
+            doc("2013PASP..125..306X", &["10.1086/670067"], false),
+            doc(
+                "2013PASP..125..306F",
+                &["arXiv:1202.3665", "10.1086/670067"],
+                true,
+            ),
+        ];
+        let merged = merge_duplicates(docs);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].bibcode.as_deref(), Some("2013PASP..125..306F"));
+    }
+}
@@ -0,0 +1,128 @@
+use super::Query;
+use crate::error::{AdsError, ApiError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize)]
+struct FacetParams<'a, 'ads> {
+    #[serde(flatten)]
+    query: &'a Query<'ads>,
+    facet: bool,
+    #[serde(rename = "facet.field")]
+    field: &'a str,
+    #[serde(rename = "facet.limit")]
+    limit: i64,
+    #[serde(rename = "facet.mincount")]
+    mincount: u64,
+}
+
+#[derive(Deserialize)]
+struct FacetEnvelope {
+    #[serde(default)]
+    facet_counts: Option<FacetCounts>,
+    #[serde(default)]
+    error: Option<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct FacetCounts {
+    // Solr returns the counts as a flat list of alternating values and counts
+    facet_fields: HashMap<String, Vec<serde_json::Value>>,
+}
+
+impl<'ads> Query<'ads> {
+    /// Count the matching records for each value of a field, without fetching
+    /// any of the records.
+    ///
+    /// Any field that supports faceting can be used, e.g. `year`, `doctype`,
+    /// or `bibstem_facet` for the publication. Values without any matching
+    /// records are skipped, and the results are sorted by value.
+    ///
+    /// # Errors
+    ///
+    /// This fails under the same conditions as [`Query::send`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// for (year, count) in client.search("exoplanet").facet_counts("year")? {
+    ///     println!("{}: {}", year, count);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn facet_counts(&self, field: &str) -> Result<Vec<(String, u64)>> {
+        let mut query = self.clone();
+        query.rows = Some(0);
+        query.start = None;
+        query.cursor_mark = None;
+        query.fl = vec!["id".to_owned()];
+        let params = FacetParams {
+            query: &query,
+            facet: true,
+            field,
+            limit: -1,
+            mincount: 1,
+        };
+        let response =
+            self.client
+                .get_with_headers("search/query", Some(&params), &self.headers)?;
+        let envelope: FacetEnvelope = crate::parse_json(response)?;
+        if let Some(error) = envelope.error {
+            return Err(AdsError::Ads(error.into_message()));
+        }
+        let values = envelope
+            .facet_counts
+            .and_then(|mut counts| counts.facet_fields.remove(field))
+            .unwrap_or_default();
+        let mut counts = values
+            .chunks(2)
+            .filter_map(|pair| {
+                let value = match &pair[0] {
+                    serde_json::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                Some((value, pair.get(1)?.as_u64()?))
+            })
+            .filter(|&(_, count)| count > 0)
+            .collect::<Vec<_>>();
+        counts.sort();
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::prelude::*;
+
+    #[test]
+    fn facet_request() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("q", "star")
+                .query_param("rows", "0")
+                .query_param("facet", "true")
+                .query_param("facet.field", "year")
+                .query_param("facet.limit", "-1")
+                .query_param("facet.mincount", "1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({
+                    "response": {"numFound": 6, "start": 0, "docs": []},
+                    "facet_counts": {"facet_fields": {"year": ["2021", 4, "2019", 2, "2020", 0]}}
+                }));
+        });
+        let client = crate::Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let counts = client.search("star").facet_counts("year").unwrap();
+        mock.assert();
+        assert_eq!(counts, [("2019".to_owned(), 2), ("2021".to_owned(), 4)]);
+    }
+}
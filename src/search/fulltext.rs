@@ -0,0 +1,135 @@
+use super::template::{push_escaped, push_value};
+use super::Query;
+
+impl<'ads> Query<'ads> {
+    // Combine a fielded search term with the existing query, if any
+    pub(super) fn and_term(self, field: &str, value: &str) -> Self {
+        let mut term = format!("{}:", field);
+        push_value(&mut term, value);
+        self.and_clause(term)
+    }
+
+    fn and_clause(mut self, clause: String) -> Self {
+        self.q = if self.q.trim().is_empty() {
+            clause
+        } else {
+            format!("({}) AND {}", self.q, clause)
+        };
+        self
+    }
+
+    /// Search the abstract, title, and keywords for a word or phrase.
+    ///
+    /// Like the other full text helpers, this is combined with the existing
+    /// query using `AND`, and values with more than one word, or with special
+    /// characters, are quoted and escaped. Single words are left unquoted, so
+    /// that ADS can still match synonyms.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.search("year:2020").abs("dark energy").ack("NASA");
+    /// assert_eq!(
+    ///     query.params().q,
+    ///     r#"((year:2020) AND abs:"dark energy") AND ack:NASA"#
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn abs(self, text: &str) -> Self {
+        self.and_term("abs", text)
+    }
+
+    /// Search the body of the full text, not including the acknowledgements.
+    pub fn body(self, text: &str) -> Self {
+        self.and_term("body", text)
+    }
+
+    /// Search the acknowledgements section of the full text.
+    pub fn ack(self, text: &str) -> Self {
+        self.and_term("ack", text)
+    }
+
+    /// Search everything: the full text, acknowledgements, abstract, title,
+    /// and keywords.
+    pub fn full(self, text: &str) -> Self {
+        self.and_term("full", text)
+    }
+
+    /// Search a field for the words of a phrase appearing within `distance`
+    /// words of each other, in any order, using the `~` proximity operator.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.search("").near("abs", "dark energy", 3);
+    /// assert_eq!(query.params().q, r#"abs:"dark energy"~3"#);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn near(self, field: &str, phrase: &str, distance: u32) -> Self {
+        let mut clause = format!("{}:\"", field);
+        push_escaped(&mut clause, phrase);
+        clause.push_str(&format!("\"~{}", distance));
+        self.and_clause(clause)
+    }
+
+    /// Search a field for terms that are spelled similarly to `term`, using
+    /// the `~` fuzzy operator; for example, `author:smith~`.
+    ///
+    /// Any characters in `term` that have a special meaning in queries,
+    /// including spaces, are escaped, so it is searched as a single term.
+    pub fn fuzzy(self, field: &str, term: &str) -> Self {
+        let mut clause = format!("{}:", field);
+        for c in term.chars() {
+            if c.is_whitespace() || "+-&|!(){}[]^\"~*?:\\/".contains(c) {
+                clause.push('\\');
+            }
+            clause.push(c);
+        }
+        clause.push('~');
+        self.and_clause(clause)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Ads;
+
+    #[test]
+    fn fulltext_terms() {
+        let client = Ads::new("token").unwrap();
+        assert_eq!(client.search("").body("exoplanet").q, "body:exoplanet");
+        assert_eq!(
+            client.search("").full("the \"hot\" Jupiter").q,
+            r#"full:"the \"hot\" Jupiter""#
+        );
+        assert_eq!(
+            client.search("a OR b").ack("Kepler mission").q,
+            r#"(a OR b) AND ack:"Kepler mission""#
+        );
+    }
+
+    #[test]
+    fn proximity_and_fuzzy() {
+        let client = Ads::new("token").unwrap();
+        assert_eq!(
+            client.search("").near("title", "a \"b\"", 0).q,
+            r#"title:"a \"b\""~0"#
+        );
+        assert_eq!(
+            client.search("year:2020").fuzzy("author", "smith").q,
+            "(year:2020) AND author:smith~"
+        );
+        assert_eq!(
+            client.search("").fuzzy("title", "x-ray burst").q,
+            r"title:x\-ray\ burst~"
+        );
+    }
+}
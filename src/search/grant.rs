@@ -0,0 +1,109 @@
+use super::Document;
+
+/// A grant acknowledged by a [`Document`], as returned by
+/// [`Document::grants`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grant<'a> {
+    /// The funding agency, e.g. `"NASA"`.
+    pub agency: &'a str,
+    /// The grant number.
+    pub id: &'a str,
+}
+
+impl Document {
+    /// The grants acknowledged by this record, pairing up each agency with
+    /// its grant number.
+    ///
+    /// The parallel `grant_agencies` and `grant_id` fields are used if they
+    /// were requested, and otherwise the combined `grant` field, where each
+    /// entry is an agency followed by a grant number, is split instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use adsabs::search::{Document, Grant};
+    /// let doc = Document {
+    ///     grant: Some(vec!["NASA NNX13AB12C".to_owned()]),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     doc.grants(),
+    ///     [Grant {
+    ///         agency: "NASA",
+    ///         id: "NNX13AB12C"
+    ///     }]
+    /// );
+    /// ```
+    pub fn grants(&self) -> Vec<Grant<'_>> {
+        match (&self.grant_agencies, &self.grant_id) {
+            (Some(agencies), Some(ids)) => agencies
+                .iter()
+                .zip(ids)
+                .map(|(agency, id)| Grant {
+                    agency: agency.trim(),
+                    id: id.trim(),
+                })
+                .collect(),
+            _ => self
+                .grant
+                .iter()
+                .flatten()
+                .filter_map(|grant| {
+                    let (agency, id) = grant.trim().split_once(char::is_whitespace)?;
+                    Some(Grant {
+                        agency,
+                        id: id.trim(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Option<Vec<String>> {
+        Some(values.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn parallel_fields() {
+        let doc = Document {
+            grant: strings(&["ignored"]),
+            grant_agencies: strings(&["NASA", "NSF"]),
+            grant_id: strings(&["NNX13AB12C", "AST-1517237"]),
+            ..Default::default()
+        };
+        assert_eq!(
+            doc.grants(),
+            [
+                Grant {
+                    agency: "NASA",
+                    id: "NNX13AB12C"
+                },
+                Grant {
+                    agency: "NSF",
+                    id: "AST-1517237"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn combined_field() {
+        let doc = Document {
+            grant: strings(&["NSF  AST-1517237", "malformed"]),
+            ..Default::default()
+        };
+        assert_eq!(
+            doc.grants(),
+            [Grant {
+                agency: "NSF",
+                id: "AST-1517237"
+            }]
+        );
+        assert!(Document::default().grants().is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use super::{push_fields, Document, Query, Sort};
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+impl<'ads> Query<'ads> {
+    /// Only return records that were added to ADS on or after `date`, which
+    /// should be formatted as `YYYY-MM-DD`.
+    ///
+    /// This filters on the `entdate` field, and is combined with any filter
+    /// query that has already been set using [`Query::fq`].
+    pub fn new_since(self, date: &str) -> Self {
+        self.and_fq(&format!("entdate:[{} TO *]", date))
+    }
+}
+
+/// The progress of a polling job that fetches newly indexed records.
+///
+/// This remembers the most recent `entdate` seen, along with the bibcodes of
+/// the records already returned for that date, so that each run only returns
+/// records that haven't been seen before. The state can be serialized and
+/// stored between runs.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::{search::HarvestState, Ads};
+/// let client = Ads::new("ADS_API_TOKEN")?;
+/// let mut state = match std::fs::read_to_string("state.json") {
+///     Ok(data) => serde_json::from_str(&data)?,
+///     Err(_) => HarvestState::new("2021-11-01"),
+/// };
+/// for doc in state.fetch(client.search("exoplanet"))? {
+///     println!("{:?}", doc.title);
+/// }
+/// std::fs::write("state.json", serde_json::to_string(&state)?)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarvestState {
+    entdate: String,
+    seen: BTreeSet<String>,
+}
+
+impl HarvestState {
+    /// Start harvesting the records added on or after `date`, formatted as
+    /// `YYYY-MM-DD`.
+    pub fn new(date: &str) -> Self {
+        Self {
+            entdate: date.to_owned(),
+            seen: BTreeSet::new(),
+        }
+    }
+
+    /// The most recent `entdate` seen so far, or the starting date.
+    pub fn entdate(&self) -> &str {
+        &self.entdate
+    }
+
+    /// Restrict `query` to the records added since the last run, sorted by
+    /// the date they were added, and request the fields needed by
+    /// [`HarvestState::observe`] in addition to any others.
+    pub fn query<'ads>(&self, mut query: Query<'ads>) -> Query<'ads> {
+        push_fields(&mut query.fl, "bibcode,entdate");
+        query.fl_is_default = false;
+        // `observe` relies on the records arriving in order of entry date,
+        // so that has to be the first sort key
+        if query.sort_is_default {
+            query.sort.clear();
+            query.sort_is_default = false;
+        }
+        query.sort.retain(|sort| sort.field() != "entry_date");
+        query.sort.insert(0, Sort::asc("entry_date"));
+        query.new_since(&self.entdate)
+    }
+
+    /// Record that `doc` has been returned, and return `true` if it hadn't
+    /// been seen before.
+    ///
+    /// Records without a `bibcode` or `entdate` are never counted as new.
+    pub fn observe(&mut self, doc: &Document) -> bool {
+        let (bibcode, entdate) = match (&doc.bibcode, &doc.entdate) {
+            (Some(bibcode), Some(entdate)) => (bibcode, entdate),
+            _ => return false,
+        };
+        if entdate.as_str() < self.entdate.as_str() {
+            return false;
+        }
+        if entdate.as_str() > self.entdate.as_str() {
+            self.entdate = entdate.clone();
+            self.seen.clear();
+        }
+        self.seen.insert(bibcode.clone())
+    }
+
+    /// Fetch all the records matching `query` that have been added since the
+    /// last run, and update the state.
+    ///
+    /// If this fails partway through, the state includes the records that
+    /// were fetched before the error.
+    pub fn fetch(&mut self, query: Query<'_>) -> Result<Vec<Document>> {
+        let mut docs = Vec::new();
+        for doc in self.query(query).iter_docs() {
+            let doc = doc?;
+            if self.observe(&doc) {
+                docs.push(doc);
+            }
+        }
+        Ok(docs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ads;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn new_since_filter() {
+        let client = Ads::new("token").unwrap();
+        let query = client.search("star").new_since("2021-11-01");
+        assert_eq!(query.fq.as_deref(), Some("entdate:[2021-11-01 TO *]"));
+        let query = client
+            .search("star")
+            .fq("property:refereed")
+            .new_since("2021-11-01");
+        assert_eq!(
+            query.fq.as_deref(),
+            Some("(property:refereed) AND entdate:[2021-11-01 TO *]")
+        );
+    }
+
+    #[test]
+    fn harvest() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("fq", "entdate:[2021-11-01 TO *]")
+                .query_param("fl", "bibcode,title,entdate")
+                .query_param("sort", "entry_date asc,citation_count desc,id asc");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({"response": {
+                    "numFound": 3,
+                    "start": 0,
+                    "docs": [
+                        {"bibcode": "a", "entdate": "2021-11-01"},
+                        {"bibcode": "b", "entdate": "2021-11-02"},
+                        {"bibcode": "c", "entdate": "2021-11-02"},
+                    ],
+                }}));
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let mut state: HarvestState =
+            serde_json::from_str(r#"{"entdate": "2021-11-01", "seen": ["a"]}"#).unwrap();
+        let docs = state
+            .fetch(
+                client
+                    .search("star")
+                    .fl("bibcode,title")
+                    .sort("citation_count"),
+            )
+            .unwrap();
+        mock.assert();
+        assert_eq!(
+            docs.iter()
+                .map(|doc| doc.bibcode.as_deref().unwrap())
+                .collect::<Vec<_>>(),
+            ["b", "c"]
+        );
+        assert_eq!(state.entdate(), "2021-11-02");
+        assert!(!state.observe(&docs[0]));
+    }
+}
@@ -0,0 +1,120 @@
+use super::Document;
+use std::borrow::Cow;
+
+impl Document {
+    /// Decode the HTML entities, like `&amp;` and `&lt;`, that the API uses
+    /// to encode some characters in the text fields.
+    ///
+    /// This applies to the abstract, acknowledgements, titles, authors,
+    /// affiliations, keywords, comments, copyright, and publication fields.
+    /// Entities that aren't recognized are left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use adsabs::search::Document;
+    /// let mut doc = Document {
+    ///     title: Some(vec!["Stars &amp; planets with M &lt; 0.5 M&#8857;".to_owned()]),
+    ///     ..Default::default()
+    /// };
+    /// doc.decode_html();
+    /// assert_eq!(doc.title.unwrap()[0], "Stars & planets with M < 0.5 M⊙");
+    /// ```
+    pub fn decode_html(&mut self) {
+        let mut strings = [
+            &mut self.abs,
+            &mut self.ack,
+            &mut self.comment,
+            &mut self.copyright,
+            &mut self.first_author,
+            &mut self.publication,
+            &mut self.pub_raw,
+        ];
+        for value in strings.iter_mut().filter_map(|value| value.as_mut()) {
+            decode_in_place(value);
+        }
+        let mut lists = [
+            &mut self.aff,
+            &mut self.alternate_title,
+            &mut self.author,
+            &mut self.keyword,
+            &mut self.pubnote,
+            &mut self.title,
+        ];
+        for values in lists.iter_mut().filter_map(|values| values.as_mut()) {
+            values.iter_mut().for_each(decode_in_place);
+        }
+    }
+}
+
+fn decode_in_place(value: &mut String) {
+    if let Cow::Owned(decoded) = decode_entities(value) {
+        *value = decoded;
+    }
+}
+
+/// Decode the named and numeric HTML entities in a string.
+fn decode_entities(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..=end])?, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    Cow::Owned(decoded)
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(|c| c == 'x' || c == 'X') {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return std::char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ndash" => '–',
+        "mdash" => '—',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entities() {
+        assert_eq!(decode_entities("a &amp; b"), "a & b");
+        assert_eq!(decode_entities("&#x3B1;&#946;&quot;"), "αβ\"");
+        assert_eq!(
+            decode_entities("AT&T &unknown; & &#xZZ; &amp"),
+            "AT&T &unknown; & &#xZZ; &amp"
+        );
+        assert!(matches!(decode_entities("plain"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,95 @@
+use super::Document;
+use std::hash::{Hash, Hasher};
+
+// The identifier that equality and hashing are based on
+#[derive(PartialEq, Eq, Hash)]
+enum Key<'a> {
+    Bibcode(&'a str),
+    Id(&'a str),
+    Missing,
+}
+
+impl Document {
+    fn key(&self) -> Key<'_> {
+        match (&self.bibcode, &self.id) {
+            (Some(bibcode), _) => Key::Bibcode(bibcode),
+            (None, Some(id)) => Key::Id(id),
+            (None, None) => Key::Missing,
+        }
+    }
+}
+
+/// Documents are equal if they have the same `bibcode`, and the other fields
+/// are ignored. If the `bibcode` field wasn't requested, documents are
+/// compared by their `id` instead, and documents with neither field are all
+/// equal to each other.
+///
+/// This means that documents can be collected into a
+/// [`std::collections::HashSet`] to remove repeated records, as long as the
+/// `bibcode` or `id` field was requested. To also match different records of
+/// the same work, like a preprint and the published article, use
+/// [`Document::same_work`] or [`super::merge_duplicates`].
+///
+/// # Example
+///
+/// ```rust
+/// use adsabs::search::Document;
+/// use std::collections::HashSet;
+/// let doc = |bibcode: &str, citation_count| Document {
+///     bibcode: Some(bibcode.to_owned()),
+///     citation_count: Some(citation_count),
+///     ..Default::default()
+/// };
+/// let docs: HashSet<_> = vec![
+///     doc("2013PASP..125..306F", 5),
+///     doc("2013PASP..125..306F", 6),
+///     doc("2019JOSS....4.1864F", 1),
+/// ]
+/// .into_iter()
+/// .collect();
+/// assert_eq!(docs.len(), 2);
+/// ```
+impl PartialEq for Document {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Document {}
+
+impl Hash for Document {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn identity() {
+        let doc = |bibcode: Option<&str>, id: Option<&str>| Document {
+            bibcode: bibcode.map(str::to_owned),
+            id: id.map(str::to_owned),
+            ..Default::default()
+        };
+        assert_eq!(doc(Some("a"), Some("1")), doc(Some("a"), Some("2")));
+        assert_ne!(doc(Some("a"), Some("1")), doc(Some("b"), Some("1")));
+        assert_eq!(doc(None, Some("1")), doc(None, Some("1")));
+        assert_ne!(doc(None, Some("a")), doc(Some("a"), None));
+        assert_eq!(doc(None, None), Document::default());
+
+        let docs: HashSet<_> = vec![
+            doc(Some("a"), None),
+            doc(Some("a"), Some("1")),
+            doc(None, Some("1")),
+            doc(None, Some("2")),
+            doc(None, Some("2")),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(docs.len(), 3);
+    }
+}
@@ -0,0 +1,150 @@
+//! Lenient deserializers for the numeric fields of a [`super::Document`].
+//!
+//! Solr occasionally returns these fields as strings, or as floats where an
+//! integer is expected. Rather than failing the whole page of results, values
+//! are converted where possible, and anything else is treated as missing.
+
+use serde::de::{self, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+
+pub(crate) fn count<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    deserializer.deserialize_any(Lenient::<u64>(PhantomData))
+}
+
+pub(crate) fn score<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f32>, D::Error> {
+    deserializer.deserialize_any(Lenient::<f32>(PhantomData))
+}
+
+trait Number: Sized {
+    fn from_u64(value: u64) -> Option<Self>;
+    fn from_i64(value: i64) -> Option<Self>;
+    fn from_f64(value: f64) -> Option<Self>;
+    fn parse(value: &str) -> Option<Self>;
+}
+
+impl Number for u64 {
+    fn from_u64(value: u64) -> Option<Self> {
+        Some(value)
+    }
+
+    fn from_i64(value: i64) -> Option<Self> {
+        if value >= 0 {
+            Some(value as u64)
+        } else {
+            None
+        }
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        if value.is_finite() && value >= 0.0 && value <= u64::MAX as f64 {
+            Some(value.round() as u64)
+        } else {
+            None
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        value
+            .parse()
+            .ok()
+            .or_else(|| value.parse().ok().and_then(Self::from_f64))
+    }
+}
+
+impl Number for f32 {
+    fn from_u64(value: u64) -> Option<Self> {
+        Some(value as f32)
+    }
+
+    fn from_i64(value: i64) -> Option<Self> {
+        Some(value as f32)
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        Some(value as f32)
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+struct Lenient<T>(PhantomData<T>);
+
+impl<'de, T: Number> Visitor<'de> for Lenient<T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a number")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(T::from_u64(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(T::from_i64(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(T::from_f64(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(T::parse(value.trim()))
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(None)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Document;
+
+    #[test]
+    fn lenient_numbers() {
+        let doc: Document = serde_json::from_str(
+            r#"{
+                "citation_count": "12",
+                "read_count": 7.0,
+                "author_count": "unknown",
+                "classic_factor": null,
+                "cite_read_boost": "0.25"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(doc.citation_count, Some(12));
+        assert_eq!(doc.read_count, Some(7));
+        assert_eq!(doc.author_count, None);
+        assert_eq!(doc.classic_factor, None);
+        assert_eq!(doc.cite_read_boost, Some(0.25));
+
+        let doc: Document = serde_json::from_str("{}").unwrap();
+        assert_eq!(doc.citation_count, None);
+    }
+}
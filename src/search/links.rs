@@ -0,0 +1,245 @@
+use super::{encode_component, Document, UI_URL};
+use serde::Deserialize;
+
+/// The pages for a record in the ADS web interface, used with
+/// [`Document::ads_url_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AbstractTab {
+    Abstract,
+    Citations,
+    References,
+    Coreads,
+    Similar,
+    Metrics,
+    Graphics,
+    ExportCitation,
+}
+
+impl AbstractTab {
+    fn path(self) -> &'static str {
+        match self {
+            AbstractTab::Abstract => "abstract",
+            AbstractTab::Citations => "citations",
+            AbstractTab::References => "references",
+            AbstractTab::Coreads => "coreads",
+            AbstractTab::Similar => "similar",
+            AbstractTab::Metrics => "metrics",
+            AbstractTab::Graphics => "graphics",
+            AbstractTab::ExportCitation => "exportcitation",
+        }
+    }
+}
+
+/// The kinds of full text links, in order of preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum LinkKind {
+    /// The PDF from the publisher.
+    PublisherPdf,
+    /// The PDF of the preprint, usually from arXiv.
+    EprintPdf,
+    /// A PDF hosted by ADS.
+    AdsPdf,
+    /// A scan of the printed article hosted by ADS.
+    AdsScan,
+    /// A PDF provided by the authors.
+    AuthorPdf,
+    /// The article on the publisher's website.
+    PublisherHtml,
+    /// The preprint landing page, usually on arXiv.
+    EprintHtml,
+    /// A web page provided by the authors.
+    AuthorHtml,
+}
+
+impl LinkKind {
+    fn from_esource(esource: &str) -> Option<Self> {
+        Some(match esource {
+            "PUB_PDF" => LinkKind::PublisherPdf,
+            "EPRINT_PDF" => LinkKind::EprintPdf,
+            "ADS_PDF" => LinkKind::AdsPdf,
+            "ADS_SCAN" => LinkKind::AdsScan,
+            "AUTHOR_PDF" => LinkKind::AuthorPdf,
+            "PUB_HTML" => LinkKind::PublisherHtml,
+            "EPRINT_HTML" => LinkKind::EprintHtml,
+            "AUTHOR_HTML" => LinkKind::AuthorHtml,
+            _ => return None,
+        })
+    }
+
+    // The link types used by the older `links_data` field
+    fn from_links_data(kind: &str) -> Option<Self> {
+        Some(match kind {
+            "pdf" => LinkKind::PublisherPdf,
+            "gif" => LinkKind::AdsScan,
+            "electr" => LinkKind::PublisherHtml,
+            "preprint" => LinkKind::EprintHtml,
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` for links to PDF files and scans.
+    pub fn is_pdf(self) -> bool {
+        self <= LinkKind::AuthorPdf
+    }
+}
+
+/// A link to the full text of a record, as returned by
+/// [`Document::fulltext_links`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FulltextLink {
+    /// The kind of link.
+    pub kind: LinkKind,
+    /// The URL, which is usually a redirect through the ADS link gateway.
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct LinksData {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+}
+
+impl Document {
+    /// The link to the abstract page for this record in the ADS web interface.
+    ///
+    /// Returns `None` if the `bibcode` field wasn't requested.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use adsabs::search::Document;
+    /// let doc = Document {
+    ///     bibcode: Some("2013PASP..125..306F".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     doc.ads_url().unwrap(),
+    ///     "https://ui.adsabs.harvard.edu/abs/2013PASP..125..306F/abstract"
+    /// );
+    /// ```
+    pub fn ads_url(&self) -> Option<String> {
+        self.ads_url_for(AbstractTab::Abstract)
+    }
+
+    /// The link to a page for this record in the ADS web interface, like the
+    /// list of citations.
+    pub fn ads_url_for(&self, tab: AbstractTab) -> Option<String> {
+        let bibcode = self.bibcode.as_deref()?;
+        Some(format!(
+            "{}abs/{}/{}",
+            UI_URL,
+            encode_component(bibcode),
+            tab.path()
+        ))
+    }
+
+    /// The available full text links for this record, with the most useful
+    /// first, as ordered by [`LinkKind`].
+    ///
+    /// Links are built from the `esources` field using the ADS link gateway,
+    /// and the direct links in `links_data` are used for anything that isn't
+    /// covered by `esources`. Both fields, and `bibcode`, should be requested.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use adsabs::search::{Document, LinkKind};
+    /// let doc = Document {
+    ///     bibcode: Some("2013PASP..125..306F".to_owned()),
+    ///     esources: Some(vec!["EPRINT_HTML".to_owned(), "PUB_PDF".to_owned()]),
+    ///     ..Default::default()
+    /// };
+    /// let links = doc.fulltext_links();
+    /// assert_eq!(links[0].kind, LinkKind::PublisherPdf);
+    /// assert_eq!(
+    ///     links[0].url,
+    ///     "https://ui.adsabs.harvard.edu/link_gateway/2013PASP..125..306F/PUB_PDF"
+    /// );
+    /// ```
+    pub fn fulltext_links(&self) -> Vec<FulltextLink> {
+        let mut links = Vec::<FulltextLink>::new();
+        if let Some(bibcode) = &self.bibcode {
+            for esource in self.esources.iter().flatten() {
+                if let Some(kind) = LinkKind::from_esource(esource) {
+                    links.push(FulltextLink {
+                        kind,
+                        url: format!(
+                            "{}link_gateway/{}/{}",
+                            UI_URL,
+                            encode_component(bibcode),
+                            esource
+                        ),
+                    });
+                }
+            }
+        }
+        for data in self.links_data.iter().flatten() {
+            let data = match serde_json::from_str::<LinksData>(data) {
+                Ok(data) if !data.url.is_empty() => data,
+                _ => continue,
+            };
+            if let Some(kind) = LinkKind::from_links_data(&data.kind) {
+                if !links.iter().any(|link| link.kind == kind) {
+                    links.push(FulltextLink {
+                        kind,
+                        url: data.url,
+                    });
+                }
+            }
+        }
+        links.sort_by_key(|link| link.kind);
+        links.dedup_by_key(|link| link.kind);
+        links
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abstract_urls() {
+        let doc = Document {
+            bibcode: Some("2020A&A...641A...6P".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            doc.ads_url_for(AbstractTab::Citations).unwrap(),
+            "https://ui.adsabs.harvard.edu/abs/2020A%26A...641A...6P/citations"
+        );
+        assert_eq!(Document::default().ads_url(), None);
+    }
+
+    #[test]
+    fn fulltext() {
+        let doc = Document {
+            bibcode: Some("1990ApJ...356..359H".to_owned()),
+            esources: Some(vec!["ADS_SCAN".to_owned(), "UNKNOWN".to_owned()]),
+            links_data: Some(vec![
+                r#"{"access": "", "type": "gif", "url": "http://example.com/scan"}"#.to_owned(),
+                r#"{"access": "", "type": "electr", "url": "http://example.com/html"}"#.to_owned(),
+                "not json".to_owned(),
+            ]),
+            ..Default::default()
+        };
+        let links = doc.fulltext_links();
+        assert_eq!(
+            links
+                .iter()
+                .map(|link| (link.kind, link.url.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    LinkKind::AdsScan,
+                    "https://ui.adsabs.harvard.edu/link_gateway/1990ApJ...356..359H/ADS_SCAN"
+                ),
+                (LinkKind::PublisherHtml, "http://example.com/html"),
+            ]
+        );
+        assert!(links[0].kind.is_pdf());
+        assert!(!links[1].kind.is_pdf());
+    }
+}
@@ -0,0 +1,105 @@
+use super::Query;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+impl<'ads> Query<'ads> {
+    /// Clean up characters in the query and filter query that often come from
+    /// copying text out of PDFs or word processors, and that can silently
+    /// change the results of a search.
+    ///
+    /// The text is normalized to Unicode NFC, curly quotes are replaced by
+    /// straight quotes, Unicode dashes and spaces by their ASCII versions,
+    /// and ligatures like `ﬁ` by separate letters. Invisible characters like
+    /// zero width spaces and soft hyphens are removed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.search("title:“ﬁrst light” author:“Ge\u{301}za”").normalize();
+    /// assert_eq!(query.params().q, "title:\"first light\" author:\"Géza\"");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn normalize(mut self) -> Self {
+        self.q = normalize(&self.q);
+        self.fq = self.fq.as_deref().map(normalize);
+        self
+    }
+
+    /// Remove accents and other diacritics from the letters in the query and
+    /// filter query, e.g. so that `Gödel` searches for `Godel`.
+    ///
+    /// ADS already matches author names with and without diacritics, so this
+    /// is mostly useful for other fields. Letters like `ø` and `ł`, which
+    /// aren't accented versions of other letters in Unicode, are also
+    /// replaced.
+    pub fn strip_diacritics(mut self) -> Self {
+        self.q = strip_diacritics(&self.q);
+        self.fq = self.fq.as_deref().map(strip_diacritics);
+        self
+    }
+}
+
+fn normalize(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    for c in text.nfc() {
+        match c {
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' | '\u{ab}'
+            | '\u{bb}' => normalized.push('"'),
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' => normalized.push('\''),
+            '\u{2010}'..='\u{2015}' | '\u{2212}' => normalized.push('-'),
+            '\u{a0}' | '\u{2000}'..='\u{200a}' | '\u{202f}' | '\u{205f}' | '\u{3000}' => {
+                normalized.push(' ')
+            }
+            '\u{ad}' | '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}' => {}
+            '\u{fb00}'..='\u{fb06}' => normalized.extend(c.to_string().nfkc()),
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+fn strip_diacritics(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    for c in text.nfd().filter(|&c| !is_combining_mark(c)) {
+        match c {
+            'ø' => stripped.push('o'),
+            'Ø' => stripped.push('O'),
+            'ł' => stripped.push('l'),
+            'Ł' => stripped.push('L'),
+            'đ' => stripped.push('d'),
+            'Đ' => stripped.push('D'),
+            'ı' => stripped.push('i'),
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_text() {
+        assert_eq!(
+            normalize("‘a’ — b\u{a0}c\u{200b}d co\u{ad}op ﬂux"),
+            "'a' - b cd coop flux"
+        );
+        assert_eq!(normalize("e\u{301}"), "é");
+    }
+
+    #[test]
+    fn strip() {
+        let client = crate::Ads::new("token").unwrap();
+        let query = client
+            .search("title:Gödel")
+            .fq("aff:Łódź")
+            .strip_diacritics();
+        assert_eq!(query.q, "title:Godel");
+        assert_eq!(query.fq.as_deref(), Some("aff:Lodz"));
+    }
+}
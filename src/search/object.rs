@@ -0,0 +1,86 @@
+use super::Query;
+use crate::error::Result;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Translated {
+    query: String,
+}
+
+impl<'ads> Query<'ads> {
+    /// Search for records about an astronomical object, like `M 31` or
+    /// `Andromeda`, using the `object:` field.
+    ///
+    /// This is combined with the existing query using `AND`, like
+    /// [`Query::abs`]. The object name is only matched against the text of
+    /// records unless the query is passed through the objects service using
+    /// [`Query::resolve_objects`] before it is sent.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// let query = client.search("year:2020").object("M 31").resolve_objects()?;
+    /// for doc in query.iter_docs().limit(10) {
+    ///     println!("{:?}", doc?.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn object(self, name: &str) -> Self {
+        self.and_term("object", name)
+    }
+
+    /// Translate any `object:` terms in the query using the ADS objects
+    /// service, which resolves the names using SIMBAD and NED.
+    ///
+    /// The translated query matches records that are tagged with the object
+    /// in either database, as well as those that mention it in the abstract,
+    /// in the same way as object searches in the ADS web interface.
+    ///
+    /// # Errors
+    ///
+    /// This fails under the same conditions as [`Query::send`].
+    pub fn resolve_objects(mut self) -> Result<Self> {
+        let translated: Translated = self
+            .client
+            .post_json("objects/query", &serde_json::json!({ "query": [self.q] }))?;
+        self.q = translated.query;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Ads;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn resolve_object() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/objects/query")
+                .json_body(serde_json::json!({"query": ["object:\"M 31\""]}));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({
+                    "query": "((=abs:\"M 31\" OR simbid:1575544 OR nedid:MESSIER_031) database:astronomy)"
+                }));
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let query = client.search("").object("M 31");
+        assert_eq!(query.q, "object:\"M 31\"");
+        let query = query.resolve_objects().unwrap();
+        mock.assert();
+        assert_eq!(
+            query.q,
+            "((=abs:\"M 31\" OR simbid:1575544 OR nedid:MESSIER_031) database:astronomy)"
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use super::Query;
+use crate::error::{AdsError, Result};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// A search query with `{name}` placeholders, which are filled in with
+/// escaped values to build a [`Query`].
+///
+/// Values substituted inside double quotes have any quotes and backslashes
+/// escaped. Elsewhere, values are used as they are if they only contain
+/// letters, digits, `-`, `.`, and `_`, and are quoted otherwise, so a value
+/// can't change the structure of the query. Literal braces are written as
+/// `{{` and `}}`.
+///
+/// Templates are serialized as strings, so they can be stored in configuration
+/// files alongside [`super::QueryParams`].
+///
+/// # Example
+///
+/// ```rust
+/// # fn run() -> adsabs::Result<()> {
+/// use adsabs::search::QueryTemplate;
+/// let template: QueryTemplate = r#"author:"^{author}" year:{year}"#.parse()?;
+/// let query = template.render(&[("author", "O'Brien, \"K\""), ("year", "2020-2023")])?;
+/// assert_eq!(query, r#"author:"^O'Brien, \"K\"" year:2020-2023"#);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct QueryTemplate {
+    template: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder { name: String, quoted: bool },
+}
+
+impl QueryTemplate {
+    /// Parse a template, failing with [`AdsError::InvalidTemplate`] if it has
+    /// unmatched or empty braces.
+    pub fn new(template: &str) -> Result<Self> {
+        let invalid = || AdsError::InvalidTemplate(template.to_owned());
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut quoted = false;
+        let mut escaped = false;
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => return Err(invalid()),
+                            Some(c) => name.push(c),
+                        }
+                    }
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return Err(invalid());
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Placeholder {
+                        name: name.to_owned(),
+                        quoted,
+                    });
+                }
+                '}' => return Err(invalid()),
+                _ => {
+                    if c == '"' && !escaped {
+                        quoted = !quoted;
+                    }
+                    escaped = c == '\\' && !escaped;
+                    literal.push(c);
+                }
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self {
+            template: template.to_owned(),
+            segments,
+        })
+    }
+
+    /// The names of the placeholders, in the order that they first appear.
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for segment in &self.segments {
+            if let Segment::Placeholder { name, .. } = segment {
+                if !names.contains(&name.as_str()) {
+                    names.push(name.as_str());
+                }
+            }
+        }
+        names
+    }
+
+    /// Fill in the placeholders with the matching values from `params`.
+    ///
+    /// If a name appears more than once in `params`, the last value is used.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`AdsError::MissingParameter`] if there is no value for one
+    /// of the placeholders.
+    pub fn render<K, V>(&self, params: &[(K, V)]) -> Result<String>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut query = String::with_capacity(self.template.len());
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => query.push_str(literal),
+                Segment::Placeholder { name, quoted } => {
+                    let value = params
+                        .iter()
+                        .rev()
+                        .find(|(key, _)| key.as_ref() == name)
+                        .map(|(_, value)| value.as_ref())
+                        .ok_or_else(|| AdsError::MissingParameter(name.clone()))?;
+                    if *quoted {
+                        push_escaped(&mut query, value);
+                    } else {
+                        push_value(&mut query, value);
+                    }
+                }
+            }
+        }
+        Ok(query)
+    }
+
+    /// Fill in the placeholders, as in [`QueryTemplate::render`], and build a
+    /// query using `client`.
+    pub fn query<'ads, K, V>(
+        &self,
+        client: &'ads crate::Ads,
+        params: &[(K, V)],
+    ) -> Result<Query<'ads>>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        Ok(client.search(&self.render(params)?))
+    }
+}
+
+impl FromStr for QueryTemplate {
+    type Err = AdsError;
+
+    fn from_str(template: &str) -> Result<Self> {
+        Self::new(template)
+    }
+}
+
+impl TryFrom<String> for QueryTemplate {
+    type Error = AdsError;
+
+    fn try_from(template: String) -> Result<Self> {
+        Self::new(&template)
+    }
+}
+
+impl From<QueryTemplate> for String {
+    fn from(template: QueryTemplate) -> Self {
+        template.template
+    }
+}
+
+impl fmt::Display for QueryTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+fn is_plain(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '.' | '_')
+}
+
+// Add a value to a query, quoting it unless it's a single plain term
+pub(super) fn push_value(query: &mut String, value: &str) {
+    if !value.is_empty() && value.chars().all(is_plain) {
+        query.push_str(value);
+    } else {
+        query.push('"');
+        push_escaped(query, value);
+        query.push('"');
+    }
+}
+
+pub(super) fn push_escaped(query: &mut String, value: &str) {
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            query.push('\\');
+        }
+        query.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_template() {
+        let template =
+            QueryTemplate::new("author:\"^{author}\" year:{year} {{x}} {author}").unwrap();
+        assert_eq!(template.placeholders(), ["author", "year"]);
+        let json = serde_json::to_string(&template).unwrap();
+        assert_eq!(
+            serde_json::from_str::<QueryTemplate>(&json).unwrap(),
+            template
+        );
+        for invalid in &["author:{", "author:}", "year:{}", "{a{b}}"] {
+            assert!(matches!(
+                QueryTemplate::new(invalid),
+                Err(AdsError::InvalidTemplate(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn render_escaped() {
+        let template: QueryTemplate = "title:\"{title}\" abs:{abs} year:{year}".parse().unwrap();
+        assert_eq!(
+            template
+                .render(&[
+                    ("title", "a \"b\" \\c"),
+                    ("abs", "x OR y"),
+                    ("year", "2020")
+                ])
+                .unwrap(),
+            r#"title:"a \"b\" \\c" abs:"x OR y" year:2020"#
+        );
+        assert!(matches!(
+            template.render(&[("title", "a")]),
+            Err(AdsError::MissingParameter(name)) if name == "abs"
+        ));
+        let template: QueryTemplate = r#"title:"\"{title}" {abs}"#.parse().unwrap();
+        assert_eq!(
+            template.render(&[("title", "a b"), ("abs", "")]).unwrap(),
+            r#"title:"\"a b" """#
+        );
+    }
+}
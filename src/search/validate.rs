@@ -0,0 +1,315 @@
+use super::Query;
+use crate::error::{AdsError, ErrorBody, Result};
+
+// The fields that can be searched, from
+// https://ui.adsabs.harvard.edu/help/search/comprehensive-solr-term-list
+const FIELDS: &[&str] = &[
+    "abs",
+    "abstract",
+    "ack",
+    "aff",
+    "aff_id",
+    "alternate_bibcode",
+    "alternate_title",
+    "arxiv",
+    "arxiv_class",
+    "author",
+    "author_count",
+    "author_norm",
+    "bibcode",
+    "bibgroup",
+    "bibstem",
+    "body",
+    "book_author",
+    "caption",
+    "citation",
+    "citation_count",
+    "citation_count_norm",
+    "cite_read_boost",
+    "classic_factor",
+    "collection",
+    "copyright",
+    "credit",
+    "credit_count",
+    "data",
+    "database",
+    "date",
+    "doctype",
+    "doi",
+    "editor",
+    "eid",
+    "entdate",
+    "entry_date",
+    "esources",
+    "facility",
+    "first_author",
+    "first_author_norm",
+    "full",
+    "grant",
+    "has",
+    "id",
+    "identifier",
+    "indexstamp",
+    "inst",
+    "issn",
+    "isbn",
+    "issue",
+    "keyword",
+    "keyword_norm",
+    "keyword_schema",
+    "lang",
+    "mention",
+    "mention_count",
+    "nedid",
+    "nedtype",
+    "object",
+    "orcid",
+    "orcid_other",
+    "orcid_pub",
+    "orcid_user",
+    "page",
+    "page_count",
+    "property",
+    "pub",
+    "pub_raw",
+    "pubdate",
+    "pubnote",
+    "read_count",
+    "reference",
+    "simbid",
+    "simbtype",
+    "title",
+    "uat",
+    "vizier",
+    "volume",
+    "year",
+];
+
+// Fields that can be returned using `fl`, in addition to the searchable fields
+const RETURNED_FIELDS: &[&str] = &[
+    "*",
+    "comment",
+    "grant_agencies",
+    "grant_id",
+    "links_data",
+    "page_range",
+    "score",
+];
+
+impl<'ads> Query<'ads> {
+    /// Check the query and filter query for common syntax errors, without
+    /// making any requests.
+    ///
+    /// This catches unbalanced quotes, parentheses, and brackets, field
+    /// prefixes that aren't searchable fields, like `auhtor:`, and requested
+    /// fields that don't exist, like `citaton_count` in [`Query::fl`], which
+    /// the API would silently leave out. Queries that pass these checks can
+    /// still be rejected by the API, so
+    /// [`Query::validate_remote`] can be used for a complete check.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`AdsError::InvalidQuery`] describing the first problem
+    /// found.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> adsabs::Result<()> {
+    /// use adsabs::Ads;
+    /// let client = Ads::new("ADS_API_TOKEN")?;
+    /// assert!(client.search("author:\"Hogg, D\" year:[2010 TO 2020]").validate().is_ok());
+    /// assert!(client.search("title:\"dark energy").validate().is_err());
+    /// assert!(client.search("auhtor:Hogg").validate().is_err());
+    /// assert!(client.search("star").fl("citaton_count").validate().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        check(&self.q).map_err(|msg| AdsError::InvalidQuery(format!("{} in q", msg)))?;
+        if let Some(fq) = &self.fq {
+            check(fq).map_err(|msg| AdsError::InvalidQuery(format!("{} in fq", msg)))?;
+        }
+        if let Some(field) = self.fl.iter().find(|field| !is_returned(field)) {
+            return Err(AdsError::InvalidQuery(format!(
+                "unknown field '{}' in fl",
+                field
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check the query locally using [`Query::validate`], and then using the
+    /// API's query parser, via the `search/qtree` endpoint.
+    ///
+    /// This uses one request, but doesn't run the search.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`AdsError::InvalidQuery`] if the query can't be parsed,
+    /// and otherwise under the same conditions as [`Query::send`].
+    pub fn validate_remote(&self) -> Result<()> {
+        self.validate()?;
+        let response = self
+            .client
+            .get_with_headers("search/qtree", Some(self), &self.headers)?;
+        if response.status() == reqwest::StatusCode::BAD_REQUEST {
+            if let Ok(body) = response.json::<ErrorBody>() {
+                return Err(AdsError::InvalidQuery(body.error.into_message()));
+            }
+            return Err(AdsError::InvalidQuery(self.q.clone()));
+        }
+        let _: serde_json::Value = crate::parse_json(response)?;
+        Ok(())
+    }
+}
+
+fn is_returned(field: &str) -> bool {
+    FIELDS.contains(&field) || RETURNED_FIELDS.contains(&field)
+}
+
+// Find the first syntax error in a query string, if any
+fn check(query: &str) -> std::result::Result<(), String> {
+    let mut groups: Vec<(char, usize)> = Vec::new();
+    let mut quote = None;
+    let mut escaped = false;
+    let mut in_value = false;
+    let mut word = String::new();
+    for (position, c) in query.chars().enumerate() {
+        let position = position + 1;
+        if escaped {
+            escaped = false;
+            word.clear();
+            continue;
+        }
+        if c == '\\' {
+            escaped = true;
+            word.clear();
+            continue;
+        }
+        if quote.is_some() {
+            if c == '"' {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' => quote = Some(position),
+            '(' | '[' | '{' => groups.push((c, position)),
+            ')' | ']' | '}' => match groups.pop() {
+                // Ranges can be inclusive or exclusive at either end
+                Some(('(', _)) if c == ')' => {}
+                Some(('[', _)) | Some(('{', _)) if c != ')' => {}
+                _ => return Err(format!("unmatched '{}' at character {}", c, position)),
+            },
+            ':' => {
+                // Colons in values, like `arXiv:1202.3665`, and in ranges
+                // of timestamps aren't field prefixes
+                let in_range = groups.iter().any(|(open, _)| *open != '(');
+                if !in_value && !in_range && !word.is_empty() {
+                    let field = word.to_lowercase();
+                    if !FIELDS.contains(&field.as_str()) {
+                        return Err(format!("unknown field '{}'", word));
+                    }
+                }
+                in_value = true;
+            }
+            _ => {}
+        }
+        if c.is_whitespace() || c == '(' {
+            in_value = false;
+        }
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+        } else {
+            word.clear();
+        }
+    }
+    if let Some(position) = quote {
+        return Err(format!("unclosed quote at character {}", position));
+    }
+    if let Some((open, position)) = groups.pop() {
+        return Err(format!("unclosed '{}' at character {}", open, position));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ads;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn local_checks() {
+        for valid in &[
+            "",
+            "*:*",
+            "=author:\"^Hogg, D\" -title:(a OR \"b) c\")",
+            "identifier:arXiv:1202.3665 year:[2010 TO 2020}",
+            "indexstamp:[2021-10-24T07:56:53Z TO NOW] citations(bibcode:x)",
+            "title:\"say \\\"hi\\\"\" abs:a\\:b",
+        ] {
+            assert_eq!(check(valid), Ok(()), "{}", valid);
+        }
+        assert_eq!(
+            check("title:\"dark energy"),
+            Err("unclosed quote at character 7".to_owned())
+        );
+        assert_eq!(
+            check("(a OR b"),
+            Err("unclosed '(' at character 1".to_owned())
+        );
+        assert_eq!(
+            check("year:[2010 TO 2020)"),
+            Err("unmatched ')' at character 19".to_owned())
+        );
+        assert_eq!(
+            check("a OR Auhtor:x"),
+            Err("unknown field 'Auhtor'".to_owned())
+        );
+
+        let client = Ads::new("token").unwrap();
+        let err = client.search("star").fq("a)").validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid query: unmatched ')' at character 2 in fq"
+        );
+        assert!(client
+            .search("star")
+            .fl("bibcode,comment,citation_count")
+            .validate()
+            .is_ok());
+        let err = client
+            .search("star")
+            .fl("bibcode,citaton_count")
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid query: unknown field 'citaton_count' in fl"
+        );
+    }
+
+    #[test]
+    fn remote_check() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/qtree")
+                .query_param("q", "title:a~b~");
+            then.status(400)
+                .header("content-type", "application/json")
+                .body(
+                    r#"{"error": {"msg": "org.apache.solr.search.SyntaxError: ...", "code": 400}}"#,
+                );
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let err = client.search("title:a~b~").validate_remote().unwrap_err();
+        mock.assert();
+        assert!(matches!(err, AdsError::InvalidQuery(msg) if msg.contains("SyntaxError")));
+    }
+}
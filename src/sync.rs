@@ -0,0 +1,374 @@
+//! Syncing the records cited in a local BibTeX file into an ADS library.
+//!
+//! Each entry in the file is resolved to a bibcode using, in order, its
+//! `adsurl` field or ADS-style citation key, its DOI or arXiv identifier, and
+//! finally the ADS reference resolver with a reference string built from the
+//! other fields. Any resolved records that aren't already in the library are
+//! added to it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # fn run() -> adsabs::Result<()> {
+//! use adsabs::{sync::{find_library, LibrarySync}, Ads};
+//! let client = Ads::new("ADS_API_TOKEN")?;
+//! let library = find_library(&client, "Thesis")?.expect("no library named Thesis");
+//! let report = LibrarySync::new(&client, &library).run_file("thesis.bib")?;
+//! println!("added {} records", report.added.len());
+//! for key in report.unmatched {
+//!     println!("couldn't resolve {}", key);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::bibtex::{parse_bibtex, BibEntry};
+use crate::error::Result;
+use crate::Ads;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+// The number of identifiers included in a single search query
+const IDENTIFIER_BATCH_SIZE: usize = 100;
+
+// The number of library records requested per page
+const LIBRARY_PAGE_SIZE: u64 = 1000;
+
+fn normalize(identifier: &str) -> String {
+    let identifier = identifier.trim().to_lowercase();
+    match identifier.strip_prefix("arxiv:") {
+        Some(rest) => rest.to_owned(),
+        None => identifier,
+    }
+}
+
+/// The outcome of [`LibrarySync::run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// The citation keys of the resolved entries, with their bibcodes.
+    pub matched: Vec<(String, String)>,
+    /// The bibcodes that were added to the library, or that would have been
+    /// added in a dry run.
+    pub added: Vec<String>,
+    /// The citation keys of the entries that couldn't be resolved.
+    pub unmatched: Vec<String>,
+}
+
+/// Adds the records cited in a BibTeX file to an ADS library.
+#[derive(Debug)]
+#[must_use]
+pub struct LibrarySync<'ads> {
+    client: &'ads Ads,
+    library: String,
+    dry_run: bool,
+    resolve_references: bool,
+}
+
+impl<'ads> LibrarySync<'ads> {
+    /// Sync into the library with the given ID; see [`find_library`] for
+    /// looking up a library by name.
+    pub fn new(client: &'ads Ads, library: &str) -> Self {
+        Self {
+            client,
+            library: library.to_owned(),
+            dry_run: false,
+            resolve_references: true,
+        }
+    }
+
+    /// Resolve the entries and report what would be added, without changing
+    /// the library.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Whether to use the reference resolver for entries without an
+    /// identifier; the default is `true`.
+    pub fn resolve_references(mut self, resolve_references: bool) -> Self {
+        self.resolve_references = resolve_references;
+        self
+    }
+
+    /// Parse a BibTeX file and sync its entries.
+    pub fn run_file<P: AsRef<Path>>(&self, path: P) -> Result<SyncReport> {
+        let contents = std::fs::read_to_string(path)?;
+        self.run(&parse_bibtex(&contents)?)
+    }
+
+    /// Resolve the entries to bibcodes and add any that are missing from the
+    /// library.
+    pub fn run(&self, entries: &[BibEntry]) -> Result<SyncReport> {
+        let bibcodes = self.resolve(entries)?;
+        let mut report = SyncReport::default();
+        for entry in entries {
+            match bibcodes.get(&entry.key) {
+                Some(bibcode) => report.matched.push((entry.key.clone(), bibcode.clone())),
+                None => report.unmatched.push(entry.key.clone()),
+            }
+        }
+
+        let existing = library_bibcodes(self.client, &self.library)?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let mut seen = HashSet::new();
+        report.added = report
+            .matched
+            .iter()
+            .map(|(_, bibcode)| bibcode)
+            .filter(|&bibcode| !existing.contains(bibcode) && seen.insert(bibcode))
+            .cloned()
+            .collect();
+        if !self.dry_run && !report.added.is_empty() {
+            let _: serde_json::Value = self.client.post_json(
+                format!("biblib/documents/{}", self.library),
+                &serde_json::json!({"bibcode": report.added, "action": "add"}),
+            )?;
+        }
+        Ok(report)
+    }
+
+    // Map citation keys to bibcodes
+    fn resolve(&self, entries: &[BibEntry]) -> Result<HashMap<String, String>> {
+        let mut bibcodes = HashMap::new();
+        let mut identifiers = Vec::new();
+        for entry in entries {
+            if let Some(bibcode) = entry.bibcode() {
+                bibcodes.insert(entry.key.clone(), bibcode);
+                continue;
+            }
+            if let Some(doi) = entry.doi() {
+                identifiers.push((normalize(doi), &entry.key));
+            }
+            if let Some(arxiv) = entry.arxiv_id() {
+                identifiers.push((normalize(arxiv), &entry.key));
+            }
+        }
+
+        for chunk in identifiers.chunks(IDENTIFIER_BATCH_SIZE) {
+            let query = chunk
+                .iter()
+                .map(|(identifier, _)| format!("\"{}\"", identifier.replace('"', "")))
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            let docs = self
+                .client
+                .search(&format!("identifier:({})", query))
+                .fl("bibcode,identifier,doi")
+                .iter_docs()
+                .limit(chunk.len() as u64);
+            for doc in docs {
+                let doc = doc?;
+                let bibcode = match doc.bibcode {
+                    Some(bibcode) => bibcode,
+                    None => continue,
+                };
+                let found = doc
+                    .identifier
+                    .iter()
+                    .flatten()
+                    .chain(doc.doi.iter().flatten())
+                    .map(|identifier| normalize(identifier))
+                    .collect::<HashSet<_>>();
+                for (identifier, key) in chunk {
+                    if found.contains(identifier) {
+                        bibcodes
+                            .entry((*key).clone())
+                            .or_insert_with(|| bibcode.clone());
+                    }
+                }
+            }
+        }
+
+        if self.resolve_references {
+            let references = entries
+                .iter()
+                .filter(|entry| !bibcodes.contains_key(&entry.key))
+                .filter_map(|entry| Some((entry.reference()?, &entry.key)))
+                .collect::<Vec<_>>();
+            if !references.is_empty() {
+                let strings = references.iter().map(|(r, _)| r).collect::<Vec<_>>();
+                let resolved: Resolved = self.client.post_json(
+                    "reference/text",
+                    &serde_json::json!({ "reference": strings }),
+                )?;
+                for ((_, key), resolved) in references.iter().zip(resolved.resolved) {
+                    if let Some(bibcode) = resolved.bibcode() {
+                        bibcodes.insert((*key).clone(), bibcode);
+                    }
+                }
+            }
+        }
+        Ok(bibcodes)
+    }
+}
+
+/// The bibcodes of all the records in the library with the given ID, in the
+/// order that they are listed in the library.
+pub fn library_bibcodes(client: &Ads, library: &str) -> Result<Vec<String>> {
+    let mut bibcodes = Vec::new();
+    let mut start = 0;
+    loop {
+        let page: LibraryPage = client.get_json(
+            format!("biblib/libraries/{}", library),
+            Some(&[("start", start), ("rows", LIBRARY_PAGE_SIZE)]),
+        )?;
+        let count = page.documents.len() as u64;
+        bibcodes.extend(page.documents);
+        start += count;
+        if count == 0 || start >= page.metadata.num_documents {
+            return Ok(bibcodes);
+        }
+    }
+}
+
+/// Find the ID of the library with the given name, among those that the
+/// user has access to.
+pub fn find_library(client: &Ads, name: &str) -> Result<Option<String>> {
+    let libraries: Libraries = client.get_json("biblib/libraries", None::<&()>)?;
+    Ok(libraries
+        .libraries
+        .into_iter()
+        .find(|library| library.name == name)
+        .map(|library| library.id))
+}
+
+#[derive(Deserialize)]
+struct Libraries {
+    libraries: Vec<Library>,
+}
+
+#[derive(Deserialize)]
+struct Library {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct LibraryPage {
+    #[serde(default)]
+    documents: Vec<String>,
+    metadata: LibraryMetadata,
+}
+
+#[derive(Deserialize)]
+struct LibraryMetadata {
+    num_documents: u64,
+}
+
+#[derive(Deserialize)]
+struct Resolved {
+    resolved: Vec<ResolvedReference>,
+}
+
+#[derive(Deserialize)]
+struct ResolvedReference {
+    #[serde(default)]
+    bibcode: Option<String>,
+    #[serde(default)]
+    score: Option<serde_json::Value>,
+}
+
+impl ResolvedReference {
+    fn bibcode(self) -> Option<String> {
+        let score = match &self.score {
+            Some(serde_json::Value::String(score)) => score.parse().unwrap_or(0.0),
+            Some(serde_json::Value::Number(score)) => score.as_f64().unwrap_or(0.0),
+            _ => 1.0,
+        };
+        self.bibcode
+            .filter(|bibcode| score > 0.0 && bibcode.chars().any(|c| c != '.'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    const BIB: &str = r#"
+        @comment{ignored {nested}}
+        @ARTICLE{2013PASP..125..306F,
+            author = {{Foreman-Mackey}, Daniel and {Hogg}, David W.},
+            title = "{emcee: The MCMC Hammer}",
+            year = 2013,
+        }
+        @misc(joss,
+            doi = {10.21105/joss.01864},
+            note = "part " # {two}
+        )
+        @article{preprint, eprint = {arXiv:1202.3665}, archivePrefix = {arXiv}}
+        @article{reference,
+            author = {Hogg, D. W.},
+            year = {2010},
+            journal = {ApJ},
+            volume = {725},
+            pages = {2166}
+        }
+        @book{unknown, title = {Nothing}}
+    "#;
+
+    #[test]
+    fn sync_library() {
+        let server = MockServer::start();
+        let search = server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/query")
+                .query_param("q", "identifier:(\"10.21105/joss.01864\" OR \"1202.3665\")");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({"response": {
+                    "numFound": 2,
+                    "start": 0,
+                    "docs": [
+                        {"bibcode": "2019JOSS....4.1864F", "doi": ["10.21105/JOSS.01864"]},
+                        {"bibcode": "2013PASP..125..306F", "identifier": ["arXiv:1202.3665"]},
+                    ],
+                }}));
+        });
+        let resolver = server.mock(|when, then| {
+            when.method(POST)
+                .path("/reference/text")
+                .json_body(serde_json::json!({"reference": ["Hogg, D. W. 2010, ApJ, 725, 2166"]}));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({"resolved": [
+                    {"refstring": "Hogg", "bibcode": "2010ApJ...725.2166H", "score": "1.0"},
+                ]}));
+        });
+        let library = server.mock(|when, then| {
+            when.method(GET).path("/biblib/libraries/abc");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(serde_json::json!({
+                    "documents": ["2013PASP..125..306F"],
+                    "metadata": {"num_documents": 1},
+                }));
+        });
+        let add = server.mock(|when, then| {
+            when.method(POST)
+                .path("/biblib/documents/abc")
+                .json_body(serde_json::json!({
+                    "bibcode": ["2019JOSS....4.1864F", "2010ApJ...725.2166H"],
+                    "action": "add",
+                }));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"number_added": 2}"#);
+        });
+        let client = Ads::builder("token")
+            .base_url(&server.url("/"))
+            .build()
+            .unwrap();
+        let entries = parse_bibtex(BIB).unwrap();
+        let report = LibrarySync::new(&client, "abc").run(&entries).unwrap();
+        search.assert();
+        resolver.assert();
+        library.assert();
+        add.assert();
+        assert_eq!(report.added, ["2019JOSS....4.1864F", "2010ApJ...725.2166H"]);
+        assert_eq!(report.matched.len(), 4);
+        assert_eq!(report.unmatched, ["unknown"]);
+    }
+}
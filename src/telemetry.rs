@@ -0,0 +1,55 @@
+use crate::RateLimit;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A callback used to report [`RequestMetrics`]; see
+/// [`crate::AdsBuilder::on_request`].
+pub(crate) type Callback = Arc<dyn Fn(&RequestMetrics) + Send + Sync>;
+
+/// Telemetry describing a single request made to the API.
+///
+/// These are reported to the callback registered using
+/// [`crate::AdsBuilder::on_request`] after each request has completed, and
+/// they can be used to feed monitoring systems like Prometheus or StatsD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RequestMetrics {
+    /// The HTTP method, e.g. `GET`.
+    pub method: String,
+    /// The endpoint, relative to the base URL of the client, e.g.
+    /// `search/query`.
+    pub endpoint: String,
+    /// The time taken to receive the response headers.
+    pub latency: Duration,
+    /// The HTTP status code, or `None` if the request failed without a
+    /// response.
+    pub status: Option<u16>,
+    /// The size of the response body in bytes, if reported by the server using
+    /// the `Content-Length` header.
+    pub content_length: Option<u64>,
+    /// The rate limit status reported in the response headers.
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// The path of `url` relative to `base`, or the full path if `url` doesn't
+/// share the same base.
+pub(crate) fn endpoint(base: &reqwest::Url, url: &reqwest::Url) -> String {
+    url.path()
+        .strip_prefix(base.path())
+        .unwrap_or_else(|| url.path())
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_endpoint() {
+        let base = reqwest::Url::parse("https://api.adsabs.harvard.edu/v1/").unwrap();
+        let url = base.join("search/query?q=star").unwrap();
+        assert_eq!(endpoint(&base, &url), "search/query");
+        let other = reqwest::Url::parse("https://example.com/other").unwrap();
+        assert_eq!(endpoint(&base, &other), "/other");
+    }
+}
@@ -0,0 +1,314 @@
+//! Helpers for testing applications that use this crate without network
+//! access.
+//!
+//! This is only available with the `testing` feature. A [`MockApi`] serves
+//! canned responses to an otherwise normal [`Ads`] client, and records the
+//! requests that were made so that they can be checked in tests.
+//!
+//! # Example
+//!
+//! ```rust
+//! # fn run() -> adsabs::Result<()> {
+//! use adsabs::{search::Document, testing::MockApi};
+//! let docs = (0..25)
+//!     .map(|n| Document {
+//!         bibcode: Some(format!("2021Test...{:03}", n)),
+//!         ..Document::default()
+//!     })
+//!     .collect();
+//! let mock = MockApi::new().documents(docs);
+//! let client = mock.client();
+//! let found = client.search("star").iter_docs().collect::<adsabs::Result<Vec<_>>>()?;
+//! assert_eq!(found.len(), 25);
+//! assert_eq!(mock.requests()[0].endpoint, "search/query");
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+
+use crate::search::{Document, Response};
+use crate::{transport::Transport, Ads, AdsError, RateLimit, Result};
+use reqwest::blocking::{Client, Request};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+// The base URL of mock clients, so that request paths are the endpoints
+const MOCK_BASE_URL: &str = "http://mock.adsabs.invalid/";
+
+// The number of rows returned by the API when none are requested
+const DEFAULT_ROWS: usize = 10;
+
+/// A request received by a [`MockApi`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// The HTTP method, e.g. `GET`.
+    pub method: String,
+    /// The endpoint, relative to the base URL, e.g. `search/query`.
+    pub endpoint: String,
+    /// The query string parameters, in order.
+    pub query: Vec<(String, String)>,
+    /// The request body, if any.
+    pub body: Option<String>,
+}
+
+impl RecordedRequest {
+    /// The value of the first query parameter called `name`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A canned response for an endpoint.
+#[derive(Debug, Clone)]
+struct Route {
+    status: u16,
+    body: String,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    documents: Option<Vec<Document>>,
+    pages: Vec<Response>,
+    routes: HashMap<String, Route>,
+    rate_limit: Option<RateLimit>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// A fake API serving canned responses.
+///
+/// Search requests are answered using the records from
+/// [`MockApi::documents`], with pagination handled like the real API, or the
+/// pages from [`MockApi::page`]. Other endpoints, like exports, are answered
+/// using [`MockApi::route`], and everything else gets a `404` response.
+#[derive(Debug, Clone, Default)]
+pub struct MockApi {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockApi {
+    /// Build a new mock API with no canned responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer search requests using these records, paginated using the
+    /// `start` and `rows` parameters of each request.
+    #[must_use]
+    pub fn documents(self, documents: Vec<Document>) -> Self {
+        self.state().documents = Some(documents);
+        self
+    }
+
+    /// Answer search requests with this page when the `start` parameter of the
+    /// request matches the start of the page.
+    #[must_use]
+    pub fn page(self, page: Response) -> Self {
+        self.state().pages.push(page);
+        self
+    }
+
+    /// Answer requests to `endpoint` (relative to the base URL, e.g.
+    /// `export/bibtex`) with a status code and JSON body.
+    #[must_use]
+    pub fn route(self, endpoint: &str, status: u16, body: &str) -> Self {
+        let route = Route {
+            status,
+            body: body.to_owned(),
+        };
+        self.state().routes.insert(endpoint.to_owned(), route);
+        self
+    }
+
+    /// Include rate limit headers in every response.
+    #[must_use]
+    pub fn rate_limit(self, rate_limit: RateLimit) -> Self {
+        self.state().rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Build a client that sends its requests to this mock API.
+    pub fn client(&self) -> Ads {
+        let mut client = Ads::builder("mock-token")
+            .base_url(MOCK_BASE_URL)
+            .build()
+            .expect("a valid client configuration");
+        client.transport = Some(Arc::new(self.clone()));
+        client
+    }
+
+    /// The requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    fn respond(&self, request: &RecordedRequest) -> Result<Route> {
+        if let Some(route) = self.routes.get(&request.endpoint) {
+            return Ok(route.clone());
+        }
+        if request.endpoint != "search/query" || (self.documents.is_none() && self.pages.is_empty())
+        {
+            return Ok(error_route(
+                404,
+                &format!("no mock response for {}", request.endpoint),
+            ));
+        }
+        let start = parse_param(request, "start")?.unwrap_or(0);
+        let page = match &self.documents {
+            Some(documents) => {
+                let rows = parse_param(request, "rows")?.unwrap_or(DEFAULT_ROWS);
+                Response {
+                    num_found: documents.len() as u64,
+                    start: start as u64,
+                    docs: documents.iter().skip(start).take(rows).cloned().collect(),
+                }
+            }
+            None => match self.pages.iter().find(|page| page.start == start as u64) {
+                Some(page) => page.clone(),
+                None => {
+                    return Ok(error_route(
+                        400,
+                        &format!("no mock page starting at {}", start),
+                    ))
+                }
+            },
+        };
+        Ok(Route {
+            status: 200,
+            body: serde_json::json!({ "response": page }).to_string(),
+        })
+    }
+}
+
+impl Transport for MockApi {
+    fn execute(&self, _: &Client, request: Request) -> Result<reqwest::blocking::Response> {
+        let recorded = RecordedRequest {
+            method: request.method().to_string(),
+            endpoint: request.url().path().trim_start_matches('/').to_owned(),
+            query: request
+                .url()
+                .query_pairs()
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| String::from_utf8_lossy(body).into_owned()),
+        };
+        let mut state = self.state();
+        let route = state.respond(&recorded)?;
+        state.requests.push(recorded);
+
+        let mut response = http::Response::builder()
+            .status(route.status)
+            .header("content-type", "application/json");
+        if let Some(rate_limit) = &state.rate_limit {
+            response = response
+                .header("x-ratelimit-limit", rate_limit.limit)
+                .header("x-ratelimit-remaining", rate_limit.remaining)
+                .header(
+                    "x-ratelimit-reset",
+                    crate::timestamp::to_unix(&rate_limit.reset).unwrap_or_default(),
+                );
+        }
+        let response = response
+            .body(route.body)
+            .map_err(|err| AdsError::Ads(err.to_string()))?;
+        Ok(response.into())
+    }
+}
+
+fn parse_param(request: &RecordedRequest, name: &str) -> Result<Option<usize>> {
+    request
+        .param(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| AdsError::Ads(format!("invalid {} parameter: {}", name, value)))
+        })
+        .transpose()
+}
+
+fn error_route(status: u16, msg: &str) -> Route {
+    Route {
+        status,
+        body: serde_json::json!({ "error": msg }).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs(count: usize) -> Vec<Document> {
+        (0..count)
+            .map(|n| Document {
+                id: Some(n.to_string()),
+                ..Document::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn paginates_documents() {
+        let mock = MockApi::new().documents(docs(25));
+        let client = mock.client();
+        let found = client
+            .search("star")
+            .rows(10)
+            .iter_docs()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(found.len(), 25);
+        assert_eq!(found[24].id.as_deref(), Some("24"));
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].param("q"), Some("star"));
+        assert_eq!(requests[1].param("start"), Some("10"));
+    }
+
+    #[test]
+    fn canned_pages_and_routes() {
+        let page = Response {
+            num_found: 100,
+            start: 0,
+            docs: docs(2),
+        };
+        let mock =
+            MockApi::new()
+                .page(page)
+                .route("export/bibtex", 200, r#"{"export": "@ARTICLE{...}"}"#);
+        let client = mock.client();
+        assert_eq!(client.search("star").send().unwrap().num_found, 100);
+        assert!(client.search("star").start(50).send().is_err());
+        let export: serde_json::Value = client.get_json("export/bibtex", None::<&()>).unwrap();
+        assert_eq!(export["export"], "@ARTICLE{...}");
+        assert!(matches!(
+            client.get_json::<serde_json::Value, _, _>("metrics", None::<&()>),
+            Err(AdsError::Api { status: 404, .. })
+        ));
+    }
+
+    #[test]
+    fn rate_limit_headers() {
+        let rate_limit = RateLimit {
+            limit: 5000,
+            remaining: 0,
+            reset: crate::timestamp::from_unix(1_635_724_800).unwrap(),
+        };
+        let client = MockApi::new()
+            .documents(docs(1))
+            .rate_limit(rate_limit)
+            .client();
+        client.search("star").send().unwrap();
+        assert!(client.rate_limit().unwrap().is_exhausted());
+    }
+}
@@ -0,0 +1,157 @@
+//! The type used for dates and times, which depends on the enabled features.
+//!
+//! With the default `chrono` feature, timestamps are [`chrono::DateTime`]s in
+//! UTC. Without it, the `time` feature selects [`time::OffsetDateTime`], and if
+//! neither feature is enabled, timestamps are kept as the RFC 3339 strings
+//! returned by the API.
+
+#[cfg(feature = "chrono")]
+/// A date and time, in UTC.
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+/// A date and time, in UTC.
+pub type Timestamp = time::OffsetDateTime;
+
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+/// A date and time, as an RFC 3339 string like `2021-10-24T07:56:53Z`.
+pub type Timestamp = String;
+
+/// Convert a Unix timestamp, in seconds, to a [`Timestamp`].
+#[cfg(feature = "chrono")]
+pub(crate) fn from_unix(seconds: i64) -> Option<Timestamp> {
+    use chrono::TimeZone;
+    chrono::Utc.timestamp_opt(seconds, 0).single()
+}
+
+/// Convert a Unix timestamp, in seconds, to a [`Timestamp`].
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub(crate) fn from_unix(seconds: i64) -> Option<Timestamp> {
+    time::OffsetDateTime::from_unix_timestamp(seconds).ok()
+}
+
+/// Convert a Unix timestamp, in seconds, to a [`Timestamp`].
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+pub(crate) fn from_unix(seconds: i64) -> Option<Timestamp> {
+    // Days to civil dates, following http://howardhinnant.github.io/date_algorithms.html
+    let days = seconds.div_euclid(86_400);
+    let time = seconds.rem_euclid(86_400);
+    let era = (days + 719_468).div_euclid(146_097);
+    let doe = (days + 719_468).rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    if !(0..=9999).contains(&year) {
+        return None;
+    }
+    Some(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    ))
+}
+
+/// The Unix timestamp, in seconds, for a [`Timestamp`].
+#[cfg(feature = "chrono")]
+pub(crate) fn to_unix(timestamp: &Timestamp) -> Option<i64> {
+    Some(timestamp.timestamp())
+}
+
+/// The Unix timestamp, in seconds, for a [`Timestamp`].
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub(crate) fn to_unix(timestamp: &Timestamp) -> Option<i64> {
+    Some(timestamp.unix_timestamp())
+}
+
+/// The Unix timestamp, in seconds, for a [`Timestamp`].
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+pub(crate) fn to_unix(timestamp: &Timestamp) -> Option<i64> {
+    // Only the format produced by `from_unix` is supported
+    let field = |range: std::ops::Range<usize>| timestamp.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hours, minutes, seconds) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(days * 86_400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+/// The current Unix timestamp, in seconds.
+pub(crate) fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as i64)
+}
+
+/// The time remaining until a [`Timestamp`], which is zero if it has already
+/// passed.
+pub(crate) fn until(timestamp: &Timestamp) -> Option<std::time::Duration> {
+    let seconds = to_unix(timestamp)?.saturating_sub(now_unix()).max(0);
+    Some(std::time::Duration::from_secs(seconds as u64))
+}
+
+/// Serialization for `Option<Timestamp>` fields, in the RFC 3339 format used
+/// by the API.
+pub(crate) mod option {
+    #[cfg(all(feature = "time", not(feature = "chrono")))]
+    pub(crate) use time::serde::rfc3339::option::{deserialize, serialize};
+
+    #[cfg(any(feature = "chrono", not(feature = "time")))]
+    pub(crate) use self::default::{deserialize, serialize};
+
+    #[cfg(any(feature = "chrono", not(feature = "time")))]
+    mod default {
+        use super::super::Timestamp;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub(crate) fn serialize<S: Serializer>(
+            value: &Option<Timestamp>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            value.serialize(serializer)
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Timestamp>, D::Error> {
+            Option::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_round_trip() {
+        for &seconds in &[0, 951_782_400, 1_635_724_800, 4_107_542_399] {
+            let timestamp = from_unix(seconds).unwrap();
+            assert_eq!(to_unix(&timestamp), Some(seconds));
+        }
+    }
+
+    #[test]
+    fn deserialize_option() {
+        #[derive(serde::Deserialize)]
+        struct Record {
+            #[serde(default, with = "option")]
+            date: Option<Timestamp>,
+        }
+        let record: Record = serde_json::from_str(r#"{"date": "2021-10-24T07:56:53Z"}"#).unwrap();
+        assert_eq!(to_unix(&record.date.unwrap()), Some(1_635_062_213));
+        let record: Record = serde_json::from_str("{}").unwrap();
+        assert!(record.date.is_none());
+    }
+}
@@ -0,0 +1,8 @@
+use crate::Result;
+use reqwest::blocking::{Client, Request, Response};
+
+/// A hook for replacing how requests are sent, used for recording and
+/// replaying responses, and for serving canned responses in tests.
+pub(crate) trait Transport: Send + Sync {
+    fn execute(&self, client: &Client, request: Request) -> Result<Response>;
+}